@@ -1,6 +1,7 @@
 use super::*;
 use crate::{
     component::Handled,
+    dispatch::network_status_port::NetworkStatusPort,
     messaging::{DispatchEnvelope, MsgEnvelope, NetMessage, UnpackError},
 };
 use std::{
@@ -149,6 +150,13 @@ pub trait Actor {
 pub trait Dispatcher: ActorRaw<Message = DispatchEnvelope> {
     /// Returns the system path for this dispatcher
     fn system_path(&mut self) -> SystemPath;
+
+    /// Returns the port this dispatcher provides [NetworkStatus](crate::prelude::NetworkStatus) indications on, if any
+    ///
+    /// The default implementation returns `None`, so components connecting to it never receive any indications.
+    fn network_status_port(&mut self) -> Option<&mut ProvidedPort<NetworkStatusPort>> {
+        None
+    }
 }
 
 impl<A, M: MessageBounds> ActorRaw for A
@@ -7,8 +7,9 @@ use std::{
     convert::TryFrom,
     error::Error,
     fmt::{self, Debug},
-    net::{AddrParseError, IpAddr, SocketAddr},
+    net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr},
     ops::Div,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 use uuid::Uuid;
@@ -21,17 +22,35 @@ use uuid::Uuid;
 /// Dispatcher implementations are not required to implement all protocols.
 /// Check your concrete implementation, before selecting an arbitrary protocol.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-#[repr(u8)]
 pub enum Transport {
     /// Local reflection only, no network messages involved
-    LOCAL = 0b00,
+    LOCAL,
     /// Send messages over TCP
-    TCP = 0b01,
+    TCP,
     /// Send messages as UDP datagrams
-    UDP = 0b10,
+    UDP,
+    /// Send messages as UDP datagrams, which are acknowledged and retransmitted until they arrive
+    ///
+    /// Messages to the same remote system are delivered in order, like over TCP,
+    /// but without establishing a connection first.
+    RUDP,
+    /// Send messages over a Unix domain socket, to systems on the same host
+    ///
+    /// Messages are framed and flow-controlled exactly like over TCP,
+    /// see [with_unix_socket](SystemPath::with_unix_socket) for addressing such systems.
+    UDS,
+    /// Send messages over the custom transport registered with this id,
+    /// see [register_transport](crate::prelude::NetworkConfig::register_transport)
+    ///
+    /// Ids range from 0 to [MAX_CUSTOM_ID](Transport::MAX_CUSTOM_ID).
+    /// As a URI, such a transport looks like `"custom3://127.0.0.1:4000"`.
+    Custom(u8),
 }
 
 impl Transport {
+    /// The largest id of a [custom transport](Transport::Custom)
+    pub const MAX_CUSTOM_ID: u8 = 0b1111;
+
     /// Returns `true` if this is an instance of [Transport::LOCAL](Transport::LOCAL)
     pub fn is_local(&self) -> bool {
         matches!(*self, Transport::LOCAL)
@@ -49,6 +68,9 @@ impl fmt::Display for Transport {
             &Transport::LOCAL => write!(fmt, "local"),
             &Transport::TCP => write!(fmt, "tcp"),
             &Transport::UDP => write!(fmt, "udp"),
+            &Transport::RUDP => write!(fmt, "rudp"),
+            &Transport::UDS => write!(fmt, "uds"),
+            &Transport::Custom(id) => write!(fmt, "custom{}", id),
         }
     }
 }
@@ -61,6 +83,14 @@ impl FromStr for Transport {
             "local" => Ok(Transport::LOCAL),
             "tcp" => Ok(Transport::TCP),
             "udp" => Ok(Transport::UDP),
+            "rudp" => Ok(Transport::RUDP),
+            "uds" => Ok(Transport::UDS),
+            _ if s.starts_with("custom") => s["custom".len()..]
+                .parse::<u8>()
+                .ok()
+                .filter(|id| *id <= Transport::MAX_CUSTOM_ID)
+                .map(Transport::Custom)
+                .ok_or(TransportParseError),
             _ => Err(TransportParseError),
         }
     }
@@ -78,7 +108,7 @@ impl fmt::Display for TransportParseError {
 
 impl Error for TransportParseError {
     fn description(&self) -> &str {
-        "Transport must be one of [local,tcp,udp]"
+        "Transport must be one of [local,tcp,udp,rudp,uds,custom<id>]"
     }
 }
 
@@ -135,14 +165,124 @@ impl From<AddrParseError> for PathParseError {
     }
 }
 
+/// Returned by the deprecated [SystemPath::address](SystemPath::address) for paths which are not addressed by IP
+static UNSPECIFIED_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+/// The network address of a [SystemPath](SystemPath)
+///
+/// Domain names are resolved to an IP address by the dispatcher when connecting to the system,
+/// and resolved again whenever the connection must be re-established.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Address {
+    /// An IPv4 or IPv6 address
+    Ip(IpAddr),
+    /// A domain name, such as `node-a` or `node-a.example.com`
+    Domain(String),
+    /// The file system path of a Unix domain socket, such as `/tmp/node-a.sock`
+    Unix(PathBuf),
+}
+
+impl Address {
+    /// Returns the IP address, if this is an instance of [Address::Ip](Address::Ip)
+    pub fn ip(&self) -> Option<&IpAddr> {
+        match self {
+            Address::Ip(ip) => Some(ip),
+            _ => None,
+        }
+    }
+
+    /// Returns the domain name, if this is an instance of [Address::Domain](Address::Domain)
+    pub fn domain(&self) -> Option<&str> {
+        match self {
+            Address::Domain(domain) => Some(domain),
+            _ => None,
+        }
+    }
+
+    /// Returns the socket path, if this is an instance of [Address::Unix](Address::Unix)
+    pub fn unix_path(&self) -> Option<&Path> {
+        match self {
+            Address::Unix(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl From<IpAddr> for Address {
+    fn from(ip: IpAddr) -> Self {
+        Address::Ip(ip)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Address::Ip(ip) => write!(fmt, "{}", ip),
+            Address::Domain(domain) => fmt.write_str(domain),
+            Address::Unix(path) => write!(fmt, "[{}]", path.display()),
+        }
+    }
+}
+
+/// Returns `true` if `domain` is a valid domain name
+///
+/// Domain names consist of labels separated by dots, where each label is between 1 and 63
+/// alphanumeric characters or hyphens, and does not start or end with a hyphen.
+/// The last label must not be numeric, so that malformed IP addresses are not mistaken for domain names.
+pub fn is_valid_domain(domain: &str) -> bool {
+    if domain.is_empty() || domain.len() > MAX_DOMAIN_LEN {
+        return false;
+    }
+    let valid_labels = domain.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    let tld = domain.rsplit('.').next().unwrap_or(domain);
+    let numeric_tld = tld.chars().all(|c| c.is_ascii_digit());
+    valid_labels && !numeric_tld
+}
+
+/// The maximum length of a domain name in a [SystemPath](SystemPath)
+pub const MAX_DOMAIN_LEN: usize = 253;
+
+/// Splits a Unix socket address, such as `[/tmp/node-a.sock]`, off the start of `s`,
+/// returning the address and the rest of `s`.
+///
+/// Socket paths are bracketed, as they contain path separators themselves.
+fn split_unix_socket(s: &str) -> Option<(Address, &str)> {
+    if !s.starts_with("[/") {
+        return None;
+    }
+    let end = s.find(']')?;
+    let path = PathBuf::from(&s[1..end]);
+    Some((Address::Unix(path), &s[end + 1..]))
+}
+
+/// Parses `host:port` into an [Address](Address) and port
+fn parse_host_port(s: &str) -> Result<(Address, u16), PathParseError> {
+    match SocketAddr::from_str(s) {
+        Ok(socket) => Ok((Address::Ip(socket.ip()), socket.port())),
+        Err(addr_err) => {
+            let (host, port) = s.rsplit_once(':').ok_or_else(|| addr_err.clone())?;
+            if !is_valid_domain(host) {
+                return Err(PathParseError::Addr(addr_err));
+            }
+            let port = u16::from_str(port).map_err(|_| PathParseError::Addr(addr_err))?;
+            Ok((Address::Domain(host.to_string()), port))
+        }
+    }
+}
+
 /// The part of an [ActorPath](ActorPath) that refers to the [KompactSystem](KompactSystem)
 ///
-/// As a URI, a `SystemPath` looks like `"tcp://127.0.0.1:8080"`, for example.
+/// As a URI, a `SystemPath` looks like `"tcp://127.0.0.1:8080"` or `"tcp://node-a:8080"`, for example.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SystemPath {
     protocol: Transport,
-    // TODO address could be IPv4, IPv6, or a domain name (not supported yet)
-    address: IpAddr,
+    address: Address,
     port: u16,
 }
 
@@ -151,7 +291,7 @@ impl SystemPath {
     pub fn new(protocol: Transport, address: IpAddr, port: u16) -> SystemPath {
         SystemPath {
             protocol,
-            address,
+            address: Address::Ip(address),
             port,
         }
     }
@@ -160,18 +300,70 @@ impl SystemPath {
     pub fn with_socket(protocol: Transport, socket: SocketAddr) -> SystemPath {
         SystemPath {
             protocol,
-            address: socket.ip(),
+            address: Address::Ip(socket.ip()),
             port: socket.port(),
         }
     }
 
+    /// Construct a new system path from individual parts using a domain name
+    ///
+    /// Make sure that the `domain` is [valid](is_valid_domain),
+    /// as it can neither be resolved nor serialised otherwise.
+    pub fn with_domain(protocol: Transport, domain: String, port: u16) -> SystemPath {
+        debug_assert!(is_valid_domain(&domain), "Invalid domain name: {}", domain);
+        SystemPath {
+            protocol,
+            address: Address::Domain(domain),
+            port,
+        }
+    }
+
+    /// Construct a new system path for the system listening on the Unix domain socket at `path`
+    ///
+    /// Such a system path uses the [UDS](Transport::UDS) transport and has no port.
+    /// As a URI, it looks like `"uds://[/tmp/node-a.sock]"`.
+    /// The system must have been configured to listen on the socket,
+    /// see [set_unix_socket_path](crate::prelude::NetworkConfig::set_unix_socket_path).
+    pub fn with_unix_socket(path: PathBuf) -> SystemPath {
+        SystemPath {
+            protocol: Transport::UDS,
+            address: Address::Unix(path),
+            port: 0,
+        }
+    }
+
+    /// Construct a new system path from individual parts using any kind of [Address](Address)
+    pub(crate) fn with_address(protocol: Transport, address: Address, port: u16) -> SystemPath {
+        SystemPath {
+            protocol,
+            address,
+            port,
+        }
+    }
+
     /// Returns a reference to the [Transport](Transport) protocol associated with with this system path
     pub fn protocol(&self) -> Transport {
         self.protocol
     }
 
     /// Returns a reference to the IP address associated with with this system path
+    ///
+    /// System paths which are not addressed by IP, but by a domain name or Unix domain socket,
+    /// return the unspecified address `0.0.0.0`. Use [host](SystemPath::host) to access any kind of address.
+    #[deprecated(
+        since = "0.11.0",
+        note = "Use host().ip() instead, which also handles system paths addressed by domain names or Unix domain sockets."
+    )]
     pub fn address(&self) -> &IpAddr {
+        match self.address {
+            Address::Ip(ref ip) => ip,
+            _ => &UNSPECIFIED_ADDRESS,
+        }
+    }
+
+    /// Returns a reference to the [address](Address) associated with with this system path,
+    /// which may be an IP address, a domain name or a Unix domain socket
+    pub fn host(&self) -> &Address {
         &self.address
     }
 
@@ -180,6 +372,12 @@ impl SystemPath {
         self.port
     }
 
+    /// Returns the [socket address](std::net::SocketAddr) of this system path,
+    /// if its address is an IP address and not a domain name
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        self.address.ip().map(|ip| SocketAddr::new(*ip, self.port))
+    }
+
     /// Create a named path starting with this system path and ending with the given string
     ///
     /// Paths created with this function will be validated to be a valid lookup path,
@@ -207,7 +405,10 @@ impl SystemPath {
 
 impl fmt::Display for SystemPath {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "{}://{}:{}", self.protocol, self.address, self.port)
+        match self.address {
+            Address::Unix(_) => write!(fmt, "{}://{}", self.protocol, self.address),
+            _ => write!(fmt, "{}://{}:{}", self.protocol, self.address, self.port),
+        }
     }
 }
 
@@ -221,11 +422,24 @@ pub trait SystemField {
         self.system().protocol()
     }
 
-    /// Returns the address used in the system path
+    /// Returns the IP address used in the system path
+    ///
+    /// System paths which are not addressed by IP return the unspecified address,
+    /// see [SystemPath::address](SystemPath::address).
+    #[deprecated(
+        since = "0.11.0",
+        note = "Use host().ip() instead, which also handles system paths addressed by domain names or Unix domain sockets."
+    )]
+    #[allow(deprecated)]
     fn address(&self) -> &IpAddr {
         &self.system().address()
     }
 
+    /// Returns the [address](Address) used in the system path
+    fn host(&self) -> &Address {
+        self.system().host()
+    }
+
     /// Returns the port used in the system path
     fn port(&self) -> u16 {
         self.system().port()
@@ -522,6 +736,11 @@ impl ActorPath {
         self.set_protocol(Transport::UDP);
     }
 
+    /// Sets the transport protocol for this actor path to reliable UDP
+    pub fn via_rudp(&mut self) {
+        self.set_protocol(Transport::RUDP);
+    }
+
     /// Sets the transport protocol for this actor path to TCP
     pub fn via_tcp(&mut self) {
         self.set_protocol(Transport::TCP);
@@ -729,16 +948,28 @@ impl FromStr for UniquePath {
             return Err(PathParseError::Form(s.to_string()));
         }
         let proto: Transport = parts[0].parse()?;
-        let parts: Vec<&str> = parts[1].split(UNIQUE_PATH_SEP).collect();
+        let (address, rest) = match split_unix_socket(parts[1]) {
+            Some((address, rest)) => (Some(address), rest),
+            None => (None, parts[1]),
+        };
+        let parts: Vec<&str> = rest.split(UNIQUE_PATH_SEP).collect();
         // parts: [IP:port]#[UUID]
         if parts.len() != 2 {
             return Err(PathParseError::Form(s.to_string()));
         }
-        let socket = SocketAddr::from_str(parts[0])?;
+        let (address, port) = match address {
+            Some(address) if parts[0].is_empty() => (address, 0),
+            Some(_) => return Err(PathParseError::Form(s.to_string())),
+            None => parse_host_port(parts[0])?,
+        };
         let uuid =
             Uuid::from_str(parts[1]).map_err(|_parse_err| PathParseError::Form(s.to_string()))?;
-
-        Ok(UniquePath::with_socket(proto, socket, uuid))
+        let system = SystemPath {
+            protocol: proto,
+            address,
+            port,
+        };
+        Ok(UniquePath::with_system(system, uuid))
     }
 }
 
@@ -809,6 +1040,24 @@ impl NamedPath {
         NamedPath { system, path }
     }
 
+    /// Construct a named path addressing the actor at `path` on all systems in the multicast `group`
+    ///
+    /// A message sent to this path is sent as a single UDP datagram to the group,
+    /// and delivered to the actor registered at `path` on every system which joined it,
+    /// for example via [join_multicast_group](crate::prelude::KompactSystem::join_multicast_group).
+    ///
+    /// # Panics
+    ///
+    /// If `group` is not a multicast address.
+    pub fn multicast(group: SocketAddr, path: Vec<String>) -> NamedPath {
+        assert!(
+            group.ip().is_multicast(),
+            "{} is not a multicast address",
+            group
+        );
+        NamedPath::with_socket(Transport::UDP, group, path)
+    }
+
     /// Returns a reference to the path vector
     pub fn path_ref(&self) -> &[String] {
         &self.path
@@ -874,18 +1123,31 @@ impl FromStr for NamedPath {
             return Err(PathParseError::Form(s.to_string()));
         }
         let proto: Transport = s1[0].parse()?;
-        let mut s2: Vec<&str> = s1[1].split(PATH_SEP).collect();
+        let (address, rest) = match split_unix_socket(s1[1]) {
+            Some((address, rest)) => (Some(address), rest),
+            None => (None, s1[1]),
+        };
+        let mut s2: Vec<&str> = rest.split(PATH_SEP).collect();
         if s2.is_empty() {
             return Err(PathParseError::Form(s.to_string()));
         }
-        let socket = SocketAddr::from_str(s2[0])?;
+        let (address, port) = match address {
+            Some(address) if s2[0].is_empty() => (address, 0),
+            Some(_) => return Err(PathParseError::Form(s.to_string())),
+            None => parse_host_port(s2[0])?,
+        };
         let path: Vec<String> = if s2.len() > 1 {
             s2.split_off(1).into_iter().map(|v| v.to_string()).collect()
         } else {
             Vec::default()
         };
         validate_lookup_path(&path)?;
-        Ok(NamedPath::with_socket(proto, socket, path))
+        let system = SystemPath {
+            protocol: proto,
+            address,
+            port,
+        };
+        Ok(NamedPath::with_system(system, path))
     }
 }
 
@@ -1039,4 +1301,75 @@ mod tests {
         assert_eq!(ref1, ref1_deser);
         assert_eq!(ref1, ref1_deser2);
     }
+
+    #[test]
+    fn actor_path_domain_strings() {
+        let named = ActorPath::from_str("tcp://node-a:4000/foo").expect("a proper path");
+        let expected = SystemPath::with_domain(Transport::TCP, "node-a".to_string(), 4000);
+        assert_eq!(named.system(), &expected);
+        assert_eq!(named.host().domain(), Some("node-a"));
+        assert_eq!(named.to_string(), "tcp://node-a:4000/foo");
+
+        let id = Uuid::new_v4();
+        let unique_string = format!("tcp://node-a.example.com:4000#{}", id);
+        let unique = ActorPath::from_str(&unique_string).expect("a proper path");
+        assert_eq!(
+            unique,
+            ActorPath::Unique(UniquePath::with_system(
+                SystemPath::with_domain(Transport::TCP, "node-a.example.com".to_string(), 4000),
+                id
+            ))
+        );
+        assert_eq!(unique.to_string(), unique_string);
+    }
+
+    #[test]
+    fn actor_path_unix_socket_strings() {
+        let system = SystemPath::with_unix_socket(PathBuf::from("/tmp/node-a.sock"));
+        let named = ActorPath::from_str("uds://[/tmp/node-a.sock]/foo/bar").expect("a proper path");
+        assert_eq!(named.system(), &system);
+        assert_eq!(
+            named.host().unix_path(),
+            Some(Path::new("/tmp/node-a.sock"))
+        );
+        assert_eq!(named.to_string(), "uds://[/tmp/node-a.sock]/foo/bar");
+
+        let id = Uuid::new_v4();
+        let unique_string = format!("uds://[/tmp/node-a.sock]#{}", id);
+        let unique = ActorPath::from_str(&unique_string).expect("a proper path");
+        assert_eq!(
+            unique,
+            ActorPath::Unique(UniquePath::with_system(system, id))
+        );
+        assert_eq!(unique.to_string(), unique_string);
+
+        assert!(ActorPath::from_str("uds://[/tmp/node-a.sock]:4000/foo").is_err());
+    }
+
+    #[test]
+    fn actor_path_custom_transport_strings() {
+        let named = ActorPath::from_str("custom3://127.0.0.1:4000/foo").expect("a proper path");
+        assert_eq!(named.protocol(), Transport::Custom(3));
+        assert_eq!(named.to_string(), "custom3://127.0.0.1:4000/foo");
+
+        assert!(ActorPath::from_str("custom://127.0.0.1:4000/foo").is_err());
+        assert!(ActorPath::from_str("custom16://127.0.0.1:4000/foo").is_err());
+    }
+
+    #[test]
+    fn actor_path_invalid_addresses() {
+        for invalid in &[
+            "tcp://node_a:4000/foo",
+            "tcp://-node-a:4000/foo",
+            "tcp://node-a/foo",
+            "tcp://node-a:70000/foo",
+            "tcp://127.0.0.256:4000/foo",
+            "tcp://:4000/foo",
+        ] {
+            match ActorPath::from_str(invalid) {
+                Err(PathParseError::Addr(_)) => (), // ok
+                res => panic!("Expected address error for {}, got {:?}", invalid, res),
+            }
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use super::*;
 
 use crate::{
-    messaging::RegistrationResult,
+    messaging::{ConnectionInfo, RegistrationResult},
     routing::groups::StorePolicy,
     timer::timer_manager::CanCancelTimers,
 };
+use std::net::SocketAddr;
 
 /// The [SystemHandle](SystemHandle) provided by a [ComponentContext](ComponentContext)
 pub struct ContextSystemHandle {
@@ -122,10 +123,42 @@ impl SystemHandle for ContextSystemHandle {
         self.component.system().system_path()
     }
 
+    fn incarnation(&self) -> Option<Uuid> {
+        Some(self.component.system().incarnation())
+    }
+
     fn deadletter_ref(&self) -> ActorRef<Never> {
         self.component.system().actor_ref()
     }
 
+    fn join_multicast_group(&self, group: SocketAddr) -> () {
+        self.component.system().join_multicast_group(group)
+    }
+
+    fn leave_multicast_group(&self, group: SocketAddr) -> () {
+        self.component.system().leave_multicast_group(group)
+    }
+
+    fn leave_remote(&self, remote: SystemPath) -> () {
+        self.component.system().leave_remote(remote)
+    }
+
+    fn leave_all_remotes(&self) -> () {
+        self.component.system().leave_all_remotes()
+    }
+
+    fn connect_remote(&self, remote: SystemPath) -> () {
+        self.component.system().connect_remote(remote)
+    }
+
+    fn connections(&self) -> KFuture<Vec<ConnectionInfo>> {
+        self.component.system().connections()
+    }
+
+    fn close_connection(&self, remote: SystemPath) -> () {
+        self.component.system().close_connection(remote)
+    }
+
     fn spawn<R: Send + 'static>(
         &self,
         future: impl futures::Future<Output = R> + 'static + Send,
@@ -1,5 +1,6 @@
 use super::*;
 use crate::{
+    dispatch::network_status_port::NetworkStatusPort,
     messaging::{DispatchEnvelope, NetMessage},
     timer::timer_manager::TimerRefFactory,
 };
@@ -41,6 +42,14 @@ impl SystemComponents for DefaultComponents {
         self.dispatcher.on_definition(|cd| cd.system_path())
     }
 
+    fn connect_network_status_port(&self, required: &mut RequiredPort<NetworkStatusPort>) -> () {
+        self.dispatcher.on_definition(|cd| {
+            if let Some(port) = cd.network_status_port() {
+                biconnect_ports(port, required);
+            }
+        });
+    }
+
     fn start(&self, system: &KompactSystem) -> () {
         system.start(&self.deadletter_box);
         system.start(&self.dispatcher);
@@ -117,6 +126,14 @@ where
         self.dispatcher.on_definition(|cd| cd.system_path())
     }
 
+    fn connect_network_status_port(&self, required: &mut RequiredPort<NetworkStatusPort>) -> () {
+        self.dispatcher.on_definition(|cd| {
+            if let Some(port) = cd.network_status_port() {
+                biconnect_ports(port, required);
+            }
+        });
+    }
+
     fn start(&self, system: &KompactSystem) -> () {
         system.start(&self.deadletter_box);
         system.start(&self.dispatcher);
@@ -189,6 +206,8 @@ impl ComponentLifecycle for DeadletterBox {
 pub struct LocalDispatcher {
     ctx: ComponentContext<LocalDispatcher>,
     notify_ready: Option<KPromise<()>>,
+    /// Never triggers any indications, as there are no remote connections
+    network_status_port: ProvidedPort<NetworkStatusPort>,
 }
 
 impl LocalDispatcher {
@@ -200,6 +219,7 @@ impl LocalDispatcher {
         LocalDispatcher {
             ctx: ComponentContext::uninitialised(),
             notify_ready: Some(notify_ready),
+            network_status_port: ProvidedPort::uninitialised(),
         }
     }
 }
@@ -208,7 +228,12 @@ impl Actor for LocalDispatcher {
     type Message = DispatchEnvelope;
 
     fn receive_local(&mut self, msg: Self::Message) -> Handled {
-        use crate::messaging::{RegistrationEnvelope, RegistrationError, RegistrationPromise};
+        use crate::messaging::{
+            ConnectionRequest,
+            RegistrationEnvelope,
+            RegistrationError,
+            RegistrationPromise,
+        };
         warn!(
             self.ctx.log(),
             "LocalDispatcher received {:?}, but doesn't know what to do with it (hint: implement dispatching ;)",
@@ -221,6 +246,10 @@ impl Actor for LocalDispatcher {
                         error!(self.ctx.log(), "Could not notify listeners: {:?}", e)
                     });
             }
+        } else if let DispatchEnvelope::Connection(ConnectionRequest::List(p)) = msg {
+            // Without networking there are no connections
+            p.fulfil(Vec::new())
+                .unwrap_or_else(|e| error!(self.ctx.log(), "Could not notify listeners: {:?}", e));
         } else {
             error!(self.ctx.log(), "Ignoring message {:?}.", msg);
         }
@@ -240,8 +269,14 @@ impl Dispatcher for LocalDispatcher {
     fn system_path(&mut self) -> SystemPath {
         SystemPath::new(Transport::LOCAL, "127.0.0.1".parse().unwrap(), 0)
     }
+
+    fn network_status_port(&mut self) -> Option<&mut ProvidedPort<NetworkStatusPort>> {
+        Some(&mut self.network_status_port)
+    }
 }
 
+ignore_requests!(NetworkStatusPort, LocalDispatcher);
+
 impl ComponentLifecycle for LocalDispatcher {
     fn on_start(&mut self) -> Handled {
         debug!(self.ctx.log(), "Starting LocalDispatcher");
@@ -0,0 +1,202 @@
+use crate::{
+    actors::ActorPath,
+    serialisation::{
+        serialisation_ids,
+        Deserialiser,
+        SerError,
+        SerId,
+        SerIdBuf,
+        SerIdBufMut,
+        SerIdSize,
+        Serialisable,
+    },
+};
+use bytes::{Buf, BufMut};
+use std::{any::Any, convert::TryFrom};
+
+/// Configures where the [NetworkDispatcher](super::NetworkDispatcher) reports messages it could not deliver
+///
+/// See [NetworkConfig::set_delivery_failure_reporting](super::NetworkConfig::set_delivery_failure_reporting).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryFailureReporting {
+    /// Undeliverable messages are dropped without notice
+    Disabled,
+    /// A [DeliveryFailure](DeliveryFailure) is sent to the local sender of the undeliverable message
+    ///
+    /// Failures for messages whose sender is not on this system are sent to the deadletter box instead.
+    ToSender,
+    /// A [DeliveryFailure](DeliveryFailure) is sent to the system's [deadletter box](crate::prelude::DeadletterBox)
+    ToDeadletter,
+}
+
+// Deriving requires `#[default]` on the variant, which needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for DeliveryFailureReporting {
+    fn default() -> Self {
+        DeliveryFailureReporting::Disabled
+    }
+}
+
+/// The reason a message could not be delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DeliveryFailureReason {
+    /// The dispatcher gave up on connecting to the receiver's system
+    /// after exhausting its [RetryStrategy](crate::prelude::RetryStrategy)
+    RetriesExhausted = 0,
+    /// The queue for the receiver's system was full
+    ///
+    /// See [QueueOverflowPolicy](crate::prelude::QueueOverflowPolicy).
+    QueueOverflow = 1,
+    /// The connection to the receiver's system was lost before the message was sent,
+    /// and is not being re-established
+    ConnectionLost = 2,
+    /// The receiver's system was restarted while the message was queued,
+    /// and the message was addressed to its previous incarnation
+    ///
+    /// See [NetworkConfig::set_reject_stale_messages](super::NetworkConfig::set_reject_stale_messages).
+    StaleIncarnation = 3,
+}
+
+impl TryFrom<u8> for DeliveryFailureReason {
+    type Error = SerError;
+
+    fn try_from(x: u8) -> Result<Self, Self::Error> {
+        match x {
+            x if x == DeliveryFailureReason::RetriesExhausted as u8 => {
+                Ok(DeliveryFailureReason::RetriesExhausted)
+            }
+            x if x == DeliveryFailureReason::QueueOverflow as u8 => {
+                Ok(DeliveryFailureReason::QueueOverflow)
+            }
+            x if x == DeliveryFailureReason::ConnectionLost as u8 => {
+                Ok(DeliveryFailureReason::ConnectionLost)
+            }
+            x if x == DeliveryFailureReason::StaleIncarnation as u8 => {
+                Ok(DeliveryFailureReason::StaleIncarnation)
+            }
+            _ => Err(SerError::InvalidType(
+                "Unsupported DeliveryFailureReason".into(),
+            )),
+        }
+    }
+}
+
+/// A notification about a message the [NetworkDispatcher](super::NetworkDispatcher) could not deliver
+///
+/// Only sent if enabled via
+/// [NetworkConfig::set_delivery_failure_reporting](super::NetworkConfig::set_delivery_failure_reporting).
+/// The notification arrives as a network message from the `receiver` of the undeliverable message.
+///
+/// # Example
+///
+/// ```
+/// use kompact::prelude::*;
+///
+/// #[derive(ComponentDefinition)]
+/// struct Requester {
+///     ctx: ComponentContext<Self>,
+/// }
+/// ignore_lifecycle!(Requester);
+/// impl Actor for Requester {
+///     type Message = ();
+///     fn receive_local(&mut self, _msg: Self::Message) -> Handled {
+///         Handled::Ok
+///     }
+///     fn receive_network(&mut self, msg: NetMessage) -> Handled {
+///         match_deser!(msg; {
+///             failure: DeliveryFailure [DeliveryFailure] => {
+///                 warn!(self.log(), "Request to {} failed: {:?}", failure.receiver, failure.reason);
+///             },
+///             // handle replies...
+///         });
+///         Handled::Ok
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeliveryFailure {
+    /// The sender of the undeliverable message
+    pub sender: ActorPath,
+    /// The intended receiver of the undeliverable message
+    pub receiver: ActorPath,
+    /// The serialisation id of the undeliverable message
+    pub ser_id: SerId,
+    /// Why the message could not be delivered
+    pub reason: DeliveryFailureReason,
+}
+
+impl Serialisable for DeliveryFailure {
+    fn ser_id(&self) -> SerId {
+        serialisation_ids::DELIVERY_FAILURE
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        let sender = self.sender.size_hint()?;
+        let receiver = self.receiver.size_hint()?;
+        Some(sender + receiver + self.ser_id.size() + 1)
+    }
+
+    fn serialise(&self, mut buf: &mut dyn BufMut) -> Result<(), SerError> {
+        self.sender.serialise(buf)?;
+        self.receiver.serialise(buf)?;
+        buf.put_ser_id(self.ser_id);
+        buf.put_u8(self.reason as u8);
+        Ok(())
+    }
+
+    fn local(self: Box<Self>) -> Result<Box<dyn Any + Send>, Box<dyn Serialisable>> {
+        Ok(self)
+    }
+}
+
+impl Deserialiser<DeliveryFailure> for DeliveryFailure {
+    const SER_ID: SerId = serialisation_ids::DELIVERY_FAILURE;
+
+    fn deserialise(mut buf: &mut dyn Buf) -> Result<DeliveryFailure, SerError> {
+        let sender = ActorPath::deserialise(buf)?;
+        let receiver = ActorPath::deserialise(buf)?;
+        if buf.remaining() < SerId::default().size() + 1 {
+            return Err(SerError::InvalidData(
+                "Could not get ser_id and reason for DeliveryFailure".into(),
+            ));
+        }
+        let ser_id = buf.get_ser_id();
+        let reason = DeliveryFailureReason::try_from(buf.get_u8())?;
+        Ok(DeliveryFailure {
+            sender,
+            receiver,
+            ser_id,
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use bytes::BytesMut;
+    use uuid::Uuid;
+
+    #[test]
+    fn delivery_failure_serequiv() {
+        let failure = DeliveryFailure {
+            sender: ActorPath::Unique(UniquePath::new(
+                Transport::TCP,
+                "127.0.0.1".parse().unwrap(),
+                4000,
+                Uuid::new_v4(),
+            )),
+            receiver: "tcp://node-a:4000/foo".parse().unwrap(),
+            ser_id: 42,
+            reason: DeliveryFailureReason::QueueOverflow,
+        };
+        let size = failure.size_hint().expect("size hint");
+        let mut buf = BytesMut::with_capacity(size);
+        failure.serialise(&mut buf).expect("serialise");
+        assert_eq!(buf.len(), size);
+        let deserialised = DeliveryFailure::deserialise(&mut buf).expect("deserialise");
+        assert_eq!(failure, deserialised);
+    }
+}
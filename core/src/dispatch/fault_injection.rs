@@ -0,0 +1,420 @@
+use crate::{
+    actors::{DispatcherRef, SystemPath},
+    messaging::{DispatchEnvelope, EventEnvelope, SerialisedFrame},
+    net::events::NetworkEvent,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// How long an injected delay holds back a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultDelay {
+    /// Every frame is delayed by the same duration
+    Fixed(Duration),
+    /// Every frame is delayed by a duration chosen uniformly at random from `[min, max)`
+    Uniform {
+        /// The shortest delay
+        min: Duration,
+        /// The longest delay
+        max: Duration,
+    },
+}
+
+/// A fault-injection layer for the messages the [NetworkDispatcher](super::NetworkDispatcher) sends to other systems
+///
+/// Set the same injector on the [NetworkConfig](super::NetworkConfig::set_fault_injector)
+/// of every system in a test and keep a clone of it to change the faults at runtime.
+/// Faults are applied to outgoing frames just before they are handed to the network:
+///
+/// - Frames are dropped with the configured [drop probability](FaultInjector::set_drop_probability).
+/// - Frames are sent twice with the configured [duplicate probability](FaultInjector::set_duplicate_probability).
+/// - Frames are held back for a [delay](FaultInjector::set_delay).
+///   TCP frames are never reordered, so a delayed frame also holds back all later frames to the same system.
+///   UDP frames are delayed independently, so random delays reorder them.
+/// - Systems on different sides of a [partition](FaultInjector::partition) can not reach each other.
+///   Their connections are considered lost, messages to them are queued and reconnection attempts fail,
+///   according to the [RetryStrategy](crate::prelude::RetryStrategy), until the partition is
+///   [healed](FaultInjector::heal).
+///
+/// # Example
+///
+/// ```
+/// use kompact::prelude::*;
+/// use std::time::Duration;
+///
+/// let injector = FaultInjector::new();
+/// injector.set_drop_probability(0.1);
+/// injector.set_delay(Some(FaultDelay::Uniform {
+///     min: Duration::from_millis(1),
+///     max: Duration::from_millis(10),
+/// }));
+/// let mut systems = Vec::new();
+/// for _ in 0..2 {
+///     let mut net_config = NetworkConfig::default();
+///     net_config.set_fault_injector(injector.clone());
+///     let mut conf = KompactConfig::default();
+///     conf.system_components(DeadletterBox::new, net_config.build());
+///     systems.push(conf.build().expect("system"));
+/// }
+/// injector.partition("split", &[systems[0].system_path()], &[systems[1].system_path()]);
+/// assert!(injector.is_partitioned(&systems[0].system_path(), &systems[1].system_path()));
+/// injector.heal("split");
+/// # for system in systems {
+/// #     system.shutdown().expect("shutdown");
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FaultInjector {
+    inner: Arc<Mutex<FaultState>>,
+}
+
+struct FaultState {
+    rng: StdRng,
+    drop_probability: f64,
+    duplicate_probability: f64,
+    delay: Option<FaultDelay>,
+    partitions: FxHashMap<String, Partition>,
+    /// The dispatchers of the systems using this injector, by their bound address
+    dispatchers: FxHashMap<SocketAddr, DispatcherRef>,
+}
+
+struct Partition {
+    side_a: FxHashSet<SocketAddr>,
+    side_b: FxHashSet<SocketAddr>,
+}
+
+impl Partition {
+    fn separates(&self, a: &SocketAddr, b: &SocketAddr) -> bool {
+        (self.side_a.contains(a) && self.side_b.contains(b))
+            || (self.side_a.contains(b) && self.side_b.contains(a))
+    }
+}
+
+/// The faults to inject for a single frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FrameFaults {
+    pub drop: bool,
+    pub duplicate: bool,
+    pub delay: Option<Duration>,
+}
+
+impl FaultInjector {
+    /// Creates a new injector, which does not inject any faults until configured to
+    pub fn new() -> Self {
+        FaultInjector::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a new injector, whose random choices are derived from `seed`
+    ///
+    /// Note that the sequence of faults is only reproducible if frames are sent in the same order,
+    /// which is not generally the case with multiple threads.
+    pub fn with_seed(seed: u64) -> Self {
+        FaultInjector::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        let state = FaultState {
+            rng,
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            delay: None,
+            partitions: FxHashMap::default(),
+            dispatchers: FxHashMap::default(),
+        };
+        FaultInjector {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, FaultState> {
+        self.inner.lock().expect("FaultInjector lock poisoned")
+    }
+
+    /// Drop each outgoing frame with the given `probability`
+    ///
+    /// # Panics
+    ///
+    /// If `probability` is not within `[0.0, 1.0]`.
+    pub fn set_drop_probability(&self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Drop probability must be within [0.0, 1.0], was {}",
+            probability
+        );
+        self.state().drop_probability = probability;
+    }
+
+    /// Returns the probability of dropping an outgoing frame
+    pub fn get_drop_probability(&self) -> f64 {
+        self.state().drop_probability
+    }
+
+    /// Send each outgoing frame twice with the given `probability`
+    ///
+    /// # Panics
+    ///
+    /// If `probability` is not within `[0.0, 1.0]`.
+    pub fn set_duplicate_probability(&self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Duplicate probability must be within [0.0, 1.0], was {}",
+            probability
+        );
+        self.state().duplicate_probability = probability;
+    }
+
+    /// Returns the probability of sending an outgoing frame twice
+    pub fn get_duplicate_probability(&self) -> f64 {
+        self.state().duplicate_probability
+    }
+
+    /// Delay each outgoing frame, or stop delaying frames if `None`
+    ///
+    /// # Panics
+    ///
+    /// If the `min` of a [Uniform](FaultDelay::Uniform) delay is larger than its `max`.
+    pub fn set_delay(&self, delay: Option<FaultDelay>) {
+        if let Some(FaultDelay::Uniform { min, max }) = delay {
+            assert!(min <= max, "Delay min {:?} exceeds max {:?}", min, max);
+        }
+        self.state().delay = delay;
+    }
+
+    /// Returns the delay for outgoing frames, if any
+    pub fn get_delay(&self) -> Option<FaultDelay> {
+        self.state().delay
+    }
+
+    /// Separates the systems in `side_a` from the systems in `side_b` until the partition called `name` is healed
+    ///
+    /// Systems on the same side, or not on either side, are not affected.
+    /// Replaces any existing partition with the same `name`.
+    ///
+    /// # Panics
+    ///
+    /// If any of the system paths is not based on an IP address.
+    pub fn partition(&self, name: &str, side_a: &[SystemPath], side_b: &[SystemPath]) {
+        let partition = Partition {
+            side_a: side_a.iter().map(partition_addr).collect(),
+            side_b: side_b.iter().map(partition_addr).collect(),
+        };
+        let mut state = self.state();
+        for (addr, dispatcher) in state.dispatchers.iter() {
+            let remotes = if partition.side_a.contains(addr) {
+                &partition.side_b
+            } else if partition.side_b.contains(addr) {
+                &partition.side_a
+            } else {
+                continue;
+            };
+            for remote in remotes {
+                tell_network_event(dispatcher, NetworkEvent::Partitioned(*remote));
+            }
+        }
+        if let Some(replaced) = state.partitions.insert(name.to_string(), partition) {
+            state.notify_healed(&replaced);
+        }
+    }
+
+    /// Heals the partition called `name`, if it exists
+    ///
+    /// Systems only reconnect if no other partition separates them.
+    pub fn heal(&self, name: &str) {
+        let mut state = self.state();
+        if let Some(partition) = state.partitions.remove(name) {
+            state.notify_healed(&partition);
+        }
+    }
+
+    /// Heals all partitions
+    pub fn heal_all(&self) {
+        let mut state = self.state();
+        let partitions: Vec<Partition> = state.partitions.drain().map(|(_, p)| p).collect();
+        for partition in partitions {
+            state.notify_healed(&partition);
+        }
+    }
+
+    /// Returns `true` if any partition separates the systems at `a` and `b`
+    pub fn is_partitioned(&self, a: &SystemPath, b: &SystemPath) -> bool {
+        match (a.socket_addr(), b.socket_addr()) {
+            (Some(a), Some(b)) => self.is_partitioned_addr(&a, &b),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn is_partitioned_addr(&self, a: &SocketAddr, b: &SocketAddr) -> bool {
+        self.state().is_partitioned(a, b)
+    }
+
+    /// Registers the dispatcher of the system at `addr` to be told about partitions
+    pub(crate) fn register(&self, addr: SocketAddr, dispatcher: DispatcherRef) {
+        self.state().dispatchers.insert(addr, dispatcher);
+    }
+
+    pub(crate) fn unregister(&self, addr: &SocketAddr) {
+        self.state().dispatchers.remove(addr);
+    }
+
+    /// Decides which faults to inject for the next outgoing frame
+    pub(crate) fn frame_faults(&self) -> FrameFaults {
+        let mut state = self.state();
+        let drop_probability = state.drop_probability;
+        if drop_probability > 0.0 && state.rng.gen_bool(drop_probability) {
+            return FrameFaults {
+                drop: true,
+                ..FrameFaults::default()
+            };
+        }
+        let duplicate_probability = state.duplicate_probability;
+        let duplicate = duplicate_probability > 0.0 && state.rng.gen_bool(duplicate_probability);
+        let delay = match state.delay {
+            Some(FaultDelay::Fixed(delay)) => Some(delay),
+            Some(FaultDelay::Uniform { min, max }) if min < max => {
+                Some(state.rng.gen_range(min, max))
+            }
+            Some(FaultDelay::Uniform { min, .. }) => Some(min),
+            None => None,
+        };
+        FrameFaults {
+            drop: false,
+            duplicate,
+            delay,
+        }
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        FaultInjector::new()
+    }
+}
+
+impl fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        let mut partitions: Vec<&String> = state.partitions.keys().collect();
+        partitions.sort();
+        f.debug_struct("FaultInjector")
+            .field("drop_probability", &state.drop_probability)
+            .field("duplicate_probability", &state.duplicate_probability)
+            .field("delay", &state.delay)
+            .field("partitions", &partitions)
+            .finish()
+    }
+}
+
+impl FaultState {
+    fn is_partitioned(&self, a: &SocketAddr, b: &SocketAddr) -> bool {
+        self.partitions.values().any(|p| p.separates(a, b))
+    }
+
+    /// Tells the dispatchers on either side of the healed `partition`
+    /// about the systems they can reach again
+    fn notify_healed(&self, partition: &Partition) {
+        for a in partition.side_a.iter() {
+            for b in partition.side_b.iter() {
+                if self.is_partitioned(a, b) {
+                    continue;
+                }
+                if let Some(dispatcher) = self.dispatchers.get(a) {
+                    tell_network_event(dispatcher, NetworkEvent::Healed(*b));
+                }
+                if let Some(dispatcher) = self.dispatchers.get(b) {
+                    tell_network_event(dispatcher, NetworkEvent::Healed(*a));
+                }
+            }
+        }
+    }
+}
+
+fn partition_addr(system: &SystemPath) -> SocketAddr {
+    system
+        .socket_addr()
+        .unwrap_or_else(|| panic!("Can not partition {}, as it is not IP based", system))
+}
+
+fn tell_network_event(dispatcher: &DispatcherRef, event: NetworkEvent) {
+    dispatcher.tell(DispatchEnvelope::Event(EventEnvelope::Network(event)));
+}
+
+/// Returns two copies of `frame`
+pub(crate) fn duplicate_frame(frame: SerialisedFrame) -> (SerialisedFrame, SerialisedFrame) {
+    match frame {
+        SerialisedFrame::Bytes(bytes) => (
+            SerialisedFrame::Bytes(bytes.clone()),
+            SerialisedFrame::Bytes(bytes),
+        ),
+        SerialisedFrame::ChunkLease(chunk) => {
+            let chunk = chunk.into_chunk_ref();
+            (
+                SerialisedFrame::ChunkRef(chunk.clone()),
+                SerialisedFrame::ChunkRef(chunk),
+            )
+        }
+        SerialisedFrame::ChunkRef(chunk) => (
+            SerialisedFrame::ChunkRef(chunk.clone()),
+            SerialisedFrame::ChunkRef(chunk),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::Transport;
+
+    fn system(port: u16) -> SystemPath {
+        SystemPath::new(Transport::TCP, "127.0.0.1".parse().unwrap(), port)
+    }
+
+    #[test]
+    fn partitions_separate_sides() {
+        let injector = FaultInjector::new();
+        injector.partition("a", &[system(1)], &[system(2), system(3)]);
+        injector.partition("b", &[system(3)], &[system(1)]);
+        assert!(injector.is_partitioned(&system(1), &system(2)));
+        assert!(injector.is_partitioned(&system(2), &system(1)));
+        assert!(injector.is_partitioned(&system(1), &system(3)));
+        assert!(!injector.is_partitioned(&system(2), &system(3)));
+        assert!(!injector.is_partitioned(&system(1), &system(4)));
+
+        injector.heal("a");
+        assert!(!injector.is_partitioned(&system(1), &system(2)));
+        assert!(injector.is_partitioned(&system(1), &system(3)));
+
+        injector.heal_all();
+        assert!(!injector.is_partitioned(&system(1), &system(3)));
+    }
+
+    #[test]
+    fn frame_faults_follow_configuration() {
+        let injector = FaultInjector::with_seed(42);
+        assert_eq!(injector.frame_faults(), FrameFaults::default());
+
+        injector.set_drop_probability(1.0);
+        assert!(injector.frame_faults().drop);
+
+        injector.set_drop_probability(0.0);
+        injector.set_duplicate_probability(1.0);
+        let min = Duration::from_millis(5);
+        let max = Duration::from_millis(10);
+        injector.set_delay(Some(FaultDelay::Uniform { min, max }));
+        for _ in 0..100 {
+            let faults = injector.frame_faults();
+            assert!(!faults.drop);
+            assert!(faults.duplicate);
+            let delay = faults.delay.expect("delay");
+            assert!(min <= delay && delay <= max);
+        }
+
+        injector.set_delay(Some(FaultDelay::Fixed(min)));
+        assert_eq!(injector.frame_faults().delay, Some(min));
+    }
+}
@@ -1,7 +1,7 @@
 use super::*;
 
 use crate::{
-    actors::{Actor, ActorPath, Dispatcher, DynActorRef, SystemPath, Transport},
+    actors::{Actor, ActorPath, Address, Dispatcher, DynActorRef, SystemPath, Transport},
     component::{Component, ComponentContext, ExecuteResult},
 };
 use std::{net::SocketAddr, pin::Pin, sync::Arc};
@@ -10,10 +10,14 @@ use crate::{
     actors::NamedPath,
     messaging::{
         ActorRegistration,
+        ConnectionInfo,
+        ConnectionRequest,
+        Departure,
         DispatchData,
         DispatchEnvelope,
         EventEnvelope,
         MsgEnvelope,
+        MulticastMembership,
         NetMessage,
         PathResolvable,
         PolicyRegistration,
@@ -23,25 +27,67 @@ use crate::{
         RegistrationPromise,
         SerialisedFrame,
     },
-    net::{buffers::*, events::NetworkEvent, ConnectionState, NetworkBridgeErr},
-    timer::timer_manager::Timer,
+    net::{
+        auth::ClusterSecret,
+        buffers::*,
+        events::NetworkEvent,
+        failure_detector::FailureDetector,
+        memory::MemoryNetwork,
+        transport::{NetworkTransport, TransportFactory},
+        unix::unix_socket_key,
+        ConnectionState,
+        NetworkBridgeErr,
+        RetryStrategy,
+    },
+    timer::timer_manager::{ScheduledTimer, Timer},
 };
 use arc_swap::ArcSwap;
+use crossbeam_channel::{unbounded, Sender};
+use delivery_failure::{DeliveryFailure, DeliveryFailureReason, DeliveryFailureReporting};
+use fault_injection::{duplicate_frame, FaultInjector};
 use futures::{
     self,
     task::{Context, Poll},
 };
 use lookup::{ActorLookup, ActorStore, InsertResult, LookupResult};
-use queue_manager::QueueManager;
-use rustc_hash::FxHashMap;
-use std::{collections::VecDeque, io::ErrorKind, time::Duration};
+use network_status_port::{NetworkStatus, NetworkStatusPort};
+use queue_manager::{QueueManager, QueueOverflowPolicy};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io,
+    io::ErrorKind,
+    net::ToSocketAddrs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
 
+#[cfg(feature = "tls")]
+use crate::net::tls::TlsConfig;
+
+pub mod delivery_failure;
+pub mod fault_injection;
 pub mod lookup;
+pub mod network_status_port;
 pub mod queue_manager;
 
 // Default values for network config.
-const RETRY_CONNECTIONS_INTERVAL: u64 = 5000;
-const MAX_RETRY_ATTEMPTS: u8 = 10;
+const CREDIT_WINDOW: u32 = 1024;
+const CREDIT_UPDATE_THRESHOLD: u32 = 256;
+const WRITE_BATCH_SIZE: usize = 64 * 1024;
+// The largest payload of an IPv4 UDP datagram, so only messages which don't fit are fragmented
+const MAX_UDP_DATAGRAM_SIZE: usize = 65507;
+const UDP_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(5);
+const RELIABLE_UDP_WINDOW: usize = 64;
+const RELIABLE_UDP_RETRANSMIT_TIMEOUT: Duration = Duration::from_millis(200);
+const RELIABLE_UDP_MAX_RETRANSMISSIONS: u32 = 10;
+const RELIABLE_UDP_MAX_PENDING: usize = 1024;
+
+/// How long a connection attempt may take to complete its handshake, before it is retried
+const HANDSHAKE_GRACE_PERIOD: Duration = Duration::from_millis(5000);
 
 type NetHashMap<K, V> = FxHashMap<K, V>;
 
@@ -66,8 +112,47 @@ pub struct NetworkConfig {
     buffer_config: BufferConfig,
     custom_allocator: Option<Arc<dyn ChunkAllocator>>,
     tcp_nodelay: bool,
-    max_connection_retry_attempts: u8,
-    connection_retry_interval: u64,
+    retry_strategy: Option<RetryStrategy>,
+    #[cfg(feature = "tls")]
+    tls_config: Option<TlsConfig>,
+    credit_window: u32,
+    credit_update_threshold: u32,
+    max_queued_frames: Option<usize>,
+    max_queued_bytes: Option<usize>,
+    queue_overflow_policy: QueueOverflowPolicy,
+    delivery_failure_reporting: DeliveryFailureReporting,
+    failure_detector: Option<FailureDetector>,
+    close_on_suspicion: bool,
+    idle_timeout: Option<Duration>,
+    memory_network: Option<MemoryNetwork>,
+    fault_injector: Option<FaultInjector>,
+    compression_threshold: Option<usize>,
+    cluster_secret: Option<ClusterSecret>,
+    reject_stale_messages: bool,
+    network_threads: usize,
+    tcp_lanes: u8,
+    write_batch_size: usize,
+    max_udp_datagram_size: usize,
+    udp_reassembly_timeout: Duration,
+    reliable_udp_window: usize,
+    reliable_udp_retransmit_timeout: Duration,
+    reliable_udp_max_retransmissions: u32,
+    reliable_udp_max_pending: usize,
+    multicast_groups: Vec<SocketAddr>,
+    unix_socket_path: Option<PathBuf>,
+    custom_transports: CustomTransports,
+}
+
+/// The custom transports registered in a [NetworkConfig](NetworkConfig), by transport identifier
+#[derive(Clone, Default)]
+struct CustomTransports(Vec<(u8, TransportFactory)>);
+
+impl fmt::Debug for CustomTransports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(id, _)| Transport::Custom(*id)))
+            .finish()
+    }
 }
 
 impl NetworkConfig {
@@ -80,8 +165,35 @@ impl NetworkConfig {
             buffer_config: BufferConfig::default(),
             custom_allocator: None,
             tcp_nodelay: false,
-            max_connection_retry_attempts: MAX_RETRY_ATTEMPTS,
-            connection_retry_interval: RETRY_CONNECTIONS_INTERVAL,
+            retry_strategy: None,
+            #[cfg(feature = "tls")]
+            tls_config: None,
+            credit_window: CREDIT_WINDOW,
+            credit_update_threshold: CREDIT_UPDATE_THRESHOLD,
+            max_queued_frames: None,
+            max_queued_bytes: None,
+            queue_overflow_policy: QueueOverflowPolicy::default(),
+            delivery_failure_reporting: DeliveryFailureReporting::default(),
+            failure_detector: None,
+            close_on_suspicion: false,
+            idle_timeout: None,
+            memory_network: None,
+            fault_injector: None,
+            compression_threshold: None,
+            cluster_secret: None,
+            reject_stale_messages: false,
+            network_threads: 1,
+            tcp_lanes: 1,
+            write_batch_size: WRITE_BATCH_SIZE,
+            max_udp_datagram_size: MAX_UDP_DATAGRAM_SIZE,
+            udp_reassembly_timeout: UDP_REASSEMBLY_TIMEOUT,
+            reliable_udp_window: RELIABLE_UDP_WINDOW,
+            reliable_udp_retransmit_timeout: RELIABLE_UDP_RETRANSMIT_TIMEOUT,
+            reliable_udp_max_retransmissions: RELIABLE_UDP_MAX_RETRANSMISSIONS,
+            reliable_udp_max_pending: RELIABLE_UDP_MAX_PENDING,
+            multicast_groups: Vec::new(),
+            unix_socket_path: None,
+            custom_transports: CustomTransports::default(),
         }
     }
 
@@ -95,8 +207,35 @@ impl NetworkConfig {
             buffer_config,
             custom_allocator: None,
             tcp_nodelay: false,
-            max_connection_retry_attempts: MAX_RETRY_ATTEMPTS,
-            connection_retry_interval: RETRY_CONNECTIONS_INTERVAL,
+            retry_strategy: None,
+            #[cfg(feature = "tls")]
+            tls_config: None,
+            credit_window: CREDIT_WINDOW,
+            credit_update_threshold: CREDIT_UPDATE_THRESHOLD,
+            max_queued_frames: None,
+            max_queued_bytes: None,
+            queue_overflow_policy: QueueOverflowPolicy::default(),
+            delivery_failure_reporting: DeliveryFailureReporting::default(),
+            failure_detector: None,
+            close_on_suspicion: false,
+            idle_timeout: None,
+            memory_network: None,
+            fault_injector: None,
+            compression_threshold: None,
+            cluster_secret: None,
+            reject_stale_messages: false,
+            network_threads: 1,
+            tcp_lanes: 1,
+            write_batch_size: WRITE_BATCH_SIZE,
+            max_udp_datagram_size: MAX_UDP_DATAGRAM_SIZE,
+            udp_reassembly_timeout: UDP_REASSEMBLY_TIMEOUT,
+            reliable_udp_window: RELIABLE_UDP_WINDOW,
+            reliable_udp_retransmit_timeout: RELIABLE_UDP_RETRANSMIT_TIMEOUT,
+            reliable_udp_max_retransmissions: RELIABLE_UDP_MAX_RETRANSMISSIONS,
+            reliable_udp_max_pending: RELIABLE_UDP_MAX_PENDING,
+            multicast_groups: Vec::new(),
+            unix_socket_path: None,
+            custom_transports: CustomTransports::default(),
         }
     }
 
@@ -114,8 +253,35 @@ impl NetworkConfig {
             buffer_config,
             custom_allocator: Some(custom_allocator),
             tcp_nodelay: false,
-            max_connection_retry_attempts: MAX_RETRY_ATTEMPTS,
-            connection_retry_interval: RETRY_CONNECTIONS_INTERVAL,
+            retry_strategy: None,
+            #[cfg(feature = "tls")]
+            tls_config: None,
+            credit_window: CREDIT_WINDOW,
+            credit_update_threshold: CREDIT_UPDATE_THRESHOLD,
+            max_queued_frames: None,
+            max_queued_bytes: None,
+            queue_overflow_policy: QueueOverflowPolicy::default(),
+            delivery_failure_reporting: DeliveryFailureReporting::default(),
+            failure_detector: None,
+            close_on_suspicion: false,
+            idle_timeout: None,
+            memory_network: None,
+            fault_injector: None,
+            compression_threshold: None,
+            cluster_secret: None,
+            reject_stale_messages: false,
+            network_threads: 1,
+            tcp_lanes: 1,
+            write_batch_size: WRITE_BATCH_SIZE,
+            max_udp_datagram_size: MAX_UDP_DATAGRAM_SIZE,
+            udp_reassembly_timeout: UDP_REASSEMBLY_TIMEOUT,
+            reliable_udp_window: RELIABLE_UDP_WINDOW,
+            reliable_udp_retransmit_timeout: RELIABLE_UDP_RETRANSMIT_TIMEOUT,
+            reliable_udp_max_retransmissions: RELIABLE_UDP_MAX_RETRANSMISSIONS,
+            reliable_udp_max_pending: RELIABLE_UDP_MAX_PENDING,
+            multicast_groups: Vec::new(),
+            unix_socket_path: None,
+            custom_transports: CustomTransports::default(),
         }
     }
 
@@ -162,29 +328,551 @@ impl NetworkConfig {
         self.tcp_nodelay = nodelay;
     }
 
+    /// Configures the [RetryStrategy](RetryStrategy) used for re-establishing lost connections.
+    ///
+    /// A strategy set here takes precedence over one given in the `network.retry_strategy`
+    /// section of the system's config.
+    ///
+    /// Default value is [RetryStrategy::default()](RetryStrategy::default),
+    /// i.e. exponential backoff from 100 ms up to 5000 ms, for 10 attempts.
+    pub fn set_retry_strategy(&mut self, retry_strategy: RetryStrategy) {
+        self.retry_strategy = Some(retry_strategy);
+    }
+
+    /// Returns the [RetryStrategy](RetryStrategy) used for re-establishing lost connections.
+    pub fn get_retry_strategy(&self) -> RetryStrategy {
+        self.retry_strategy.unwrap_or_default()
+    }
+
     /// Configures how many attempts at re-establishing a connection will be made before giving up
     /// and discarding the enqueued outgoing messages.
     ///
     /// Default value is 10 times.
     pub fn set_max_connection_retry_attempts(&mut self, count: u8) {
-        self.max_connection_retry_attempts = count;
+        self.retry_strategy
+            .get_or_insert_with(RetryStrategy::default)
+            .set_num_tries(count);
     }
 
     /// Returns the number of times the system will retry before giving up on a connection.
     pub fn get_max_connection_retry_attempts(&self) -> u8 {
-        self.max_connection_retry_attempts
+        self.get_retry_strategy().num_tries()
     }
 
-    /// Configures how long to wait (in ms) between attempts at establishing a connection.
+    /// Configures how long to wait (in ms) before the first attempt at re-establishing a connection.
+    ///
+    /// With the default [ExponentialBackoff](RetryStrategy::ExponentialBackoff) strategy,
+    /// the wait doubles with every further attempt.
     ///
-    /// Default value is 5000 ms.
+    /// Default value is 100 ms.
     pub fn set_connection_retry_interval(&mut self, milliseconds: u64) {
-        self.connection_retry_interval = milliseconds;
+        self.retry_strategy
+            .get_or_insert_with(RetryStrategy::default)
+            .set_interval_ms(milliseconds);
     }
 
-    /// How long (in ms) the system will wait between attempts at re-establishing connection.
+    /// How long (in ms) the system will wait before the first attempt at re-establishing connection.
     pub fn get_connection_retry_interval(&self) -> u64 {
-        self.connection_retry_interval
+        self.get_retry_strategy().interval_ms()
+    }
+
+    /// Encrypts all TCP channels with TLS, using the certificates and keys in `tls_config`.
+    ///
+    /// All remote systems must be configured with TLS as well.
+    /// See [TlsConfig](TlsConfig) for details.
+    /// Requires the `tls` feature.
+    ///
+    /// Default value is `None`, i.e. TCP channels are not encrypted.
+    #[cfg(feature = "tls")]
+    pub fn set_tls_config(&mut self, tls_config: TlsConfig) {
+        self.tls_config = Some(tls_config);
+    }
+
+    /// Returns the [TlsConfig](TlsConfig) for TCP channels, if TLS is enabled.
+    #[cfg(feature = "tls")]
+    pub fn get_tls_config(&self) -> Option<&TlsConfig> {
+        self.tls_config.as_ref()
+    }
+
+    /// Configures how many data frames a remote host may send on a TCP channel
+    /// before it has to wait for this system to grant it more credit.
+    ///
+    /// Credit is granted back to the remote host as received frames are decoded,
+    /// but is withheld while the network thread has no free receive buffers.
+    ///
+    /// Must be at least 1. Default value is 1024 frames.
+    pub fn set_credit_window(&mut self, frames: u32) {
+        assert!(frames > 0, "credit_window must be at least 1");
+        self.credit_window = frames;
+    }
+
+    /// Returns the maximum number of data frames a remote host may have in flight on a TCP channel.
+    pub fn get_credit_window(&self) -> u32 {
+        self.credit_window
+    }
+
+    /// Configures how many bytes of pending frames are gathered into a single vectored write on a TCP channel.
+    ///
+    /// Frames are added to a write until they add up to at least this many bytes,
+    /// so many small frames are written with a single system call, while a large frame is never split up.
+    ///
+    /// Must be at least 1. Default value is 64 KiB.
+    pub fn set_write_batch_size(&mut self, bytes: usize) {
+        assert!(bytes > 0, "write_batch_size must be at least 1");
+        self.write_batch_size = bytes;
+    }
+
+    /// Returns how many bytes of pending frames are gathered into a single write on a TCP channel.
+    pub fn get_write_batch_size(&self) -> usize {
+        self.write_batch_size
+    }
+
+    /// Configures the maximum size of the UDP datagrams sent by the network thread.
+    ///
+    /// Larger messages are split into numbered fragments, which the remote system reassembles.
+    /// Fragments which would exceed the path MTU are in turn fragmented by IP,
+    /// which makes their loss much more likely.
+    ///
+    /// Must be at least 64. Default value is 65507 bytes, the largest payload of an IPv4 UDP datagram,
+    /// so that only messages which could not be sent in a single datagram at all are fragmented.
+    /// Use 1472 bytes, which fit into a single Ethernet frame, to avoid IP fragmentation.
+    pub fn set_max_udp_datagram_size(&mut self, bytes: usize) {
+        assert!(bytes >= 64, "max_udp_datagram_size must be at least 64");
+        self.max_udp_datagram_size = bytes;
+    }
+
+    /// Returns the maximum size of the UDP datagrams sent by the network thread.
+    pub fn get_max_udp_datagram_size(&self) -> usize {
+        self.max_udp_datagram_size
+    }
+
+    /// Configures how long the fragments of a UDP message are kept while waiting for the rest.
+    ///
+    /// Messages which are still incomplete after this long are dropped,
+    /// and indicated as [IncompleteUdpMessages](NetworkStatus::IncompleteUdpMessages).
+    ///
+    /// Default value is 5 seconds.
+    pub fn set_udp_reassembly_timeout(&mut self, timeout: Duration) {
+        self.udp_reassembly_timeout = timeout;
+    }
+
+    /// Returns how long the fragments of a UDP message are kept while waiting for the rest.
+    pub fn get_udp_reassembly_timeout(&self) -> Duration {
+        self.udp_reassembly_timeout
+    }
+
+    /// Configures how many messages may be sent to a remote system over [reliable UDP](Transport::RUDP)
+    /// before the first of them must be acknowledged.
+    ///
+    /// Further messages are held back until earlier ones have been acknowledged.
+    ///
+    /// Must be at least 1. Default value is 64.
+    pub fn set_reliable_udp_window(&mut self, messages: usize) {
+        assert!(messages > 0, "reliable_udp_window must be at least 1");
+        self.reliable_udp_window = messages;
+    }
+
+    /// Returns how many unacknowledged messages may be sent to a remote system over reliable UDP.
+    pub fn get_reliable_udp_window(&self) -> usize {
+        self.reliable_udp_window
+    }
+
+    /// Configures how long to wait for the acknowledgement of a message sent over
+    /// [reliable UDP](Transport::RUDP) before retransmitting it.
+    ///
+    /// Default value is 200 ms.
+    pub fn set_reliable_udp_retransmit_timeout(&mut self, timeout: Duration) {
+        self.reliable_udp_retransmit_timeout = timeout;
+    }
+
+    /// Returns how long to wait for the acknowledgement of a message sent over reliable UDP.
+    pub fn get_reliable_udp_retransmit_timeout(&self) -> Duration {
+        self.reliable_udp_retransmit_timeout
+    }
+
+    /// Configures how often a message sent over [reliable UDP](Transport::RUDP) is retransmitted
+    /// before the remote system is considered unreachable.
+    ///
+    /// The connection is then [lost](NetworkStatus::ConnectionLost),
+    /// and all unacknowledged messages to the remote system are dropped.
+    ///
+    /// Default value is 10.
+    pub fn set_reliable_udp_max_retransmissions(&mut self, retransmissions: u32) {
+        self.reliable_udp_max_retransmissions = retransmissions;
+    }
+
+    /// Returns how often a message sent over reliable UDP is retransmitted before giving up.
+    pub fn get_reliable_udp_max_retransmissions(&self) -> u32 {
+        self.reliable_udp_max_retransmissions
+    }
+
+    /// Configures how many messages to a remote system may wait for room in the
+    /// [reliable UDP window](NetworkConfig::set_reliable_udp_window).
+    ///
+    /// Messages which do not fit are handled according to the
+    /// [queue overflow policy](NetworkConfig::set_queue_overflow_policy).
+    ///
+    /// Must be at least 1. Default value is 1024.
+    pub fn set_reliable_udp_max_pending(&mut self, messages: usize) {
+        assert!(messages > 0, "reliable_udp_max_pending must be at least 1");
+        self.reliable_udp_max_pending = messages;
+    }
+
+    /// Returns how many messages to a remote system may wait for room in the reliable UDP window.
+    pub fn get_reliable_udp_max_pending(&self) -> usize {
+        self.reliable_udp_max_pending
+    }
+
+    /// Joins the UDP multicast `group` when the system starts.
+    ///
+    /// Messages sent to a [multicast path](NamedPath::multicast) of the group are then received
+    /// by this system, as well as by all other members of the group.
+    /// Groups can also be joined and left while the system is running,
+    /// see [join_multicast_group](KompactSystem::join_multicast_group).
+    ///
+    /// # Panics
+    ///
+    /// If `group` is not a multicast address.
+    pub fn join_multicast_group(&mut self, group: SocketAddr) {
+        assert!(
+            group.ip().is_multicast(),
+            "{} is not a multicast address",
+            group
+        );
+        if !self.multicast_groups.contains(&group) {
+            self.multicast_groups.push(group);
+        }
+    }
+
+    /// Returns the UDP multicast groups which are joined when the system starts.
+    pub fn get_multicast_groups(&self) -> &[SocketAddr] {
+        &self.multicast_groups
+    }
+
+    /// Also listens for channels from systems on the same host on a Unix domain socket at `path`.
+    ///
+    /// Such systems reach this one through [UDS paths](SystemPath::with_unix_socket) for `path`,
+    /// which avoid the overhead of the TCP stack, but are otherwise used like TCP paths.
+    /// A stale socket file left behind at `path` by a system which is no longer running is replaced.
+    /// Unix domain sockets are only supported on Unix platforms.
+    ///
+    /// Default value is `None`, i.e. no Unix domain socket is bound.
+    pub fn set_unix_socket_path(&mut self, path: PathBuf) {
+        self.unix_socket_path = Some(path);
+    }
+
+    /// Returns the path of the Unix domain socket the system listens on, if any.
+    pub fn get_unix_socket_path(&self) -> Option<&Path> {
+        self.unix_socket_path.as_deref()
+    }
+
+    /// Registers a custom transport for the transport identifier [Custom(id)](Transport::Custom).
+    ///
+    /// The `factory` is invoked once for each system built from this config,
+    /// and the transport it returns carries all frames to paths with this identifier,
+    /// like `custom3://127.0.0.1:4000/actor` for `id` 3.
+    /// See [NetworkTransport](net::transport::NetworkTransport) for how transports are driven.
+    /// Registering another transport for the same `id` replaces the earlier one.
+    ///
+    /// Default value is no custom transports.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is larger than [MAX_CUSTOM_ID](Transport::MAX_CUSTOM_ID).
+    pub fn register_transport<F>(&mut self, id: u8, factory: F)
+    where
+        F: Fn() -> Box<dyn NetworkTransport> + Send + Sync + 'static,
+    {
+        assert!(
+            id <= Transport::MAX_CUSTOM_ID,
+            "custom transport identifiers must be at most {}",
+            Transport::MAX_CUSTOM_ID
+        );
+        self.custom_transports.0.retain(|(other, _)| *other != id);
+        self.custom_transports.0.push((id, Arc::new(factory)));
+    }
+
+    /// Returns the identifiers of the registered custom transports.
+    pub fn get_custom_transports(&self) -> Vec<u8> {
+        self.custom_transports.0.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Returns the registered custom transports along with their identifiers
+    pub(crate) fn custom_transports(&self) -> impl Iterator<Item = (u8, &TransportFactory)> + '_ {
+        self.custom_transports
+            .0
+            .iter()
+            .map(|(id, factory)| (*id, factory))
+    }
+
+    /// Configures how many received data frames are batched together before
+    /// the consumed credit is granted back to the remote host in a single `CreditUpdate`.
+    ///
+    /// Values larger than the [credit window](NetworkConfig::set_credit_window) are capped to the window.
+    ///
+    /// Must be at least 1. Default value is 256 frames.
+    pub fn set_credit_update_threshold(&mut self, frames: u32) {
+        assert!(frames > 0, "credit_update_threshold must be at least 1");
+        self.credit_update_threshold = frames;
+    }
+
+    /// Returns the number of consumed credits which are batched into a single `CreditUpdate`.
+    pub fn get_credit_update_threshold(&self) -> u32 {
+        self.credit_update_threshold.min(self.credit_window)
+    }
+
+    /// Configures how many frames may be queued for a single remote host
+    /// while the connection to it is being (re-)established.
+    ///
+    /// Frames which do not fit are handled according to the
+    /// [queue overflow policy](NetworkConfig::set_queue_overflow_policy).
+    ///
+    /// Default value is `None`, i.e. the number of queued frames is unbounded.
+    pub fn set_max_queued_frames(&mut self, frames: Option<usize>) {
+        self.max_queued_frames = frames;
+    }
+
+    /// Returns the maximum number of frames queued for a single remote host, if bounded.
+    pub fn get_max_queued_frames(&self) -> Option<usize> {
+        self.max_queued_frames
+    }
+
+    /// Configures how many bytes may be queued for a single remote host
+    /// while the connection to it is being (re-)established.
+    ///
+    /// Frames which do not fit are handled according to the
+    /// [queue overflow policy](NetworkConfig::set_queue_overflow_policy).
+    ///
+    /// Default value is `None`, i.e. the number of queued bytes is unbounded.
+    pub fn set_max_queued_bytes(&mut self, bytes: Option<usize>) {
+        self.max_queued_bytes = bytes;
+    }
+
+    /// Returns the maximum number of bytes queued for a single remote host, if bounded.
+    pub fn get_max_queued_bytes(&self) -> Option<usize> {
+        self.max_queued_bytes
+    }
+
+    /// Configures what happens to frames for a remote host whose queue is full.
+    ///
+    /// Frames which are not sent are reported as [QueueDropped](NetworkStatus::QueueDropped)
+    /// on the [NetworkStatusPort](NetworkStatusPort).
+    ///
+    /// Default value is [DropOldest](QueueOverflowPolicy::DropOldest).
+    pub fn set_queue_overflow_policy(&mut self, policy: QueueOverflowPolicy) {
+        self.queue_overflow_policy = policy;
+    }
+
+    /// Returns what happens to frames for a remote host whose queue is full.
+    pub fn get_queue_overflow_policy(&self) -> QueueOverflowPolicy {
+        self.queue_overflow_policy
+    }
+
+    /// Configures where messages are reported which could not be delivered to a remote host.
+    ///
+    /// Reports are sent as [DeliveryFailure](DeliveryFailure) messages, when the dispatcher gives up
+    /// on a remote host, when a host's queue overflows, or when the network thread rejects a frame
+    /// for a host which is no longer being connected to.
+    ///
+    /// Default value is [Disabled](DeliveryFailureReporting::Disabled), i.e. such messages are dropped silently.
+    pub fn set_delivery_failure_reporting(&mut self, reporting: DeliveryFailureReporting) {
+        self.delivery_failure_reporting = reporting;
+    }
+
+    /// Returns where messages are reported which could not be delivered to a remote host.
+    pub fn get_delivery_failure_reporting(&self) -> DeliveryFailureReporting {
+        self.delivery_failure_reporting
+    }
+
+    /// Sends heartbeats on all TCP channels, and uses `failure_detector` to suspect
+    /// remote systems which stop answering them.
+    ///
+    /// Suspicion and recovery are indicated as [Suspected](NetworkStatus::Suspected) and
+    /// [Alive](NetworkStatus::Alive) on the [NetworkStatusPort](NetworkStatusPort).
+    ///
+    /// A detector set here takes precedence over one given in the `network.failure_detector`
+    /// section of the system's config.
+    ///
+    /// Default value is `None`, i.e. remote systems are only considered lost once their channel breaks.
+    pub fn set_failure_detector(&mut self, failure_detector: FailureDetector) {
+        self.failure_detector = Some(failure_detector);
+    }
+
+    /// Returns the [FailureDetector](FailureDetector) for remote systems, if failure detection is enabled.
+    pub fn get_failure_detector(&self) -> Option<FailureDetector> {
+        self.failure_detector
+    }
+
+    /// If set to `true`, the channel to a remote system is closed as soon as it is suspected,
+    /// and re-established according to the [RetryStrategy](RetryStrategy).
+    ///
+    /// Only has an effect if a [failure detector](NetworkConfig::set_failure_detector) is set.
+    ///
+    /// Default value is `false`, i.e. suspected channels are kept open.
+    pub fn set_close_on_suspicion(&mut self, close: bool) {
+        self.close_on_suspicion = close;
+    }
+
+    /// Returns `true` if channels to suspected remote systems are closed.
+    pub fn get_close_on_suspicion(&self) -> bool {
+        self.close_on_suspicion
+    }
+
+    /// Gracefully closes the TCP channel to a remote system with a Bye, once no messages have been
+    /// sent to or received from it for `timeout`.
+    ///
+    /// Heartbeats and flow control do not count as messages. Both systems indicate the [idle](NetworkStatus::ConnectionIdle)
+    /// connection, and neither tries to reconnect, until the next message is sent, which re-establishes
+    /// the connection transparently. Messages either system sent just as the connection was closed
+    /// are sent over a new connection, too. This bounds the number of open sockets when there are many transient peers.
+    ///
+    /// Remote systems which cannot be told why the connection is closed indicate that this system
+    /// [left](NetworkStatus::PeerLeft) instead.
+    ///
+    /// Channels are checked for being idle every half `timeout`, so they are closed after at most 1.5 times `timeout`.
+    ///
+    /// Default value is `None`, i.e. idle channels are kept open.
+    ///
+    /// # Panics
+    ///
+    /// If `timeout` is zero.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        assert!(
+            timeout != Some(Duration::ZERO),
+            "idle_timeout must not be zero"
+        );
+        self.idle_timeout = timeout;
+    }
+
+    /// Returns after how long without messages channels are closed, if at all.
+    pub fn get_idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Connects the system to `network` instead of binding real sockets.
+    ///
+    /// The configured socket address is then only a virtual address within the `network`,
+    /// and the [Transport](Transport) is ignored.
+    /// See [MemoryNetwork](MemoryNetwork) for details.
+    ///
+    /// Default value is `None`, i.e. the system binds real sockets.
+    pub fn set_memory_network(&mut self, network: MemoryNetwork) {
+        self.memory_network = Some(network);
+    }
+
+    /// Returns the [MemoryNetwork](MemoryNetwork) the system is connected to, if any.
+    pub fn get_memory_network(&self) -> Option<&MemoryNetwork> {
+        self.memory_network.as_ref()
+    }
+
+    /// Injects faults into the messages sent to other systems, as configured on the `injector`.
+    ///
+    /// See [FaultInjector](FaultInjector) for details.
+    ///
+    /// Default value is `None`, i.e. no faults are injected.
+    pub fn set_fault_injector(&mut self, injector: FaultInjector) {
+        self.fault_injector = Some(injector);
+    }
+
+    /// Returns the [FaultInjector](FaultInjector) used by the system, if any.
+    pub fn get_fault_injector(&self) -> Option<&FaultInjector> {
+        self.fault_injector.as_ref()
+    }
+
+    /// Compress messages of at least `bytes` bytes with LZ4, before sending them over TCP.
+    ///
+    /// Messages are only compressed for remote systems which support compression,
+    /// and only if compression actually makes them smaller.
+    /// Compressed messages are always accepted from remote systems, regardless of this setting.
+    ///
+    /// Default value is `None`, i.e. messages are not compressed.
+    pub fn set_compression_threshold(&mut self, bytes: Option<usize>) {
+        self.compression_threshold = bytes;
+    }
+
+    /// Returns the minimum size of messages which are compressed, if compression is enabled.
+    pub fn get_compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    /// Authenticates all TCP channels with the shared `secret`.
+    ///
+    /// Connections from or to hosts which do not know the same secret are dropped during the handshake.
+    /// All remote systems must be configured with the same secret.
+    /// See [ClusterSecret](ClusterSecret) for details.
+    ///
+    /// Default value is `None`, i.e. TCP channels are not authenticated.
+    pub fn set_cluster_secret(&mut self, secret: ClusterSecret) {
+        self.cluster_secret = Some(secret);
+    }
+
+    /// Returns the [ClusterSecret](ClusterSecret) for TCP channels, if authentication is enabled.
+    pub fn get_cluster_secret(&self) -> Option<&ClusterSecret> {
+        self.cluster_secret.as_ref()
+    }
+
+    /// If set to `true`, messages queued for a remote system are dropped when it turns out
+    /// to have been [restarted](NetworkStatus::Restarted) while they were waiting,
+    /// as they were addressed to its previous incarnation.
+    ///
+    /// Dropped messages are reported as [QueueDropped](NetworkStatus::QueueDropped),
+    /// and as [delivery failures](NetworkConfig::set_delivery_failure_reporting) if enabled.
+    ///
+    /// Only messages still queued at the dispatcher when the new incarnation connects are rejected.
+    /// Frames are not stamped with the incarnation they are addressed to, so messages which were
+    /// already handed to a channel, e.g. while the previous incarnation was still believed to be alive,
+    /// may be delivered to the new incarnation.
+    ///
+    /// Default value is `false`, i.e. queued messages are delivered to the new incarnation.
+    pub fn set_reject_stale_messages(&mut self, reject: bool) {
+        self.reject_stale_messages = reject;
+    }
+
+    /// Returns `true` if messages queued for a previous incarnation of a remote system are dropped.
+    pub fn get_reject_stale_messages(&self) -> bool {
+        self.reject_stale_messages
+    }
+
+    /// Configures the number of threads driving TCP and UDP I/O.
+    ///
+    /// Connections are sharded across the threads by the address of their remote system,
+    /// so all traffic to and from the same remote system is handled by the same thread,
+    /// preserving its FIFO ordering.
+    /// The first thread also accepts incoming connections and drives the UDP socket.
+    /// Ignored when using a [MemoryNetwork](MemoryNetwork).
+    ///
+    /// Must be at least 1. Default value is 1 thread.
+    pub fn set_network_threads(&mut self, threads: usize) {
+        assert!(threads > 0, "network_threads must be at least 1");
+        self.network_threads = threads;
+    }
+
+    /// Returns the number of threads driving TCP and UDP I/O.
+    pub fn get_network_threads(&self) -> usize {
+        self.network_threads
+    }
+
+    /// Configures the number of TCP connections, called lanes, opened to each remote system.
+    ///
+    /// Each message is sent on the lane picked by hashing its sender and receiver paths,
+    /// so messages between the same pair of actors stay in FIFO order,
+    /// while large messages between some actors do not hold up the messages between others.
+    /// Messages between different pairs of actors may be reordered, however.
+    ///
+    /// The system requesting a connection opens the additional lanes once the first one is established.
+    /// Both systems use the smaller of their configured values,
+    /// so a remote system configured with, or predating, a single lane gets a single connection.
+    /// Ignored when using a [MemoryNetwork](MemoryNetwork).
+    ///
+    /// Must be at least 1. Default value is 1 lane.
+    pub fn set_tcp_lanes(&mut self, lanes: u8) {
+        assert!(lanes > 0, "tcp_lanes must be at least 1");
+        self.tcp_lanes = lanes;
+    }
+
+    /// Returns the number of TCP connections opened to each remote system.
+    pub fn get_tcp_lanes(&self) -> u8 {
+        self.tcp_lanes
     }
 }
 
@@ -197,8 +885,35 @@ impl Default for NetworkConfig {
             buffer_config: BufferConfig::default(),
             custom_allocator: None,
             tcp_nodelay: false,
-            max_connection_retry_attempts: MAX_RETRY_ATTEMPTS,
-            connection_retry_interval: RETRY_CONNECTIONS_INTERVAL,
+            retry_strategy: None,
+            #[cfg(feature = "tls")]
+            tls_config: None,
+            credit_window: CREDIT_WINDOW,
+            credit_update_threshold: CREDIT_UPDATE_THRESHOLD,
+            max_queued_frames: None,
+            max_queued_bytes: None,
+            queue_overflow_policy: QueueOverflowPolicy::default(),
+            delivery_failure_reporting: DeliveryFailureReporting::default(),
+            failure_detector: None,
+            close_on_suspicion: false,
+            idle_timeout: None,
+            memory_network: None,
+            fault_injector: None,
+            compression_threshold: None,
+            cluster_secret: None,
+            reject_stale_messages: false,
+            network_threads: 1,
+            tcp_lanes: 1,
+            write_batch_size: WRITE_BATCH_SIZE,
+            max_udp_datagram_size: MAX_UDP_DATAGRAM_SIZE,
+            udp_reassembly_timeout: UDP_REASSEMBLY_TIMEOUT,
+            reliable_udp_window: RELIABLE_UDP_WINDOW,
+            reliable_udp_retransmit_timeout: RELIABLE_UDP_RETRANSMIT_TIMEOUT,
+            reliable_udp_max_retransmissions: RELIABLE_UDP_MAX_RETRANSMISSIONS,
+            reliable_udp_max_pending: RELIABLE_UDP_MAX_PENDING,
+            multicast_groups: Vec::new(),
+            unix_socket_path: None,
+            custom_transports: CustomTransports::default(),
         }
     }
 }
@@ -218,6 +933,8 @@ impl Default for NetworkConfig {
 #[derive(ComponentDefinition)]
 pub struct NetworkDispatcher {
     ctx: ComponentContext<NetworkDispatcher>,
+    /// Provides indications about connection changes to interested components
+    network_status_port: ProvidedPort<NetworkStatusPort>,
     /// Local map of connection statuses
     connections: NetHashMap<SocketAddr, ConnectionState>,
     /// Network configuration for this dispatcher
@@ -235,11 +952,74 @@ pub struct NetworkDispatcher {
     reaper: lookup::gc::ActorRefReaper,
     notify_ready: Option<KPromise<()>>,
     encode_buffer: EncodeBuffer,
-    /// Stores the number of retry-attempts for connections. Checked and incremented periodically by the reaper.
-    retry_map: FxHashMap<SocketAddr, u8>,
+    /// Stores the pending retry for each connection being re-established.
+    retry_map: FxHashMap<SocketAddr, PendingRetry>,
+    /// Stores the backoff level each connection was established at, and when.
+    /// Used to continue backing off from hosts whose connections are lost again right away.
+    established_map: FxHashMap<SocketAddr, (u8, Instant)>,
+    /// Caches the socket address each domain name based system path was last resolved to.
+    domain_cache: FxHashMap<SystemPath, SocketAddr>,
+    /// The requests waiting for the domain name of a system path to be resolved for the first time.
+    unresolved: FxHashMap<SystemPath, Vec<AwaitingResolution>>,
+    /// The transports of remote systems which are not reached over TCP, by the addresses keying their connections
+    remote_transports: FxHashMap<SocketAddr, RemoteTransport>,
+    /// TCP frames held back by the fault injector, with the time they are released at.
+    delayed_frames: FxHashMap<SocketAddr, VecDeque<(Instant, SerialisedFrame)>>,
+    /// The incarnation each remote system announced when it last connected.
+    ///
+    /// Kept while reconnecting and after the remote system left, to detect restarts,
+    /// and forgotten once this system closes, leaves or gives up on the connection.
+    incarnations: FxHashMap<SocketAddr, Uuid>,
+    /// The remote hosts this system is leaving, whose connections are closed once all frames are sent.
+    departing: FxHashSet<SocketAddr>,
+    /// The remote hosts whose connections are closed on request, without trying to reconnect.
+    closing: FxHashSet<SocketAddr>,
+    /// The remote hosts whose connections were closed for being idle, which are reconnected to for rejected frames.
+    idle: FxHashSet<SocketAddr>,
+    /// The queue of the thread resolving domain names, which is started for the first domain name to resolve.
+    resolver: Option<Sender<DomainResolution>>,
     garbage_buffers: VecDeque<BufferChunk>,
 }
 
+/// A request to the resolver thread to resolve the domain names of `systems`
+struct DomainResolution {
+    systems: Vec<SystemPath>,
+    /// Whether to pick IPv4 over IPv6 addresses, if a domain name resolves to both
+    prefer_ipv4: bool,
+    promise: KPromise<Vec<(SystemPath, io::Result<SocketAddr>)>>,
+}
+
+impl DomainResolution {
+    /// Resolves the domain names, blocking until all lookups completed, and fulfils the promise with the results
+    fn resolve(self) -> () {
+        let DomainResolution {
+            systems,
+            prefer_ipv4,
+            promise,
+        } = self;
+        let resolved = systems
+            .into_iter()
+            .map(|system| {
+                let result = resolve_domain(&system, prefer_ipv4);
+                (system, result)
+            })
+            .collect();
+        // The dispatcher may have been stopped in the meantime
+        let _ = promise.fulfil(resolved);
+    }
+}
+
+/// A scheduled attempt at re-establishing a connection
+struct PendingRetry {
+    /// The number of attempts made so far
+    attempt: u8,
+    /// The backoff level used to compute the delay of the next attempt
+    backoff: u8,
+    /// Whether a connection attempt is in progress, which is given time to complete its handshake
+    awaiting_handshake: bool,
+    timer: ScheduledTimer,
+}
+
 impl NetworkDispatcher {
     /// Create a new dispatcher with the default configuration
     ///
@@ -273,20 +1053,36 @@ impl NetworkDispatcher {
             &cfg.buffer_config,
             &cfg.custom_allocator,
         );
+        let queue_manager = QueueManager::with_limits(
+            cfg.max_queued_frames,
+            cfg.max_queued_bytes,
+            cfg.queue_overflow_policy,
+        );
 
         NetworkDispatcher {
             ctx: ComponentContext::uninitialised(),
+            network_status_port: ProvidedPort::uninitialised(),
             connections: Default::default(),
             cfg,
             lookup,
             net_bridge: None,
             system_path: None,
-            queue_manager: QueueManager::new(),
+            queue_manager,
             reaper,
             notify_ready: Some(notify_ready),
             encode_buffer,
             garbage_buffers: VecDeque::new(),
             retry_map: Default::default(),
+            established_map: Default::default(),
+            domain_cache: Default::default(),
+            unresolved: Default::default(),
+            remote_transports: Default::default(),
+            delayed_frames: Default::default(),
+            incarnations: Default::default(),
+            departing: Default::default(),
+            closing: Default::default(),
+            idle: Default::default(),
+            resolver: None,
         }
     }
 
@@ -317,11 +1113,21 @@ impl NetworkDispatcher {
             .expect("Self can hardly be deallocated!");
         let bridge_logger = self.ctx.log().new(o!("owner" => "Bridge"));
         let network_thread_logger = self.ctx.log().new(o!("owner" => "NetworkThread"));
-        let (mut bridge, _addr) = net::Bridge::new(
+        if self.cfg.failure_detector.is_none() {
+            self.cfg.failure_detector = FailureDetector::from_config(self.ctx.config())
+                .map_err(|e| net::NetworkBridgeErr::Other(format!("Invalid config: {}", e)))?;
+        }
+        if self.cfg.retry_strategy.is_none() {
+            self.cfg.retry_strategy = RetryStrategy::from_config(self.ctx.config())
+                .map_err(|e| net::NetworkBridgeErr::Other(format!("Invalid config: {}", e)))?;
+        }
+        let (mut bridge, addr) = net::Bridge::new(
             self.lookup.clone(),
             network_thread_logger,
             bridge_logger,
             self.cfg.addr,
+            // Only a handle which does not know its system falls back to a fresh incarnation
+            self.ctx.system().incarnation().unwrap_or_else(Uuid::new_v4),
             dispatcher.clone(),
             &self.cfg,
         );
@@ -334,8 +1140,10 @@ impl NetworkDispatcher {
             next
         });
 
+        if let Some(injector) = &self.cfg.fault_injector {
+            injector.register(addr, dispatcher.clone());
+        }
         bridge.set_dispatcher(dispatcher);
-        self.schedule_retries();
         self.net_bridge = Some(bridge);
         Ok(())
     }
@@ -350,6 +1158,9 @@ impl NetworkDispatcher {
 
     fn do_stop(&mut self, _cleanup: bool) -> () {
         if let Some(bridge) = self.net_bridge.take() {
+            if let (Some(injector), Some(addr)) = (&self.cfg.fault_injector, bridge.local_addr()) {
+                injector.unregister(addr);
+            }
             if let Err(e) = bridge.stop() {
                 error!(
                     self.ctx().log(),
@@ -357,6 +1168,8 @@ impl NetworkDispatcher {
                 );
             }
         }
+        // The resolver thread ends once its queue is dropped
+        self.resolver = None;
     }
 
     fn schedule_reaper(&mut self) {
@@ -394,47 +1207,346 @@ impl NetworkDispatcher {
         });
     }
 
-    fn schedule_retries(&mut self) {
-        // First check the retry_map if we should re-request connections
-        let drain = self.retry_map.clone();
-        self.retry_map.clear();
-        for (addr, retry) in drain {
-            if retry < self.cfg.max_connection_retry_attempts {
-                // Make sure we will re-request connection later
-                self.retry_map.insert(addr, retry + 1);
-                if let Some(bridge) = &self.net_bridge {
-                    // Do connection attempt
-                    debug!(
-                        self.ctx().log(),
-                        "Dispatcher retrying connection to host {}, attempt {}/{}",
-                        addr,
-                        retry,
-                        self.cfg.max_connection_retry_attempts
-                    );
-                    bridge.connect(Transport::TCP, addr).unwrap();
-                }
-            } else {
-                // Too many retries, give up on the connection.
-                info!(
+    /// Schedules the given (zero-based) retry `attempt` for `addr`,
+    /// replacing any retry which is already pending for it.
+    ///
+    /// The delay is determined by the `backoff` level, which is usually equal to `attempt`,
+    /// but keeps growing across reconnections to a flapping host.
+    /// If a connection attempt is `awaiting_handshake`, the delay is at least the
+    /// [handshake grace period](HANDSHAKE_GRACE_PERIOD), unless the attempt fails before.
+    fn schedule_retry(
+        &mut self,
+        addr: SocketAddr,
+        attempt: u8,
+        backoff: u8,
+        awaiting_handshake: bool,
+    ) {
+        let mut delay = self.cfg.get_retry_strategy().delay(backoff);
+        if awaiting_handshake {
+            delay = delay.max(HANDSHAKE_GRACE_PERIOD);
+        }
+        let timer = self.schedule_once(delay, move |target, _id| {
+            target.retry_connection(addr);
+            Handled::Ok
+        });
+        let retry = PendingRetry {
+            attempt,
+            backoff,
+            awaiting_handshake,
+            timer,
+        };
+        if let Some(old_retry) = self.retry_map.insert(addr, retry) {
+            self.cancel_timer(old_retry.timer);
+        }
+    }
+
+    /// Cancels the pending retry for `addr`, returning the backoff level it had reached.
+    fn cancel_retry(&mut self, addr: &SocketAddr) -> Option<u8> {
+        let retry = self.retry_map.remove(addr)?;
+        self.cancel_timer(retry.timer);
+        Some(retry.backoff)
+    }
+
+    /// Starts re-establishing a lost connection to `addr`.
+    ///
+    /// If the connection was only just established, the host is considered flapping
+    /// and the backoff continues from where it left off, instead of starting over.
+    fn start_retrying(&mut self, addr: SocketAddr) {
+        let strategy = self.cfg.get_retry_strategy();
+        let backoff = match self.established_map.remove(&addr) {
+            Some((backoff, since)) if since.elapsed() < strategy.max_delay() => {
+                backoff.saturating_add(1)
+            }
+            _ => 0,
+        };
+        self.schedule_retry(addr, 0, backoff, false);
+    }
+
+    fn retry_connection(&mut self, addr: SocketAddr) {
+        if !self.retry_map.contains_key(&addr) {
+            return; // Connected in the meantime
+        }
+        // The host may have moved, if we know it by name
+        let systems: Vec<SystemPath> = self
+            .domain_cache
+            .iter()
+            .filter(|(_, cached)| **cached == addr)
+            .map(|(system, _)| system.clone())
+            .collect();
+        if systems.is_empty() {
+            self.attempt_connection(addr);
+        } else {
+            self.resolve_domains(systems, move |dispatcher, resolved| {
+                let addr = dispatcher.reresolved_domains(addr, resolved);
+                dispatcher.attempt_connection(addr);
+            });
+        }
+    }
+
+    /// Makes the next attempt of the pending retry for `addr`, or gives up on it.
+    fn attempt_connection(&mut self, addr: SocketAddr) {
+        let (attempt, backoff) = match self.retry_map.get(&addr) {
+            Some(retry) => (retry.attempt, retry.backoff),
+            None => return, // Connected in the meantime
+        };
+        let max_attempts = self.cfg.get_max_connection_retry_attempts();
+        if attempt < max_attempts {
+            let mut awaiting_handshake = false;
+            if self.is_partitioned(&addr) {
+                debug!(
                     self.ctx().log(),
-                    "Dispatcher giving up on remote host {}, dropping queues", addr
+                    "Dispatcher can not reach partitioned host {}, attempt {}/{}",
+                    addr,
+                    attempt + 1,
+                    max_attempts
                 );
-                self.queue_manager.drop_queue(&addr);
-                self.connections.remove(&addr);
+            } else if let Some(bridge) = &self.net_bridge {
+                // Do connection attempt
+                debug!(
+                    self.ctx().log(),
+                    "Dispatcher retrying connection to host {}, attempt {}/{}",
+                    addr,
+                    attempt + 1,
+                    max_attempts
+                );
+                match connect(bridge, &self.remote_transports, addr) {
+                    Ok(()) => awaiting_handshake = true,
+                    Err(e) => error!(self.ctx().log(), "Could not connect to {}: {:?}", addr, e),
+                }
+            }
+            // Make sure we will re-request connection later
+            self.schedule_retry(
+                addr,
+                attempt + 1,
+                backoff.saturating_add(1),
+                awaiting_handshake,
+            );
+        } else {
+            // Too many retries, give up on the connection.
+            info!(
+                self.ctx().log(),
+                "Dispatcher giving up on remote host {}, dropping queues", addr
+            );
+            self.retry_map.remove(&addr);
+            let dropped = self.queue_manager.drop_queue(&addr);
+            self.connections.remove(&addr);
+            self.incarnations.remove(&addr);
+            self.idle.remove(&addr);
+            let system_path = self.remote_system_path(addr);
+            self.network_status_port
+                .trigger(NetworkStatus::RetriesExhausted(system_path.clone()));
+            if !dropped.is_empty() {
+                self.network_status_port
+                    .trigger(NetworkStatus::QueueDropped(system_path, dropped.len()));
+                self.report_delivery_failures(dropped, DeliveryFailureReason::RetriesExhausted);
             }
         }
-        self.schedule_once(
-            Duration::from_millis(self.cfg.connection_retry_interval),
-            move |target, _id| {
-                target.schedule_retries();
-                Handled::Ok
-            },
-        );
     }
 
-    fn on_event(&mut self, ev: EventEnvelope) {
-        match ev {
-            EventEnvelope::Network(ev) => match ev {
+    /// Returns the socket address of `system`, if it is known.
+    ///
+    /// Domain names are only known once they have been resolved, see [await_resolution](NetworkDispatcher::await_resolution),
+    /// and are cached until they are re-resolved when reconnecting.
+    fn socket_addr(&mut self, system: &SystemPath) -> Option<SocketAddr> {
+        match system.host() {
+            Address::Ip(ip) => Some(SocketAddr::new(*ip, system.port())),
+            Address::Unix(path) => {
+                let addr = unix_socket_key(path);
+                self.remote_transports
+                    .entry(addr)
+                    .or_insert_with(|| RemoteTransport::Unix(path.clone()));
+                Some(addr)
+            }
+            Address::Domain(_) => self.domain_cache.get(system).copied(),
+        }
+    }
+
+    /// Has the `request` wait until the domain name of `system` is resolved,
+    /// starting to resolve it, unless that is already underway.
+    fn await_resolution(&mut self, system: SystemPath, request: AwaitingResolution) {
+        match self.unresolved.get_mut(&system) {
+            Some(requests) => requests.push(request),
+            None => {
+                self.unresolved.insert(system.clone(), vec![request]);
+                self.resolve_domains(vec![system], |dispatcher, resolved| {
+                    for (system, result) in resolved {
+                        dispatcher.resolved_domain(system, result);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Caches the first resolution of the domain name of `system`, and resumes the requests waiting for it.
+    fn resolved_domain(&mut self, system: SystemPath, result: io::Result<SocketAddr>) {
+        let requests = self.unresolved.remove(&system).unwrap_or_default();
+        match result {
+            Ok(addr) => {
+                debug!(self.ctx.log(), "Resolved {} to {}", system, addr);
+                self.domain_cache.insert(system.clone(), addr);
+                for request in requests {
+                    match request {
+                        AwaitingResolution::Msg(msg) => {
+                            if let Err(e) = self.route(*msg) {
+                                error!(self.ctx.log(), "Failed to route message: {:?}", e);
+                            }
+                        }
+                        AwaitingResolution::Connect => self.connect_remote(system.clone()),
+                        AwaitingResolution::Close => {
+                            self.manage_connections(ConnectionRequest::Close(system.clone()))
+                        }
+                        AwaitingResolution::Leave => self.leave(Departure::Remote(system.clone())),
+                    }
+                }
+            }
+            Err(e) => {
+                error!(
+                    self.ctx.log(),
+                    "Could not resolve {}, dropping {} requests waiting for it: {}",
+                    system,
+                    requests.len(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Resolves the domain names of `systems` on the resolver thread, as lookups may block,
+    /// and hands the results over to `then` back on the dispatcher.
+    fn resolve_domains<F>(&mut self, systems: Vec<SystemPath>, then: F)
+    where
+        F: FnOnce(&mut NetworkDispatcher, Vec<(SystemPath, io::Result<SocketAddr>)>)
+            + Send
+            + 'static,
+    {
+        let prefer_ipv4 = match self.system_path_ref().socket_addr() {
+            Some(own) => own.is_ipv4(),
+            None => true,
+        };
+        let (promise, resolved) = utils::promise();
+        let request = DomainResolution {
+            systems: systems.clone(),
+            prefer_ipv4,
+            promise,
+        };
+        let sent = self.resolver().and_then(|resolver| {
+            resolver
+                .send(request)
+                .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "The resolver thread died"))
+        });
+        match sent {
+            Ok(()) => self.spawn_local(move |mut async_self| async move {
+                let resolved = resolved.await.unwrap_or_else(|_| {
+                    failed_resolution(systems, ErrorKind::Other, "The resolver thread died")
+                });
+                then(&mut async_self, resolved);
+                Handled::Ok
+            }),
+            Err(e) => {
+                // Started again for the next domain names to resolve
+                self.resolver = None;
+                let resolved = failed_resolution(systems, e.kind(), &e.to_string());
+                then(self, resolved);
+            }
+        }
+    }
+
+    /// Returns the queue of the resolver thread, starting the thread if it is not running yet
+    fn resolver(&mut self) -> io::Result<&Sender<DomainResolution>> {
+        if self.resolver.is_none() {
+            let (sender, requests) = unbounded::<DomainResolution>();
+            thread::Builder::new()
+                .name("kompact-resolver".to_string())
+                .spawn(move || {
+                    for request in requests {
+                        request.resolve();
+                    }
+                })?;
+            self.resolver = Some(sender);
+        }
+        Ok(self
+            .resolver
+            .as_ref()
+            .expect("The resolver thread was just started"))
+    }
+
+    /// Returns the system path of the remote host, whose channel is keyed by `addr`
+    ///
+    /// Channels over a Unix domain socket are keyed by its synthetic address, see [socket_addr](NetworkDispatcher::socket_addr),
+    /// and so are channels accepted from remote systems which announced their socket. All others are keyed
+    /// by a TCP or custom transport address.
+    fn remote_system_path(&self, addr: SocketAddr) -> SystemPath {
+        match self.remote_transports.get(&addr) {
+            Some(RemoteTransport::Unix(path)) => SystemPath::with_unix_socket(path.clone()),
+            Some(RemoteTransport::Custom(id)) => {
+                SystemPath::with_socket(Transport::Custom(*id), addr)
+            }
+            None => SystemPath::with_socket(Transport::TCP, addr),
+        }
+    }
+
+    /// Returns the protocol the frames of the connection keyed by `addr` are sent over
+    fn stream_protocol(&self, addr: SocketAddr) -> net::Protocol {
+        match self.remote_transports.get(&addr) {
+            Some(RemoteTransport::Custom(id)) => net::Protocol::Custom(*id),
+            _ => net::Protocol::TCP,
+        }
+    }
+
+    /// Caches the domain names which were last resolved to `addr` and have been `resolved` again,
+    /// and returns the address to connect to from now on.
+    ///
+    /// If a domain name now resolves to a different address, the pending retry and queued frames
+    /// for `addr` are moved over to the new address.
+    fn reresolved_domains(
+        &mut self,
+        addr: SocketAddr,
+        resolved: Vec<(SystemPath, io::Result<SocketAddr>)>,
+    ) -> SocketAddr {
+        let mut new_addr = addr;
+        for (system, result) in resolved {
+            match result {
+                Ok(resolved) => {
+                    if resolved != addr {
+                        info!(
+                            self.ctx.log(),
+                            "{} moved from {} to {}", system, addr, resolved
+                        );
+                        new_addr = resolved;
+                    }
+                    self.domain_cache.insert(system, resolved);
+                }
+                Err(e) => {
+                    warn!(
+                        self.ctx.log(),
+                        "Could not re-resolve {}, keeping {}: {}", system, addr, e
+                    );
+                }
+            }
+        }
+        if new_addr != addr {
+            // Unless connected in the meantime, the retry continues at the new address
+            if let Some(retry) = self.retry_map.remove(&addr) {
+                if let Some(old_retry) = self.retry_map.insert(new_addr, retry) {
+                    self.cancel_timer(old_retry.timer);
+                }
+                self.established_map.remove(&addr);
+                self.connections.remove(&addr);
+                self.queue_manager.move_queue(&addr, new_addr);
+                if let Some(incarnation) = self.incarnations.remove(&addr) {
+                    self.incarnations.insert(new_addr, incarnation);
+                }
+                self.connections
+                    .entry(new_addr)
+                    .or_insert(ConnectionState::Initializing);
+            }
+        }
+        new_addr
+    }
+
+    fn on_event(&mut self, ev: EventEnvelope) {
+        match ev {
+            EventEnvelope::Network(ev) => match ev {
                 NetworkEvent::Connection(addr, conn_state) => {
                     if let Err(e) = self.on_conn_state(addr, conn_state) {
                         error!(
@@ -443,18 +1555,149 @@ impl NetworkDispatcher {
                         )
                     }
                 }
+                NetworkEvent::ReliableUdpConnection(addr, conn_state) => {
+                    self.on_reliable_udp_state(addr, conn_state);
+                }
+                NetworkEvent::ReliableUdpOverflow(addr, overflow) => {
+                    let path = SystemPath::with_socket(Transport::RUDP, addr);
+                    self.handle_overflow(path, overflow);
+                }
+                NetworkEvent::ReliableUdpDropped(addr, dropped) => {
+                    self.network_status_port
+                        .trigger(NetworkStatus::QueueDropped(
+                            SystemPath::with_socket(Transport::RUDP, addr),
+                            dropped.len(),
+                        ));
+                    self.report_delivery_failures(dropped, DeliveryFailureReason::RetriesExhausted);
+                }
+                NetworkEvent::Incarnation(addr, incarnation) => {
+                    self.on_incarnation(addr, incarnation);
+                }
+                NetworkEvent::UnixSocket(addr, path) => {
+                    // Reconnected over the socket, should the channel be lost
+                    self.remote_transports
+                        .entry(addr)
+                        .or_insert(RemoteTransport::Unix(path));
+                }
                 NetworkEvent::Data(_) => {
                     // TODO shouldn't be receiving these here, as they should be routed directly to the ActorRef
                     debug!(self.ctx().log(), "Received important data!");
                 }
+                NetworkEvent::Suspected(addr) => {
+                    warn!(
+                        self.ctx().log(),
+                        "Remote host {} is suspected to have failed", addr
+                    );
+                    self.network_status_port
+                        .trigger(NetworkStatus::Suspected(self.remote_system_path(addr)));
+                }
+                NetworkEvent::IncompleteUdpMessages(addr, count) => {
+                    warn!(
+                        self.ctx().log(),
+                        "Dropped {} incomplete UDP messages from {}", count, addr
+                    );
+                    self.network_status_port
+                        .trigger(NetworkStatus::IncompleteUdpMessages(
+                            SystemPath::with_socket(Transport::UDP, addr),
+                            count,
+                        ));
+                }
+                NetworkEvent::Alive(addr) => {
+                    info!(
+                        self.ctx().log(),
+                        "Remote host {} is no longer suspected", addr
+                    );
+                    self.network_status_port
+                        .trigger(NetworkStatus::Alive(self.remote_system_path(addr)));
+                }
+                NetworkEvent::PeerLeft(addr) => {
+                    if let Err(e) = self.on_peer_left(addr) {
+                        error!(
+                            self.ctx().log(),
+                            "Error while closing the connection to {}, \n{:?}", addr, e
+                        )
+                    }
+                }
+                NetworkEvent::Idle(addr) => {
+                    if let Err(e) = self.on_idle(addr) {
+                        error!(
+                            self.ctx().log(),
+                            "Error while closing the idle connection to {}, \n{:?}", addr, e
+                        )
+                    }
+                }
+                NetworkEvent::Partitioned(addr) => {
+                    info!(self.ctx().log(), "Partitioned from remote host {}", addr);
+                    self.lose_partitioned_connection(addr);
+                }
+                NetworkEvent::Healed(addr) => {
+                    info!(
+                        self.ctx().log(),
+                        "Partition from remote host {} healed", addr
+                    );
+                    if self.retry_map.contains_key(&addr) {
+                        // Reconnect right away, instead of waiting for the next retry
+                        self.retry_connection(addr);
+                    }
+                }
                 NetworkEvent::RejectedFrame(addr, frame) => {
                     // These are messages which we routed to a network-thread before they lost the connection.
-                    self.queue_manager.enqueue_priority_frame(frame, addr);
+                    if self.connections.contains_key(&addr) {
+                        self.queue_manager.enqueue_priority_frame(frame, addr);
+                    } else if self.idle.contains(&addr) {
+                        // Sent just as the idle connection was closed, so we reconnect for it
+                        self.queue_manager.enqueue_priority_frame(frame, addr);
+                        self.open_connection(addr);
+                    } else {
+                        // We already gave up on the host, so they would never be sent
+                        self.network_status_port
+                            .trigger(NetworkStatus::QueueDropped(
+                                self.remote_system_path(addr),
+                                1,
+                            ));
+                        self.report_delivery_failures(
+                            vec![frame],
+                            DeliveryFailureReason::ConnectionLost,
+                        );
+                    }
                 }
             },
         }
     }
 
+    /// Records the `incarnation` of the remote system at `addr`, detecting whether it was restarted
+    ///
+    /// Must be handled before the connection to `addr` is established, such that stale messages
+    /// can be rejected before they are sent to the new incarnation.
+    fn on_incarnation(&mut self, addr: SocketAddr, incarnation: Uuid) -> () {
+        match self.incarnations.insert(addr, incarnation) {
+            Some(previous) if previous != incarnation => {
+                info!(
+                    self.ctx().log(),
+                    "Remote system {} restarted, incarnation {} replaced {}",
+                    addr,
+                    incarnation,
+                    previous
+                );
+                let system_path = self.remote_system_path(addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::Restarted(system_path.clone()));
+                if self.cfg.reject_stale_messages {
+                    let stale = self.queue_manager.drop_queue(&addr);
+                    if !stale.is_empty() {
+                        self.network_status_port
+                            .trigger(NetworkStatus::QueueDropped(system_path, stale.len()));
+                        self.report_delivery_failures(
+                            stale,
+                            DeliveryFailureReason::StaleIncarnation,
+                        );
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
     fn on_conn_state(
         &mut self,
         addr: SocketAddr,
@@ -462,26 +1705,78 @@ impl NetworkDispatcher {
     ) -> Result<(), NetworkBridgeErr> {
         use self::ConnectionState::*;
         match state {
+            Closed if self.departing.remove(&addr) => {
+                info!(self.ctx().log(), "Left remote host {}", addr);
+                self.forget_connection(addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::Left(self.remote_system_path(addr)));
+                if let Some(bridge) = &self.net_bridge {
+                    bridge.ack_closed(addr)?;
+                }
+                return Ok(());
+            }
+            Closed if self.closing.remove(&addr) => {
+                info!(
+                    self.ctx().log(),
+                    "Closed connection to remote host {}", addr
+                );
+                self.forget_connection(addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::ConnectionClosed(
+                        self.remote_system_path(addr),
+                    ));
+                if let Some(bridge) = &self.net_bridge {
+                    bridge.ack_closed(addr)?;
+                }
+                return Ok(());
+            }
+            Connected(_) if self.is_partitioned(&addr) => {
+                // The connection is unusable until the partition is healed
+                debug!(
+                    self.ctx().log(),
+                    "Ignoring connection to partitioned host {}", addr
+                );
+                if !self.retry_map.contains_key(&addr) {
+                    self.start_retrying(addr);
+                }
+                self.connections.insert(addr, Closed);
+                return Ok(());
+            }
             Connected(ref mut _frame_sender) => {
                 info!(
                     self.ctx().log(),
                     "registering newly connected conn at {:?}", addr
                 );
-                let _ = self.retry_map.remove(&addr);
+                let backoff = self.cancel_retry(&addr).unwrap_or(0);
+                self.established_map.insert(addr, (backoff, Instant::now()));
+                self.idle.remove(&addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::ConnectionEstablished(
+                        self.remote_system_path(addr),
+                    ));
                 if self.queue_manager.has_frame(&addr) {
                     // Drain as much as possible
                     while let Some(frame) = self.queue_manager.pop_frame(&addr) {
-                        if let Some(bridge) = &self.net_bridge {
-                            //println!("Sending queued frame to newly established connection");
-                            bridge.route(addr, frame, net::Protocol::TCP)?;
-                        }
+                        self.send_frame(addr, frame, self.stream_protocol(addr))?;
                     }
                 }
             }
             Closed => {
-                if self.retry_map.get(&addr).is_none() {
-                    warn!(self.ctx().log(), "connection closed for {:?}", addr);
-                    self.retry_map.insert(addr, 0); // Make sure we try to re-establish the connection
+                match self.retry_map.get(&addr) {
+                    None => {
+                        warn!(self.ctx().log(), "connection closed for {:?}", addr);
+                        self.start_retrying(addr); // Make sure we try to re-establish the connection
+                    }
+                    Some(retry) if retry.awaiting_handshake => {
+                        // The connection attempt failed, retry without waiting out the grace period
+                        let (attempt, backoff) = (retry.attempt, retry.backoff);
+                        self.schedule_retry(addr, attempt, backoff, false);
+                    }
+                    Some(_) => (),
+                }
+                if let Some(Connected(_)) = self.connections.get(&addr) {
+                    self.network_status_port
+                        .trigger(NetworkStatus::ConnectionLost(self.remote_system_path(addr)));
                 }
                 // Ack the close message
                 if let Some(bridge) = &self.net_bridge {
@@ -510,6 +1805,30 @@ impl NetworkDispatcher {
         Ok(())
     }
 
+    /// Indicates changes in the state of a reliable UDP session like those of TCP connections
+    fn on_reliable_udp_state(&mut self, addr: SocketAddr, state: ConnectionState) {
+        let path = SystemPath::with_socket(Transport::RUDP, addr);
+        match state {
+            ConnectionState::Connected(_) => {
+                info!(
+                    self.ctx().log(),
+                    "Reliable UDP messages to {} are being acknowledged", addr
+                );
+                self.network_status_port
+                    .trigger(NetworkStatus::ConnectionEstablished(path));
+            }
+            ConnectionState::Closed | ConnectionState::Error(_) => {
+                warn!(
+                    self.ctx().log(),
+                    "Lost reliable UDP session to {}: {:?}", addr, state
+                );
+                self.network_status_port
+                    .trigger(NetworkStatus::ConnectionLost(path));
+            }
+            _ => (), // Don't care
+        }
+    }
+
     /// Forwards `msg` up to a local `dst` actor, if it exists.
     fn route_local<R>(&mut self, msg: R) -> Result<(), NetworkBridgeErr>
     where
@@ -562,16 +1881,45 @@ impl NetworkDispatcher {
     {
         let dst = msg.destination();
         let protocol: Transport = dst.protocol();
-        let addr = SocketAddr::new(*dst.address(), dst.port());
+        let addr = match self.socket_addr(dst.system()) {
+            Some(addr) => addr,
+            None => {
+                let system = dst.system().clone();
+                let msg = msg.into_local()?;
+                self.await_resolution(system, AwaitingResolution::Msg(Box::new(msg)));
+                return Ok(());
+            }
+        };
+        if self.is_own_addr(addr) {
+            // The domain name or Unix domain socket refers to this system
+            return self.route_local(msg);
+        }
+        if addr.ip().is_multicast() && protocol != Transport::UDP {
+            warn!(
+                self.ctx.log(),
+                "Dropping message to multicast group {}, as it can only be reached over UDP.", addr
+            );
+            return Ok(());
+        }
         let serialised = {
             let buf = &mut self.encode_buffer.get_buffer_encoder();
             msg.into_serialised(buf)?
         };
 
         match protocol {
-            Transport::TCP => self.route_remote_tcp(addr, serialised),
-            Transport::UDP => self.route_remote_udp(addr, serialised),
-            x => unimplemented!("Unsupported protocol: {}", x),
+            Transport::TCP | Transport::UDS => self.route_remote_tcp(addr, serialised),
+            Transport::UDP => self.route_remote_udp(addr, serialised, net::Protocol::UDP),
+            Transport::RUDP => self.route_remote_udp(addr, serialised, net::Protocol::ReliableUDP),
+            Transport::Custom(id) => {
+                self.remote_transports
+                    .entry(addr)
+                    .or_insert(RemoteTransport::Custom(id));
+                self.route_remote_tcp(addr, serialised)
+            }
+            x => Err(NetworkBridgeErr::Other(format!(
+                "Unsupported protocol: {}",
+                x
+            ))),
         }
     }
 
@@ -579,9 +1927,15 @@ impl NetworkDispatcher {
         &mut self,
         addr: SocketAddr,
         serialised: SerialisedFrame,
+        protocol: net::Protocol,
     ) -> Result<(), NetworkBridgeErr> {
-        if let Some(bridge) = &self.net_bridge {
-            bridge.route(addr, serialised, net::Protocol::UDP)?;
+        if self.is_partitioned(&addr) {
+            debug!(
+                self.ctx.log(),
+                "Dropping UDP message to {}, as it is partitioned.", addr
+            );
+        } else if self.net_bridge.is_some() {
+            self.send_frame(addr, serialised, protocol)?;
         } else {
             warn!(
                 self.ctx.log(),
@@ -596,6 +1950,11 @@ impl NetworkDispatcher {
         addr: SocketAddr,
         serialised: SerialisedFrame,
     ) -> Result<(), NetworkBridgeErr> {
+        if self.is_partitioned(&addr) {
+            self.lose_partitioned_connection(addr);
+        }
+        let mut overflow = Vec::new();
+        let mut sendable = Vec::new();
         let state: &mut ConnectionState =
             self.connections.entry(addr).or_insert(ConnectionState::New);
         let next: Option<ConnectionState> = match *state {
@@ -604,12 +1963,13 @@ impl NetworkDispatcher {
                     self.ctx.log(),
                     "No connection found; establishing and queuing frame"
                 );
-                self.queue_manager.enqueue_frame(serialised, addr);
+                overflow = self.queue_manager.enqueue_frame(serialised, addr);
 
                 if let Some(ref mut bridge) = self.net_bridge {
                     debug!(self.ctx.log(), "Establishing new connection to {:?}", addr);
-                    self.retry_map.insert(addr, 0); // Make sure we will re-request connection later
-                    bridge.connect(Transport::TCP, addr).unwrap();
+                    if let Err(e) = connect(bridge, &self.remote_transports, addr) {
+                        error!(self.ctx.log(), "Could not connect to {}: {:?}", addr, e);
+                    }
                     Some(ConnectionState::Initializing)
                 } else {
                     error!(self.ctx.log(), "No network bridge found; dropping message");
@@ -618,30 +1978,26 @@ impl NetworkDispatcher {
             }
             ConnectionState::Connected(_) => {
                 if self.queue_manager.has_frame(&addr) {
-                    self.queue_manager.enqueue_frame(serialised, addr);
+                    overflow = self.queue_manager.enqueue_frame(serialised, addr);
 
-                    if let Some(bridge) = &self.net_bridge {
-                        while let Some(frame) = self.queue_manager.pop_frame(&addr) {
-                            bridge.route(addr, frame, net::Protocol::TCP)?;
-                        }
+                    while let Some(frame) = self.queue_manager.pop_frame(&addr) {
+                        sendable.push(frame);
                     }
                     None
                 } else {
                     // Send frame
-                    if let Some(bridge) = &self.net_bridge {
-                        bridge.route(addr, serialised, net::Protocol::TCP)?;
-                    }
+                    sendable.push(serialised);
                     None
                 }
             }
             ConnectionState::Initializing => {
                 //debug!(self.ctx.log(), "Connection is initializing; queuing frame");
-                self.queue_manager.enqueue_frame(serialised, addr);
+                overflow = self.queue_manager.enqueue_frame(serialised, addr);
                 None
             }
             ConnectionState::Closed => {
                 // Enqueue the Frame. The connection will sort itself out or drop the queue eventually
-                self.queue_manager.enqueue_frame(serialised, addr);
+                overflow = self.queue_manager.enqueue_frame(serialised, addr);
                 None
             }
             _ => None,
@@ -649,10 +2005,553 @@ impl NetworkDispatcher {
 
         if let Some(next) = next {
             *state = next;
+            if let ConnectionState::Initializing = state {
+                // Make sure we will re-request connection later
+                self.schedule_retry(addr, 0, 0, true);
+            }
+        }
+        let protocol = self.stream_protocol(addr);
+        for frame in sendable {
+            self.send_frame(addr, frame, protocol)?;
         }
+        self.handle_queue_overflow(addr, overflow);
         Ok(())
     }
 
+    /// Returns `true` if `addr` is the address of this system, or the synthetic address of its Unix domain socket
+    fn is_own_addr(&mut self, addr: SocketAddr) -> bool {
+        self.system_path_ref().socket_addr() == Some(addr)
+            || self.cfg.get_unix_socket_path().map(unix_socket_key) == Some(addr)
+    }
+
+    /// Handles a `request` to manage the connections to remote systems
+    fn manage_connections(&mut self, request: ConnectionRequest) -> () {
+        match request {
+            ConnectionRequest::Connect(system) => self.connect_remote(system),
+            ConnectionRequest::List(promise) => {
+                let connections = self.connection_infos();
+                promise.fulfil(connections).unwrap_or_else(|e| {
+                    error!(self.ctx.log(), "Could not notify listeners: {:?}", e)
+                });
+            }
+            ConnectionRequest::Close(system) => match self.socket_addr(&system) {
+                Some(addr) => {
+                    if let Err(e) = self.close_connection(addr) {
+                        error!(self.ctx.log(), "Could not close {}: {:?}", addr, e);
+                    }
+                }
+                None if self.unresolved.contains_key(&system) => {
+                    self.await_resolution(system, AwaitingResolution::Close)
+                }
+                None => debug!(
+                    self.ctx.log(),
+                    "Not closing the connection to {}, as it was never resolved", system
+                ),
+            },
+        }
+    }
+
+    /// Establishes a connection to the remote `system` before the first message is sent to it
+    fn connect_remote(&mut self, system: SystemPath) -> () {
+        let addr = match self.socket_addr(&system) {
+            Some(addr) => addr,
+            None => {
+                self.await_resolution(system, AwaitingResolution::Connect);
+                return;
+            }
+        };
+        match system.protocol() {
+            Transport::TCP | Transport::UDS => (),
+            Transport::Custom(id) => {
+                self.remote_transports
+                    .entry(addr)
+                    .or_insert(RemoteTransport::Custom(id));
+            }
+            other => {
+                warn!(
+                    self.ctx.log(),
+                    "Can not connect to {}, as {} is connectionless", system, other
+                );
+                return;
+            }
+        }
+        if self.is_own_addr(addr) || self.connections.contains_key(&addr) {
+            debug!(
+                self.ctx.log(),
+                "Not connecting to {}, as it is this system or already connected", system
+            );
+            return;
+        }
+        self.open_connection(addr);
+    }
+
+    /// Starts establishing a connection to the remote host `addr`, which is not connected
+    fn open_connection(&mut self, addr: SocketAddr) -> () {
+        if let Some(ref bridge) = self.net_bridge {
+            debug!(self.ctx.log(), "Establishing new connection to {:?}", addr);
+            if let Err(e) = connect(bridge, &self.remote_transports, addr) {
+                error!(self.ctx.log(), "Could not connect to {}: {:?}", addr, e);
+            }
+            self.connections.insert(addr, ConnectionState::Initializing);
+            // Make sure we will re-request connection later
+            self.schedule_retry(addr, 0, 0, true);
+        } else {
+            warn!(
+                self.ctx.log(),
+                "No network bridge found; can not connect to {}", addr
+            );
+        }
+    }
+
+    /// Returns the current connections, along with the frames queued for them
+    fn connection_infos(&self) -> Vec<ConnectionInfo> {
+        self.connections
+            .iter()
+            .map(|(addr, state)| ConnectionInfo {
+                system: self.remote_system_path(*addr),
+                state: copy_connection_state(state),
+                queued_frames: self.queue_manager.queued_frames(addr),
+                queued_bytes: self.queue_manager.queued_bytes(addr),
+            })
+            .collect()
+    }
+
+    /// Closes the connection to the remote host `addr` right away, dropping the frames queued for it
+    ///
+    /// Connections which are not established are given up on without involving the bridge.
+    fn close_connection(&mut self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        match self.connections.get(&addr) {
+            Some(ConnectionState::Connected(_)) => {
+                if let Some(bridge) = &self.net_bridge {
+                    info!(self.ctx.log(), "Closing connection to remote host {}", addr);
+                    bridge.close(self.stream_protocol(addr), addr)?;
+                    self.departing.remove(&addr);
+                    self.closing.insert(addr);
+                }
+            }
+            Some(_) => {
+                info!(
+                    self.ctx.log(),
+                    "Closing connection to remote host {}, which is not connected", addr
+                );
+                self.departing.remove(&addr);
+                self.forget_connection(addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::ConnectionClosed(
+                        self.remote_system_path(addr),
+                    ));
+            }
+            None => debug!(
+                self.ctx.log(),
+                "Not connected to remote host {}, there is nothing to close", addr
+            ),
+        }
+        Ok(())
+    }
+
+    /// Gracefully leaves the remote systems of `departure`
+    fn leave(&mut self, departure: Departure) -> () {
+        let addrs: Vec<SocketAddr> = match departure {
+            Departure::Remote(system) => match self.socket_addr(&system) {
+                Some(addr) => vec![addr],
+                None if self.unresolved.contains_key(&system) => {
+                    self.await_resolution(system, AwaitingResolution::Leave);
+                    return;
+                }
+                None => {
+                    debug!(
+                        self.ctx.log(),
+                        "Not leaving {}, as it was never resolved", system
+                    );
+                    return;
+                }
+            },
+            Departure::All => self.connections.keys().copied().collect(),
+        };
+        for addr in addrs {
+            if let Err(e) = self.leave_remote(addr) {
+                error!(self.ctx.log(), "Could not leave {}: {:?}", addr, e);
+            }
+        }
+    }
+
+    /// Sends the frames queued for the remote host `addr`, and then has the connection to it closed with a Bye.
+    ///
+    /// Connections which are not established are given up on right away, dropping their queued frames.
+    fn leave_remote(&mut self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        match self.connections.get(&addr) {
+            Some(ConnectionState::Connected(_)) => {
+                let protocol = self.stream_protocol(addr);
+                while let Some(frame) = self.queue_manager.pop_frame(&addr) {
+                    self.send_frame(addr, frame, protocol)?;
+                }
+                if let Some(bridge) = &self.net_bridge {
+                    info!(self.ctx.log(), "Leaving remote host {}", addr);
+                    bridge.leave(protocol, addr)?;
+                    self.departing.insert(addr);
+                }
+            }
+            Some(_) => {
+                info!(
+                    self.ctx.log(),
+                    "Leaving remote host {}, which is not connected", addr
+                );
+                self.forget_connection(addr);
+                self.network_status_port
+                    .trigger(NetworkStatus::Left(self.remote_system_path(addr)));
+            }
+            None => debug!(
+                self.ctx.log(),
+                "Not connected to remote host {}, there is nothing to leave", addr
+            ),
+        }
+        Ok(())
+    }
+
+    /// Closes the connection to the remote host `addr`, which said Bye, without trying to reconnect
+    ///
+    /// Frames queued or rejected for the remote host in the meantime are dropped.
+    fn on_peer_left(&mut self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        info!(self.ctx.log(), "Remote host {} left", addr);
+        self.departing.remove(&addr);
+        if let Some(ConnectionState::Connected(_)) = self.connections.get(&addr) {
+            self.network_status_port
+                .trigger(NetworkStatus::PeerLeft(self.remote_system_path(addr)));
+        }
+        // Systems say Bye when shutting down, so their incarnation is kept to detect a restart
+        let incarnation = self.incarnations.get(&addr).copied();
+        self.forget_connection(addr);
+        if let Some(incarnation) = incarnation {
+            self.incarnations.insert(addr, incarnation);
+        }
+        if let Some(bridge) = &self.net_bridge {
+            bridge.ack_closed(addr)?;
+        }
+        Ok(())
+    }
+
+    /// Forgets the connection to the remote host `addr`, which either host closed for being idle, without retrying it
+    ///
+    /// Frames queued or rejected for the remote host in the meantime are sent over a new connection.
+    fn on_idle(&mut self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        info!(self.ctx.log(), "Closed idle connection to {}", addr);
+        self.departing.remove(&addr);
+        self.closing.remove(&addr);
+        self.cancel_retry(&addr);
+        self.connections.remove(&addr);
+        self.idle.insert(addr);
+        self.network_status_port
+            .trigger(NetworkStatus::ConnectionIdle(self.remote_system_path(addr)));
+        if let Some(bridge) = &self.net_bridge {
+            bridge.ack_closed(addr)?;
+        }
+        if self.queue_manager.has_frame(&addr) {
+            self.open_connection(addr);
+        }
+        Ok(())
+    }
+
+    /// Forgets the connection to the remote host `addr` without retrying it,
+    /// dropping the frames queued for it.
+    ///
+    /// Sending another message to the remote host connects to it again.
+    fn forget_connection(&mut self, addr: SocketAddr) -> () {
+        self.cancel_retry(&addr);
+        self.connections.remove(&addr);
+        self.idle.remove(&addr);
+        self.incarnations.remove(&addr);
+        let dropped = self.queue_manager.drop_queue(&addr);
+        if !dropped.is_empty() {
+            self.network_status_port
+                .trigger(NetworkStatus::QueueDropped(
+                    self.remote_system_path(addr),
+                    dropped.len(),
+                ));
+            self.report_delivery_failures(dropped, DeliveryFailureReason::ConnectionLost);
+        }
+    }
+
+    /// Forwards the request to join or leave a UDP multicast group to the network thread
+    fn change_multicast_membership(&mut self, membership: MulticastMembership) -> () {
+        match self.net_bridge {
+            Some(ref bridge) => {
+                if let Err(e) = bridge.multicast(membership) {
+                    error!(
+                        self.ctx.log(),
+                        "Could not forward {:?} to the network thread: {:?}", membership, e
+                    );
+                }
+            }
+            None => {
+                warn!(
+                    self.ctx.log(),
+                    "Ignoring {:?}, as bridge is not connected.", membership
+                );
+            }
+        }
+    }
+
+    /// Returns `true` if the fault injector partitioned this system from the one at `addr`.
+    fn is_partitioned(&mut self, addr: &SocketAddr) -> bool {
+        let own_addr = match self
+            .net_bridge
+            .as_ref()
+            .and_then(|bridge| *bridge.local_addr())
+        {
+            Some(own_addr) => own_addr,
+            None => return false,
+        };
+        match self.cfg.fault_injector {
+            Some(ref injector) => injector.is_partitioned_addr(&own_addr, addr),
+            None => false,
+        }
+    }
+
+    /// Treats the connection to the partitioned system at `addr` as lost,
+    /// so that messages for it are queued until the partition is healed.
+    fn lose_partitioned_connection(&mut self, addr: SocketAddr) {
+        if let Some(ConnectionState::Connected(_)) = self.connections.get(&addr) {
+            warn!(self.ctx().log(), "connection to {:?} partitioned", addr);
+            self.connections.insert(addr, ConnectionState::Closed);
+            self.network_status_port
+                .trigger(NetworkStatus::ConnectionLost(self.remote_system_path(addr)));
+            if !self.retry_map.contains_key(&addr) {
+                self.start_retrying(addr);
+            }
+        }
+    }
+
+    /// Hands `frame` to the network bridge, after injecting the faults the fault injector decides on.
+    fn send_frame(
+        &mut self,
+        addr: SocketAddr,
+        frame: SerialisedFrame,
+        protocol: net::Protocol,
+    ) -> Result<(), NetworkBridgeErr> {
+        let faults = match self.cfg.fault_injector {
+            Some(ref injector) => injector.frame_faults(),
+            None => return self.route_frame(addr, frame, protocol),
+        };
+        if faults.drop {
+            debug!(self.ctx.log(), "Injected fault: dropping frame to {}", addr);
+            return Ok(());
+        }
+        if faults.duplicate {
+            debug!(
+                self.ctx.log(),
+                "Injected fault: duplicating frame to {}", addr
+            );
+            let (frame, copy) = duplicate_frame(frame);
+            self.send_delayed_frame(addr, frame, protocol, faults.delay)?;
+            self.send_delayed_frame(addr, copy, protocol, faults.delay)
+        } else {
+            self.send_delayed_frame(addr, frame, protocol, faults.delay)
+        }
+    }
+
+    /// Hands `frame` to the network bridge right away, if it is still running
+    fn route_frame(
+        &self,
+        addr: SocketAddr,
+        frame: SerialisedFrame,
+        protocol: net::Protocol,
+    ) -> Result<(), NetworkBridgeErr> {
+        match &self.net_bridge {
+            Some(bridge) => bridge.route(addr, frame, protocol),
+            None => Ok(()),
+        }
+    }
+
+    /// Hands `frame` to the network bridge after `delay`
+    ///
+    /// TCP and custom transport frames are held back behind earlier delayed frames to the same address,
+    /// to keep FIFO order.
+    fn send_delayed_frame(
+        &mut self,
+        addr: SocketAddr,
+        frame: SerialisedFrame,
+        protocol: net::Protocol,
+        delay: Option<Duration>,
+    ) -> Result<(), NetworkBridgeErr> {
+        match protocol {
+            net::Protocol::TCP | net::Protocol::Custom(_) => {
+                let now = Instant::now();
+                let last_release = self
+                    .delayed_frames
+                    .get(&addr)
+                    .and_then(|delayed| delayed.back())
+                    .map(|(last_release, _)| *last_release);
+                let release = match (delay, last_release) {
+                    (None, None) => return self.route_frame(addr, frame, protocol),
+                    (delay, last_release) => {
+                        let release = now + delay.unwrap_or_default();
+                        match last_release {
+                            Some(last_release) if last_release > release => last_release,
+                            _ => release,
+                        }
+                    }
+                };
+                self.delayed_frames
+                    .entry(addr)
+                    .or_default()
+                    .push_back((release, frame));
+                self.schedule_once(release - now, move |target, _id| {
+                    target.release_delayed_frame(addr);
+                    Handled::Ok
+                });
+                Ok(())
+            }
+            net::Protocol::UDP | net::Protocol::ReliableUDP => match delay {
+                Some(delay) => {
+                    self.schedule_once(delay, move |target, _id| {
+                        if let Some(bridge) = &target.net_bridge {
+                            if let Err(e) = bridge.route(addr, frame, protocol) {
+                                error!(
+                                    target.ctx.log(),
+                                    "Failed to send delayed frame to {}: {:?}", addr, e
+                                );
+                            }
+                        }
+                        Handled::Ok
+                    });
+                    Ok(())
+                }
+                None => self.route_frame(addr, frame, protocol),
+            },
+        }
+    }
+
+    /// Sends the oldest delayed TCP frame to `addr`, or queues it if the connection was lost in the meantime
+    fn release_delayed_frame(&mut self, addr: SocketAddr) {
+        let frame = match self.delayed_frames.get_mut(&addr) {
+            Some(delayed) => match delayed.pop_front() {
+                Some((_, frame)) => {
+                    if delayed.is_empty() {
+                        self.delayed_frames.remove(&addr);
+                    }
+                    frame
+                }
+                None => return,
+            },
+            None => return,
+        };
+        match (self.connections.get(&addr), &self.net_bridge) {
+            (Some(ConnectionState::Connected(_)), Some(bridge)) => {
+                if let Err(e) = bridge.route(addr, frame, self.stream_protocol(addr)) {
+                    error!(
+                        self.ctx.log(),
+                        "Failed to send delayed frame to {}: {:?}", addr, e
+                    );
+                }
+            }
+            _ => self.queue_manager.enqueue_priority_frame(frame, addr),
+        }
+    }
+
+    /// Accounts for the frames which did not fit into the queue for `addr`
+    ///
+    /// Depending on the overflow policy, they are either dropped or forwarded to the deadletter box.
+    /// Either way, they are reported on the [NetworkStatusPort](NetworkStatusPort).
+    fn handle_queue_overflow(&mut self, addr: SocketAddr, overflow: Vec<SerialisedFrame>) {
+        let path = self.remote_system_path(addr);
+        self.handle_overflow(path, overflow);
+    }
+
+    /// Handles the `overflow` of the queue for the remote system at `path`,
+    /// as described for [handle_queue_overflow](NetworkDispatcher::handle_queue_overflow)
+    fn handle_overflow(&mut self, path: SystemPath, overflow: Vec<SerialisedFrame>) {
+        if overflow.is_empty() {
+            return;
+        }
+        let dropped = overflow.len();
+        let policy = self.queue_manager.overflow_policy();
+        warn!(
+            self.ctx.log(),
+            "Queue for {} is full, {} frame(s) not sent ({:?})", path, dropped, policy
+        );
+        self.network_status_port
+            .trigger(NetworkStatus::QueueDropped(path, dropped));
+        if let QueueOverflowPolicy::RejectToDeadletter = policy {
+            for frame in overflow {
+                match crate::ser_helpers::deserialise_frame(frame) {
+                    Ok(msg) => {
+                        self.report_delivery_failure(&msg, DeliveryFailureReason::QueueOverflow);
+                        self.ctx.deadletter_ref().enqueue(MsgEnvelope::Net(msg));
+                    }
+                    Err(e) => error!(
+                        self.ctx.log(),
+                        "Could not forward frame to DeadletterBox: {:?}. Dropping...", e
+                    ),
+                }
+            }
+        } else {
+            self.report_delivery_failures(overflow, DeliveryFailureReason::QueueOverflow);
+        }
+    }
+
+    /// Reports the messages in `frames` as undeliverable, if enabled in the config
+    fn report_delivery_failures(
+        &mut self,
+        frames: Vec<SerialisedFrame>,
+        reason: DeliveryFailureReason,
+    ) {
+        if let DeliveryFailureReporting::Disabled = self.cfg.delivery_failure_reporting {
+            return;
+        }
+        for frame in frames {
+            match crate::ser_helpers::deserialise_frame(frame) {
+                Ok(msg) => self.report_delivery_failure(&msg, reason),
+                Err(e) => error!(
+                    self.ctx.log(),
+                    "Could not report undeliverable frame: {:?}. Dropping...", e
+                ),
+            }
+        }
+    }
+
+    /// Reports `msg` as undeliverable, if enabled in the config
+    ///
+    /// The [DeliveryFailure](DeliveryFailure) is sent from the original receiver of `msg`
+    /// to its sender, or the deadletter box if the sender is not local.
+    fn report_delivery_failure(&mut self, msg: &NetMessage, reason: DeliveryFailureReason) {
+        let reporting = self.cfg.delivery_failure_reporting;
+        if let DeliveryFailureReporting::Disabled = reporting {
+            return;
+        }
+        debug!(
+            self.ctx.log(),
+            "Reporting undeliverable message from {} to {} ({:?})",
+            msg.sender,
+            msg.receiver,
+            reason
+        );
+        let failure = DeliveryFailure {
+            sender: msg.sender.clone(),
+            receiver: msg.receiver.clone(),
+            ser_id: msg.data.ser_id,
+            reason,
+        };
+        let notification = NetMessage::with_box(
+            serialisation_ids::DELIVERY_FAILURE,
+            msg.receiver.clone(),
+            msg.sender.clone(),
+            Box::new(failure),
+        );
+        match reporting {
+            DeliveryFailureReporting::ToSender
+                if notification.receiver.system() == self.system_path_ref() =>
+            {
+                if let Err(e) = self.route_local(notification) {
+                    error!(self.ctx.log(), "Could not report delivery failure: {:?}", e);
+                }
+            }
+            _ => self
+                .ctx
+                .deadletter_ref()
+                .enqueue(MsgEnvelope::Net(notification)),
+        }
+    }
+
     fn resolve_path(&mut self, resolvable: &PathResolvable) -> Result<ActorPath, PathParseError> {
         match resolvable {
             PathResolvable::Path(actor_path) => Ok(actor_path.clone()),
@@ -683,6 +2582,9 @@ impl NetworkDispatcher {
                 Transport::LOCAL => self.route_local(msg),
                 Transport::TCP => self.route_remote(msg),
                 Transport::UDP => self.route_remote(msg),
+                Transport::RUDP => self.route_remote(msg),
+                Transport::UDS => self.route_remote(msg),
+                Transport::Custom(_) => self.route_remote(msg),
             }
         }
     }
@@ -793,6 +2695,85 @@ impl NetworkDispatcher {
     }
 }
 
+/// How a remote system, which is not reached over TCP, is connected to
+enum RemoteTransport {
+    /// Over the Unix domain socket at the path
+    Unix(PathBuf),
+    /// Over the custom transport with the identifier
+    Custom(u8),
+}
+
+/// A request to the dispatcher waiting for the domain name of a system path to be resolved
+enum AwaitingResolution {
+    /// A message to route to the system
+    Msg(Box<NetMessage>),
+    /// A request to connect to the system
+    Connect,
+    /// A request to close the connection to the system
+    Close,
+    /// A request to leave the system
+    Leave,
+}
+
+/// Resolves the domain name of `system`, preferring IPv4 addresses if `prefer_ipv4`,
+/// and IPv6 addresses otherwise.
+///
+/// This may block until the lookup is finished.
+fn resolve_domain(system: &SystemPath, prefer_ipv4: bool) -> io::Result<SocketAddr> {
+    let domain = system.host().domain().unwrap_or_default();
+    let addrs: Vec<SocketAddr> = (domain, system.port()).to_socket_addrs()?.collect();
+    addrs
+        .iter()
+        .find(|addr| addr.is_ipv4() == prefer_ipv4)
+        .or_else(|| addrs.first())
+        .copied()
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("No address found for {}", domain),
+            )
+        })
+}
+
+/// Fails the resolution of all `systems` with an error of `kind` and `msg`
+fn failed_resolution(
+    systems: Vec<SystemPath>,
+    kind: ErrorKind,
+    msg: &str,
+) -> Vec<(SystemPath, io::Result<SocketAddr>)> {
+    systems
+        .into_iter()
+        .map(|system| (system, Err(io::Error::new(kind, msg.to_string()))))
+        .collect()
+}
+
+/// Copies `state`, whose error can only be copied by its kind and message
+fn copy_connection_state(state: &ConnectionState) -> ConnectionState {
+    match state {
+        ConnectionState::New => ConnectionState::New,
+        ConnectionState::Initializing => ConnectionState::Initializing,
+        ConnectionState::Connected(addr) => ConnectionState::Connected(*addr),
+        ConnectionState::Closed => ConnectionState::Closed,
+        ConnectionState::Error(e) => {
+            ConnectionState::Error(io::Error::new(e.kind(), e.to_string()))
+        }
+    }
+}
+
+/// Asks `bridge` to connect to the remote host `addr`,
+/// over the transport recorded for it in `remote_transports`, or TCP otherwise
+fn connect(
+    bridge: &net::Bridge,
+    remote_transports: &FxHashMap<SocketAddr, RemoteTransport>,
+    addr: SocketAddr,
+) -> Result<(), NetworkBridgeErr> {
+    match remote_transports.get(&addr) {
+        Some(RemoteTransport::Unix(path)) => bridge.connect_unix(addr, path.clone()),
+        Some(RemoteTransport::Custom(id)) => bridge.connect(Transport::Custom(*id), addr),
+        None => bridge.connect(Transport::TCP, addr),
+    }
+}
+
 impl Actor for NetworkDispatcher {
     type Message = DispatchEnvelope;
 
@@ -823,6 +2804,9 @@ impl Actor for NetworkDispatcher {
             }
             DispatchEnvelope::Event(ev) => self.on_event(ev),
             DispatchEnvelope::LockedChunk(trash) => self.garbage_buffers.push_back(trash),
+            DispatchEnvelope::Multicast(membership) => self.change_multicast_membership(membership),
+            DispatchEnvelope::Leave(departure) => self.leave(departure),
+            DispatchEnvelope::Connection(request) => self.manage_connections(request),
         }
         Handled::Ok
     }
@@ -851,8 +2835,14 @@ impl Dispatcher for NetworkDispatcher {
             }
         }
     }
+
+    fn network_status_port(&mut self) -> Option<&mut ProvidedPort<NetworkStatusPort>> {
+        Some(&mut self.network_status_port)
+    }
 }
 
+ignore_requests!(NetworkStatusPort, NetworkDispatcher);
+
 impl ComponentLifecycle for NetworkDispatcher {
     fn on_start(&mut self) -> Handled {
         info!(self.ctx.log(), "Starting network...");
@@ -978,8 +2968,14 @@ impl Routable for (ActorPath, ActorPath, DispatchData) {
 #[cfg(test)]
 mod tests {
     use super::{super::*, *};
-    use crate::prelude_test::net_test_helpers::{PingerAct, PongerAct};
-    use std::{thread, time::Duration};
+    use crate::{
+        net::frames::{Ack, Frame, FrameType, Hello, FRAME_HEAD_LEN},
+        prelude_test::net_test_helpers::{PingerAct, PongerAct, PING_COUNT},
+    };
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
 
     // replace ignore with panic cfg gate when https://github.com/rust-lang/rust/pull/74754 is merged
     #[test]
@@ -1218,4 +3214,844 @@ mod tests {
             .shutdown()
             .expect("Kompact didn't shut down properly");
     }
+
+    #[derive(ComponentDefinition)]
+    struct NetworkStatusListener {
+        ctx: ComponentContext<Self>,
+        network_status_port: RequiredPort<NetworkStatusPort>,
+        statuses: Vec<NetworkStatus>,
+    }
+
+    impl NetworkStatusListener {
+        fn new() -> Self {
+            NetworkStatusListener {
+                ctx: ComponentContext::uninitialised(),
+                network_status_port: RequiredPort::uninitialised(),
+                statuses: Vec::new(),
+            }
+        }
+    }
+
+    ignore_lifecycle!(NetworkStatusListener);
+
+    impl Require<NetworkStatusPort> for NetworkStatusListener {
+        fn handle(&mut self, status: NetworkStatus) -> Handled {
+            self.statuses.push(status);
+            Handled::Ok
+        }
+    }
+
+    impl Actor for NetworkStatusListener {
+        type Message = Never;
+
+        fn receive_local(&mut self, _msg: Self::Message) -> Handled {
+            unimplemented!();
+        }
+
+        fn receive_network(&mut self, _msg: NetMessage) -> Handled {
+            unimplemented!();
+        }
+    }
+
+    /// How long to wait for the network before failing a test
+    const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Waits until `condition` holds, panicking if it does not within the [WAIT_TIMEOUT].
+    fn wait_until(description: &str, mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        while !condition() {
+            assert!(
+                Instant::now() < deadline,
+                "Timed out waiting until {}",
+                description
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Builds a system, whose network is set up by `configure`
+    fn system_with_network(configure: impl FnOnce(&mut NetworkConfig)) -> KompactSystem {
+        let mut net_config = NetworkConfig::default();
+        configure(&mut net_config);
+        let mut cfg = KompactConfig::new();
+        cfg.system_components(DeadletterBox::new, net_config.build());
+        cfg.build().expect("KompactSystem")
+    }
+
+    /// Starts recording the network status indications of `system`
+    fn listen_to_network_status(system: &KompactSystem) -> Arc<Component<NetworkStatusListener>> {
+        let listener = system.create(NetworkStatusListener::new);
+        listener.on_definition(|c| system.connect_network_status_port(&mut c.network_status_port));
+        system.start(&listener);
+        listener
+    }
+
+    /// Waits until the statuses recorded by `listener` satisfy `condition`
+    fn wait_for_statuses(
+        listener: &Arc<Component<NetworkStatusListener>>,
+        description: &str,
+        condition: impl Fn(&[NetworkStatus]) -> bool,
+    ) {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        loop {
+            let statuses = listener.on_definition(|c| c.statuses.clone());
+            if condition(&statuses) {
+                return;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "Timed out waiting for {}, got {:?}",
+                description,
+                statuses
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Starts a ponger on `system` registered as "ponger", and returns its path on the system at `remote`
+    fn start_ponger(system: &KompactSystem, remote: &SystemPath) -> ActorPath {
+        let (ponger, ponf) = system.create_and_register(PongerAct::new_lazy);
+        let poaf = system.register_by_alias(&ponger, "ponger");
+        ponf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+        poaf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+        system.start(&ponger);
+        ActorPath::Named(NamedPath::with_system(
+            remote.clone(),
+            vec!["ponger".into()],
+        ))
+    }
+
+    /// Starts a pinger on `system`, which pings the ponger at `ponger_path`
+    fn start_pinger(system: &KompactSystem, ponger_path: ActorPath) -> Arc<Component<PingerAct>> {
+        let (pinger, pinf) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+        pinf.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+        system.start(&pinger);
+        pinger
+    }
+
+    /// Waits until `pinger` has received a pong for each of its pings
+    fn expect_pongs(pinger: &Arc<Component<PingerAct>>) {
+        wait_until("all pongs are received", || {
+            pinger.on_definition(|c| c.count) == PING_COUNT
+        });
+    }
+
+    /// Returns the number of frames indicated as dropped for `remote` in `statuses`
+    fn dropped_frames(statuses: &[NetworkStatus], remote: &SystemPath) -> usize {
+        statuses
+            .iter()
+            .map(|status| match status {
+                NetworkStatus::QueueDropped(path, dropped) if path == remote => *dropped,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn network_status_port_indications() {
+        let system1 = system_with_network(|net_config| {
+            net_config.set_retry_strategy(RetryStrategy::FixedInterval {
+                interval_ms: 100,
+                num_tries: 2,
+            })
+        });
+        let system2 = system_with_network(|_| ());
+        let remote = system2.system_path();
+        let listener = listen_to_network_status(&system1);
+
+        let ponger_path = start_ponger(&system2, &remote);
+        let pinger = start_pinger(&system1, ponger_path.clone());
+        expect_pongs(&pinger);
+        wait_for_statuses(&listener, "the connection", |statuses| {
+            statuses == [NetworkStatus::ConnectionEstablished(remote.clone())]
+        });
+
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+        // The remote said Bye while shutting down, so it is not reconnected to before sending again
+        wait_for_statuses(&listener, "the remote to leave", |statuses| {
+            statuses.contains(&NetworkStatus::PeerLeft(remote.clone()))
+        });
+        // Queue up some messages for the now unreachable remote
+        start_pinger(&system1, ponger_path);
+        wait_for_statuses(&listener, "the queued messages to be dropped", |statuses| {
+            statuses.contains(&NetworkStatus::RetriesExhausted(remote.clone()))
+                && dropped_frames(statuses, &remote) > 0
+        });
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn reliable_udp_delivery_and_indications() {
+        let system1 = system_with_network(|net_config| {
+            net_config.set_reliable_udp_retransmit_timeout(Duration::from_millis(50));
+            net_config.set_reliable_udp_max_retransmissions(2);
+        });
+        let system2 = system_with_network(|_| ());
+        let remote = SystemPath::with_socket(
+            Transport::RUDP,
+            system2.system_path().socket_addr().expect("socket address"),
+        );
+        let listener = listen_to_network_status(&system1);
+
+        let ponger_path = start_ponger(&system2, &remote);
+        let pinger = start_pinger(&system1, ponger_path.clone());
+        expect_pongs(&pinger);
+        wait_for_statuses(&listener, "the connection", |statuses| {
+            statuses.contains(&NetworkStatus::ConnectionEstablished(remote.clone()))
+        });
+
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+        // Messages to the now unreachable remote are never acknowledged
+        ponger_path.tell_with_sender(0u64, &system1, system1.actor_path_for(&listener));
+        wait_for_statuses(&listener, "the connection to be lost", |statuses| {
+            statuses.contains(&NetworkStatus::ConnectionLost(remote.clone()))
+                && dropped_frames(statuses, &remote) == 1
+        });
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn leave_remote_indications() {
+        let system1 = system_with_network(|_| ());
+        let system2 = system_with_network(|_| ());
+        let remote1 = system1.system_path();
+        let remote2 = system2.system_path();
+        let listener1 = listen_to_network_status(&system1);
+        let listener2 = listen_to_network_status(&system2);
+
+        let ponger_path = start_ponger(&system2, &remote2);
+        let pinger = start_pinger(&system1, ponger_path);
+        expect_pongs(&pinger);
+
+        system1.leave_remote(remote2.clone());
+        wait_for_statuses(&listener1, "leaving the remote", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote2.clone()),
+                    NetworkStatus::Left(remote2.clone()),
+                ]
+        });
+        wait_for_statuses(&listener2, "the remote to leave", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote1.clone()),
+                    NetworkStatus::PeerLeft(remote1.clone()),
+                ]
+        });
+
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn connection_management() {
+        let system1 = system_with_network(|_| ());
+        let system2 = system_with_network(|_| ());
+        let remote = system2.system_path();
+        // Nobody listens on a port that was just released
+        let unreachable = SystemPath::with_socket(
+            Transport::TCP,
+            std::net::TcpListener::bind("127.0.0.1:0")
+                .and_then(|listener| listener.local_addr())
+                .expect("free port"),
+        );
+        let listener = listen_to_network_status(&system1);
+        let connections = || {
+            system1
+                .connections()
+                .wait_timeout(Duration::from_millis(1000))
+                .expect("connections")
+        };
+        assert!(connections().is_empty());
+
+        // Connect eagerly, without sending a message
+        system1.connect_remote(remote.clone());
+        wait_for_statuses(&listener, "the connection", |statuses| {
+            statuses == [NetworkStatus::ConnectionEstablished(remote.clone())]
+        });
+        let established = connections();
+        assert_eq!(established.len(), 1);
+        assert_eq!(established[0].system, remote);
+        assert!(matches!(
+            established[0].state,
+            ConnectionState::Connected(_)
+        ));
+        assert_eq!(established[0].queued_frames, 0);
+
+        // Messages for an unreachable system are queued
+        let unreachable_path = ActorPath::Named(NamedPath::with_system(
+            unreachable.clone(),
+            vec!["ponger".into()],
+        ));
+        start_pinger(&system1, unreachable_path);
+        wait_until("the messages are queued", || {
+            connections()
+                .iter()
+                .any(|connection| connection.system == unreachable && connection.queued_frames > 0)
+        });
+        let queued = connections()
+            .into_iter()
+            .find(|connection| connection.system == unreachable)
+            .expect("connection to unreachable system");
+        assert!(!matches!(queued.state, ConnectionState::Connected(_)));
+        assert!(queued.queued_bytes > 0);
+
+        system1.close_connection(remote.clone());
+        system1.close_connection(unreachable.clone());
+        wait_for_statuses(&listener, "the connections to be closed", |statuses| {
+            statuses.contains(&NetworkStatus::ConnectionClosed(remote.clone()))
+                && statuses.contains(&NetworkStatus::ConnectionClosed(unreachable.clone()))
+                && dropped_frames(statuses, &unreachable) > 0
+        });
+        wait_until("no connections are left", || connections().is_empty());
+        listener.on_definition(|c| {
+            assert!(!c
+                .statuses
+                .contains(&NetworkStatus::ConnectionLost(remote.clone())));
+        });
+
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn idle_connections_closed_and_reestablished() {
+        let system1 = system_with_network(|net_config| {
+            net_config.set_idle_timeout(Some(Duration::from_millis(200)));
+        });
+        let system2 = system_with_network(|_| ());
+        let remote1 = system1.system_path();
+        let remote2 = system2.system_path();
+        let listener1 = listen_to_network_status(&system1);
+        let listener2 = listen_to_network_status(&system2);
+
+        let ponger_path = start_ponger(&system2, &remote2);
+        let pinger = start_pinger(&system1, ponger_path.clone());
+        expect_pongs(&pinger);
+        wait_for_statuses(&listener1, "the connection to become idle", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote2.clone()),
+                    NetworkStatus::ConnectionIdle(remote2.clone()),
+                ]
+        });
+        wait_for_statuses(&listener2, "the connection to become idle", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote1.clone()),
+                    NetworkStatus::ConnectionIdle(remote1.clone()),
+                ]
+        });
+        for system in [&system1, &system2].iter() {
+            wait_until("no connections are left", || {
+                system
+                    .connections()
+                    .wait_timeout(Duration::from_millis(1000))
+                    .expect("connections")
+                    .is_empty()
+            });
+        }
+
+        // The next message re-establishes the connection
+        let pinger2 = start_pinger(&system1, ponger_path);
+        expect_pongs(&pinger2);
+        wait_for_statuses(
+            &listener1,
+            "the connection to be re-established",
+            |statuses| {
+                statuses.get(2) == Some(&NetworkStatus::ConnectionEstablished(remote2.clone()))
+            },
+        );
+        listener1.on_definition(|c| {
+            assert!(!c
+                .statuses
+                .contains(&NetworkStatus::ConnectionLost(remote2.clone())));
+        });
+
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn messages_sent_as_the_peer_closes_an_idle_connection_are_resent() {
+        let system1 = system_with_network(|net_config| {
+            net_config.set_idle_timeout(Some(Duration::from_millis(500)));
+        });
+        let system2 = system_with_network(|_| ());
+        let remote1 = system1.system_path();
+        let listener2 = listen_to_network_status(&system2);
+        let sender = system2.actor_path_for(&listener2);
+        let nobody = ActorPath::Named(NamedPath::with_system(
+            remote1.clone(),
+            vec!["nobody".into()],
+        ));
+
+        let ponger_path = start_ponger(&system1, &remote1);
+        let pinger = start_pinger(&system2, ponger_path);
+        expect_pongs(&pinger);
+
+        // Hold the dispatcher of system2 until system1 closed the idle connection,
+        // such that it only learns about the idle Bye after routing the next message
+        let sc: &dyn SystemComponents = system2.get_system_components();
+        let dispatcher = &sc
+            .downcast::<CustomComponents<DeadletterBox, NetworkDispatcher>>()
+            .expect("NetworkDispatcher")
+            .dispatcher;
+        dispatcher.on_definition(|_| {
+            nobody.tell_with_sender(0u64, &system2, sender);
+            thread::sleep(Duration::from_millis(1500));
+        });
+        wait_for_statuses(
+            &listener2,
+            "the connection to be re-established",
+            |statuses| {
+                statuses
+                    .iter()
+                    .filter(|status| {
+                        **status == NetworkStatus::ConnectionEstablished(remote1.clone())
+                    })
+                    .count()
+                    == 2
+            },
+        );
+        listener2.on_definition(|c| {
+            assert!(c
+                .statuses
+                .contains(&NetworkStatus::ConnectionIdle(remote1.clone())));
+            assert_eq!(dropped_frames(&c.statuses, &remote1), 0);
+        });
+
+        system1
+            .shutdown()
+            .expect("1st KompactSystem failed to shut down!");
+        system2
+            .shutdown()
+            .expect("2nd KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn bounded_queue_overflow_indications() {
+        let system = system_with_network(|net_config| {
+            net_config.set_retry_strategy(RetryStrategy::FixedInterval {
+                interval_ms: 1000,
+                num_tries: 10,
+            });
+            net_config.set_max_queued_frames(Some(2));
+            net_config.set_queue_overflow_policy(QueueOverflowPolicy::DropNewest);
+        });
+        let listener = listen_to_network_status(&system);
+
+        // Reserve a port which nobody listens on
+        let unreachable = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Could not reserve port");
+        let remote = SystemPath::with_socket(Transport::TCP, unreachable);
+        let path = ActorPath::Named(NamedPath::with_system(
+            remote.clone(),
+            vec!["nobody".into()],
+        ));
+        for i in 0..5u64 {
+            path.tell(i, &system);
+        }
+        wait_for_statuses(
+            &listener,
+            "the overflowing frames to be dropped",
+            |statuses| dropped_frames(statuses, &remote) >= 3,
+        );
+        listener.on_definition(|c| assert_eq!(dropped_frames(&c.statuses, &remote), 3));
+        system
+            .shutdown()
+            .expect("KompactSystem failed to shut down!");
+    }
+
+    #[derive(ComponentDefinition)]
+    struct DeliveryFailureCollector {
+        ctx: ComponentContext<Self>,
+        target: ActorPath,
+        failures: Vec<DeliveryFailure>,
+    }
+
+    impl DeliveryFailureCollector {
+        fn new(target: ActorPath) -> Self {
+            DeliveryFailureCollector {
+                ctx: ComponentContext::uninitialised(),
+                target,
+                failures: Vec::new(),
+            }
+        }
+    }
+
+    impl ComponentLifecycle for DeliveryFailureCollector {
+        fn on_start(&mut self) -> Handled {
+            for i in 0..3u64 {
+                self.target.tell(i, self);
+            }
+            Handled::Ok
+        }
+    }
+
+    impl Actor for DeliveryFailureCollector {
+        type Message = Never;
+
+        fn receive_local(&mut self, _msg: Self::Message) -> Handled {
+            unimplemented!();
+        }
+
+        fn receive_network(&mut self, msg: NetMessage) -> Handled {
+            let failure = msg
+                .try_deserialise::<DeliveryFailure, DeliveryFailure>()
+                .expect("DeliveryFailure");
+            self.failures.push(failure);
+            Handled::Ok
+        }
+    }
+
+    #[test]
+    fn delivery_failures_reported_to_sender() {
+        let system = system_with_network(|net_config| {
+            net_config.set_retry_strategy(RetryStrategy::FixedInterval {
+                interval_ms: 100,
+                num_tries: 2,
+            });
+            net_config.set_max_queued_frames(Some(2));
+            net_config.set_delivery_failure_reporting(DeliveryFailureReporting::ToSender);
+        });
+
+        // Reserve a port which nobody listens on
+        let unreachable = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Could not reserve port");
+        let target = ActorPath::Named(NamedPath::with_system(
+            SystemPath::with_socket(Transport::TCP, unreachable),
+            vec!["nobody".into()],
+        ));
+        let target2 = target.clone();
+        let (collector, registration) =
+            system.create_and_register(move || DeliveryFailureCollector::new(target2));
+        let sender =
+            registration.wait_expect(Duration::from_millis(1000), "Collector failed to register!");
+        system.start(&collector);
+        wait_until("all failures are reported", || {
+            collector.on_definition(|c| c.failures.len()) >= 3
+        });
+        collector.on_definition(|c| {
+            let reasons: Vec<DeliveryFailureReason> = c
+                .failures
+                .iter()
+                .map(|failure| {
+                    assert_eq!(failure.sender, sender);
+                    assert_eq!(failure.receiver, target);
+                    assert_eq!(failure.ser_id, serialisation_ids::U64);
+                    failure.reason
+                })
+                .collect();
+            assert_eq!(
+                reasons,
+                vec![
+                    DeliveryFailureReason::QueueOverflow,
+                    DeliveryFailureReason::RetriesExhausted,
+                    DeliveryFailureReason::RetriesExhausted
+                ]
+            );
+        });
+        system
+            .shutdown()
+            .expect("KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    // A remote system is restarted on the same port while messages are queued for it.
+    // The restart must be indicated, and the queued messages rejected as addressed to the stale incarnation.
+    fn restarted_remote_rejects_stale_messages() {
+        let system = system_with_network(|net_config| {
+            net_config.set_retry_strategy(RetryStrategy::FixedInterval {
+                interval_ms: 100,
+                num_tries: 50,
+            });
+            net_config.set_reject_stale_messages(true);
+            net_config.set_delivery_failure_reporting(DeliveryFailureReporting::ToSender);
+        });
+        let remote_system = |port| {
+            system_with_network(|net_config| {
+                *net_config =
+                    NetworkConfig::new(SocketAddr::new("127.0.0.1".parse().unwrap(), port));
+            })
+        };
+        let remote_a = remote_system(0);
+        let remote = remote_a.system_path();
+        let listener = listen_to_network_status(&system);
+
+        let target = ActorPath::Named(NamedPath::with_system(
+            remote.clone(),
+            vec!["nobody".into()],
+        ));
+        target.tell_with_sender(0u64, &system, system.actor_path_for(&listener));
+        wait_for_statuses(&listener, "the connection to be established", |statuses| {
+            statuses.contains(&NetworkStatus::ConnectionEstablished(remote.clone()))
+        });
+
+        let incarnation_a = remote_a.incarnation();
+        remote_a
+            .shutdown()
+            .expect("Remote KompactSystem failed to shut down!");
+        wait_for_statuses(&listener, "the remote to leave", |statuses| {
+            statuses.contains(&NetworkStatus::PeerLeft(remote.clone()))
+        });
+        // Queue up messages for the restarting remote
+        let target2 = target.clone();
+        let (collector, registration) =
+            system.create_and_register(move || DeliveryFailureCollector::new(target2));
+        registration.wait_expect(Duration::from_millis(1000), "Collector failed to register!");
+        system.start(&collector);
+        wait_until("the messages are queued", || {
+            system
+                .connections()
+                .wait_timeout(Duration::from_millis(1000))
+                .expect("connections")
+                .iter()
+                .any(|connection| connection.system == remote && connection.queued_frames == 3)
+        });
+
+        let remote_b = remote_system(remote.port());
+        assert_ne!(incarnation_a, remote_b.incarnation());
+        wait_for_statuses(
+            &listener,
+            "the restart and the stale messages",
+            |statuses| {
+                statuses.contains(&NetworkStatus::Restarted(remote.clone()))
+                    && dropped_frames(statuses, &remote) == 3
+            },
+        );
+        wait_until("the stale messages are reported", || {
+            collector.on_definition(|c| c.failures.len()) == 3
+        });
+        collector.on_definition(|c| {
+            assert!(c
+                .failures
+                .iter()
+                .all(|failure| failure.reason == DeliveryFailureReason::StaleIncarnation));
+        });
+        system
+            .shutdown()
+            .expect("KompactSystem failed to shut down!");
+        remote_b
+            .shutdown()
+            .expect("Remote KompactSystem failed to shut down!");
+    }
+
+    /// Encodes a control frame as a raw remote host would send it
+    fn encode_raw_frame(mut frame: Frame) -> Vec<u8> {
+        let mut buf = bytes::BytesMut::with_capacity(FRAME_HEAD_LEN as usize + frame.encoded_len());
+        frame.encode_into(&mut buf).expect("encode frame");
+        buf.to_vec()
+    }
+
+    /// Accepts a single connection and completes the handshake on it, like a system bound to `listener`.
+    ///
+    /// Afterwards, heartbeats are only answered while `answer_heartbeats` is set.
+    fn spawn_raw_remote(
+        listener: std::net::TcpListener,
+        answer_heartbeats: Arc<std::sync::atomic::AtomicBool>,
+    ) {
+        use std::{
+            io::{Read, Write},
+            sync::atomic::Ordering,
+        };
+        let own_addr = listener.local_addr().expect("local addr");
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            stream
+                .write_all(&encode_raw_frame(Frame::Hello(Hello::new(own_addr))))
+                .expect("write Hello");
+            let mut head = [0u8; FRAME_HEAD_LEN as usize];
+            while stream.read_exact(&mut head).is_ok() {
+                let content_length = u32::from_be_bytes([head[4], head[5], head[6], head[7]]);
+                let mut content = vec![0u8; content_length as usize];
+                if stream.read_exact(&mut content).is_err() {
+                    return;
+                }
+                let reply = match FrameType::from(head[8]) {
+                    FrameType::Start => Frame::Ack(Ack {
+                        offset: 0,
+                        response: None,
+                    }),
+                    FrameType::Heartbeat if answer_heartbeats.load(Ordering::SeqCst) => {
+                        Frame::HeartbeatAck()
+                    }
+                    _ => continue,
+                };
+                if stream.write_all(&encode_raw_frame(reply)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn failure_detector_indications() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let system = system_with_network(|net_config| {
+            net_config.set_failure_detector(FailureDetector::Timeout {
+                heartbeat_interval_ms: 50,
+                timeout_ms: 300,
+            });
+        });
+        let listener = listen_to_network_status(&system);
+
+        let remote_listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let remote = SystemPath::with_socket(
+            Transport::TCP,
+            remote_listener.local_addr().expect("local addr"),
+        );
+        let answer_heartbeats = Arc::new(AtomicBool::new(true));
+        spawn_raw_remote(remote_listener, answer_heartbeats.clone());
+        let path = ActorPath::Named(NamedPath::with_system(
+            remote.clone(),
+            vec!["nobody".into()],
+        ));
+        path.tell(0u64, &system);
+        wait_for_statuses(&listener, "the connection", |statuses| {
+            statuses == [NetworkStatus::ConnectionEstablished(remote.clone())]
+        });
+
+        answer_heartbeats.store(false, Ordering::SeqCst);
+        wait_for_statuses(&listener, "the remote to be suspected", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote.clone()),
+                    NetworkStatus::Suspected(remote.clone()),
+                ]
+        });
+        answer_heartbeats.store(true, Ordering::SeqCst);
+        wait_for_statuses(&listener, "the remote to be alive again", |statuses| {
+            statuses
+                == [
+                    NetworkStatus::ConnectionEstablished(remote.clone()),
+                    NetworkStatus::Suspected(remote.clone()),
+                    NetworkStatus::Alive(remote.clone()),
+                ]
+        });
+        system
+            .shutdown()
+            .expect("KompactSystem failed to shut down!");
+    }
+
+    #[test]
+    fn exponential_backoff_retry_delays() {
+        let strategy = RetryStrategy::ExponentialBackoff {
+            base_ms: 100,
+            max_ms: 1000,
+            num_tries: 10,
+        };
+        for attempt in 0..10u8 {
+            let expected = (100u64 << attempt).min(1000);
+            let delay = strategy.delay(attempt).as_millis() as u64;
+            assert!(
+                delay >= expected / 2 && delay <= expected,
+                "delay {}ms for attempt {} not in [{}, {}]",
+                delay,
+                attempt,
+                expected / 2,
+                expected
+            );
+        }
+        // Must not overflow for very large attempt counts
+        assert!(strategy.delay(u8::MAX) <= Duration::from_millis(1000));
+
+        let fixed = RetryStrategy::FixedInterval {
+            interval_ms: 500,
+            num_tries: 2,
+        };
+        assert_eq!(fixed.delay(0), Duration::from_millis(500));
+        assert_eq!(fixed.delay(7), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn retry_strategy_from_config() {
+        let config = hocon::HoconLoader::new()
+            .load_str(
+                r#"{
+                    network.retry_strategy {
+                        type = "fixed_interval",
+                        interval_ms = 250,
+                    }
+                }"#,
+            )
+            .expect("config")
+            .hocon()
+            .expect("hocon");
+        let strategy = RetryStrategy::from_config(&config)
+            .expect("valid config")
+            .expect("strategy");
+        assert_eq!(
+            strategy,
+            RetryStrategy::FixedInterval {
+                interval_ms: 250,
+                num_tries: 10
+            }
+        );
+        let empty = hocon::HoconLoader::new().hocon().expect("hocon");
+        assert_eq!(RetryStrategy::from_config(&empty), Ok(None));
+        let invalid = hocon::HoconLoader::new()
+            .load_str(
+                r#"{
+                    network.retry_strategy {
+                        type = "fixed_interval",
+                        num_tries = 256,
+                    }
+                }"#,
+            )
+            .expect("config")
+            .hocon()
+            .expect("hocon");
+        assert!(RetryStrategy::from_config(&invalid).is_err());
+        for invalid in [
+            r#"{ network.retry_strategy { type = "exponential_backof" } }"#,
+            r#"{ network.retry_strategy { type = "fixed_interval", interval_ms = -1 } }"#,
+            r#"{ network.retry_strategy { type = "exponential_backoff", max_ms = -1 } }"#,
+        ]
+        .iter()
+        {
+            let invalid = hocon::HoconLoader::new()
+                .load_str(invalid)
+                .expect("config")
+                .hocon()
+                .expect("hocon");
+            assert!(RetryStrategy::from_config(&invalid).is_err());
+        }
+
+        let mut net_cfg = NetworkConfig::default();
+        assert_eq!(net_cfg.get_retry_strategy(), RetryStrategy::default());
+        net_cfg.set_max_connection_retry_attempts(3);
+        net_cfg.set_connection_retry_interval(50);
+        assert_eq!(
+            net_cfg.get_retry_strategy(),
+            RetryStrategy::ExponentialBackoff {
+                base_ms: 50,
+                max_ms: 5000,
+                num_tries: 3
+            }
+        );
+    }
 }
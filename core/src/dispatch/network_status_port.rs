@@ -0,0 +1,121 @@
+use crate::{actors::SystemPath, prelude::Port, Never};
+
+/// A port providing information about the connections of the [NetworkDispatcher](super::NetworkDispatcher)
+///
+/// Connect a component's [RequiredPort](crate::prelude::RequiredPort) to the dispatcher via
+/// [connect_network_status_port](crate::prelude::KompactSystem::connect_network_status_port)
+/// to receive [NetworkStatus](NetworkStatus) indications.
+///
+/// # Example
+///
+/// ```
+/// use kompact::prelude::*;
+///
+/// #[derive(ComponentDefinition)]
+/// struct StatusListener {
+///     ctx: ComponentContext<Self>,
+///     network_status_port: RequiredPort<NetworkStatusPort>,
+/// }
+/// impl StatusListener {
+///     fn new() -> Self {
+///         StatusListener {
+///             ctx: ComponentContext::uninitialised(),
+///             network_status_port: RequiredPort::uninitialised(),
+///         }
+///     }
+/// }
+/// ignore_lifecycle!(StatusListener);
+/// impl Require<NetworkStatusPort> for StatusListener {
+///     fn handle(&mut self, status: NetworkStatus) -> Handled {
+///         info!(self.log(), "Network status changed: {:?}", status);
+///         Handled::Ok
+///     }
+/// }
+/// impl Actor for StatusListener {
+///     type Message = ();
+///     fn receive_local(&mut self, _msg: Self::Message) -> Handled {
+///         Handled::Ok
+///     }
+///     fn receive_network(&mut self, _msg: NetMessage) -> Handled {
+///         Handled::Ok
+///     }
+/// }
+///
+/// let mut conf = KompactConfig::default();
+/// conf.system_components(DeadletterBox::new, NetworkConfig::default().build());
+/// let system = conf.build().expect("system");
+/// let listener = system.create(StatusListener::new);
+/// listener.on_definition(|c| system.connect_network_status_port(&mut c.network_status_port));
+/// system.start(&listener);
+/// # system.shutdown().expect("shutdown");
+/// ```
+pub struct NetworkStatusPort;
+
+impl Port for NetworkStatusPort {
+    type Indication = NetworkStatus;
+    type Request = Never;
+}
+
+/// Indications about the connections of the [NetworkDispatcher](super::NetworkDispatcher)
+///
+/// Each indication identifies the remote system by its [SystemPath](SystemPath).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetworkStatus {
+    /// A connection to the remote system has been established.
+    ///
+    /// Messages queued for the remote system are sent after this is indicated.
+    ConnectionEstablished(SystemPath),
+    /// The connection to the remote system has been lost.
+    ///
+    /// The dispatcher will try to re-establish it according to its [RetryStrategy](crate::prelude::RetryStrategy).
+    ConnectionLost(SystemPath),
+    /// The dispatcher has given up on (re-)establishing a connection to the remote system,
+    /// after exhausting its [RetryStrategy](crate::prelude::RetryStrategy).
+    RetriesExhausted(SystemPath),
+    /// Messages queued for the remote system were dropped without being sent.
+    ///
+    /// This happens when the dispatcher gives up on the remote system, or when its queue is full.
+    /// In the latter case, the messages may have been forwarded to the deadletter box instead,
+    /// depending on the [QueueOverflowPolicy](crate::prelude::QueueOverflowPolicy).
+    ///
+    /// Contains the number of dropped messages.
+    QueueDropped(SystemPath, usize),
+    /// The [FailureDetector](crate::prelude::FailureDetector) suspects the remote system to have failed,
+    /// as it has not answered heartbeats in a while.
+    ///
+    /// The connection may still be open, unless
+    /// [close_on_suspicion](crate::prelude::NetworkConfig::set_close_on_suspicion) is set.
+    Suspected(SystemPath),
+    /// A previously [suspected](NetworkStatus::Suspected) remote system has answered heartbeats again.
+    Alive(SystemPath),
+    /// The remote system was restarted, as it connected with a different
+    /// [incarnation](crate::prelude::KompactSystem::incarnation) than before.
+    ///
+    /// Actors of the previous incarnation are gone, so unique paths to them no longer resolve.
+    /// Indicated before the connection to the new incarnation is [established](NetworkStatus::ConnectionEstablished).
+    Restarted(SystemPath),
+    /// UDP messages from the remote system were dropped, as some of their fragments
+    /// did not arrive within the [reassembly timeout](crate::prelude::NetworkConfig::set_udp_reassembly_timeout).
+    ///
+    /// Contains the number of dropped messages.
+    IncompleteUdpMessages(SystemPath, usize),
+    /// The remote system deliberately closed its connection, e.g. as it
+    /// [left](crate::prelude::KompactSystem::leave_remote) this system or shut down.
+    ///
+    /// Unlike for a [lost connection](NetworkStatus::ConnectionLost), the dispatcher does not try to reconnect,
+    /// until another message is sent to the remote system.
+    PeerLeft(SystemPath),
+    /// This system [left](crate::prelude::KompactSystem::leave_remote) the remote system,
+    /// after sending all messages which were sent to it before.
+    Left(SystemPath),
+    /// The connection to the remote system was [closed](crate::prelude::KompactSystem::close_connection)
+    /// by this system, dropping the messages queued for it.
+    ///
+    /// The dispatcher does not try to reconnect, until another message is sent to the remote system.
+    ConnectionClosed(SystemPath),
+    /// The connection to the remote system was closed with a Bye by either system, as no messages were exchanged
+    /// for the [idle timeout](crate::prelude::NetworkConfig::set_idle_timeout).
+    ///
+    /// The connection is re-established when the next message is sent to the remote system.
+    ConnectionIdle(SystemPath),
+}
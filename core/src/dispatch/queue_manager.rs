@@ -4,23 +4,64 @@ use std::{
     net::SocketAddr,
 };
 
+/// What to do with frames for a destination whose queue is full
+///
+/// See [NetworkConfig::set_max_queued_frames](crate::prelude::NetworkConfig::set_max_queued_frames)
+/// and [NetworkConfig::set_max_queued_bytes](crate::prelude::NetworkConfig::set_max_queued_bytes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueOverflowPolicy {
+    /// Drop the oldest queued frames, until the new frame fits
+    DropOldest,
+    /// Drop the new frame
+    DropNewest,
+    /// Forward the new frame to the [deadletter box](crate::prelude::DeadletterBox)
+    RejectToDeadletter,
+}
+
+// Deriving requires `#[default]` on the variant, which needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for QueueOverflowPolicy {
+    fn default() -> Self {
+        QueueOverflowPolicy::DropOldest
+    }
+}
+
 /// Wrapper around a hashmap of frame queues.
 ///
 /// Used when waiting for connections to establish and drained when possible.
 /// `priority_queue` allows the NetworkDispatcher to maintain FIFO Order in the event of shaky connections
+///
+/// Each destination's queues (including the priority queue) are limited to `max_frames` frames
+/// and `max_bytes` bytes in total, if set.
 pub struct QueueManager {
     inner: HashMap<SocketAddr, VecDeque<SerialisedFrame>>,
     priority_queue: HashMap<SocketAddr, VecDeque<SerialisedFrame>>,
+    queued_bytes: HashMap<SocketAddr, usize>,
+    max_frames: Option<usize>,
+    max_bytes: Option<usize>,
+    overflow_policy: QueueOverflowPolicy,
 }
 
 impl QueueManager {
-    pub fn new() -> Self {
+    pub fn with_limits(
+        max_frames: Option<usize>,
+        max_bytes: Option<usize>,
+        overflow_policy: QueueOverflowPolicy,
+    ) -> Self {
         QueueManager {
             inner: HashMap::new(),
             priority_queue: HashMap::new(),
+            queued_bytes: HashMap::new(),
+            max_frames,
+            max_bytes,
+            overflow_policy,
         }
     }
 
+    pub fn overflow_policy(&self) -> QueueOverflowPolicy {
+        self.overflow_policy
+    }
+
     /*
     The queuemanager is really just a struct, no need to stop it?
     pub fn stop(self) -> () {
@@ -28,21 +69,80 @@ impl QueueManager {
     }
     */
     /// Appends the given frame onto the SocketAddr's queue
-    pub fn enqueue_frame(&mut self, frame: SerialisedFrame, dst: SocketAddr) {
+    ///
+    /// If the queue is full, the frames which did not fit are returned, according to the overflow policy.
+    /// Those are either the oldest queued frames, or the given frame itself.
+    #[must_use = "overflowing frames must be accounted for"]
+    pub fn enqueue_frame(
+        &mut self,
+        frame: SerialisedFrame,
+        dst: SocketAddr,
+    ) -> Vec<SerialisedFrame> {
+        let mut overflow = Vec::new();
+        if let Some(max) = self.max_bytes {
+            if frame.len() > max {
+                // Would never fit
+                overflow.push(frame);
+                return overflow;
+            }
+        }
+        while self.is_full(&dst, frame.len()) {
+            match self.overflow_policy {
+                QueueOverflowPolicy::DropOldest => match self.pop_frame(&dst) {
+                    Some(oldest) => overflow.push(oldest),
+                    None => break,
+                },
+                QueueOverflowPolicy::DropNewest | QueueOverflowPolicy::RejectToDeadletter => {
+                    overflow.push(frame);
+                    return overflow;
+                }
+            }
+        }
+        *self.queued_bytes.entry(dst).or_insert(0) += frame.len();
         self.inner
             .entry(dst)
             .or_insert_with(VecDeque::new)
             .push_front(frame);
+        overflow
     }
 
     /// Appends the given frame onto the SocketAddr's queue
+    ///
+    /// Priority frames were already accepted once, so they are never rejected,
+    /// but count towards the limits of the queue.
     pub fn enqueue_priority_frame(&mut self, frame: SerialisedFrame, dst: SocketAddr) {
+        *self.queued_bytes.entry(dst).or_insert(0) += frame.len();
         self.priority_queue
             .entry(dst)
             .or_insert_with(VecDeque::new)
             .push_front(frame);
     }
 
+    /// Returns `true` if another frame of `len` bytes does not fit into the SocketAddr's queue
+    fn is_full(&self, dst: &SocketAddr, len: usize) -> bool {
+        let frames_full = match self.max_frames {
+            Some(max) => self.queued_frames(dst) >= max,
+            None => false,
+        };
+        let bytes_full = match self.max_bytes {
+            Some(max) => self.queued_bytes(dst) + len > max,
+            None => false,
+        };
+        frames_full || bytes_full
+    }
+
+    /// Returns the number of frames queued for the SocketAddr
+    pub fn queued_frames(&self, dst: &SocketAddr) -> usize {
+        let priority = self.priority_queue.get(dst).map_or(0, |q| q.len());
+        let normal = self.inner.get(dst).map_or(0, |q| q.len());
+        priority + normal
+    }
+
+    /// Returns the number of bytes queued for the SocketAddr
+    pub fn queued_bytes(&self, dst: &SocketAddr) -> usize {
+        self.queued_bytes.get(dst).copied().unwrap_or(0)
+    }
+
     /// Extracts the next queue-up frame for the SocketAddr, if one exists
     ///
     /// If the SocketAddr exists but its queue is empty, the entry is removed.
@@ -57,12 +157,48 @@ impl QueueManager {
                 self.inner.remove(dst);
             }
         }
+        if let Some(ref frame) = res {
+            let remaining = match self.queued_bytes.get_mut(dst) {
+                Some(bytes) => {
+                    *bytes = bytes.saturating_sub(frame.len());
+                    *bytes
+                }
+                None => 0,
+            };
+            if remaining == 0 && self.queued_frames(dst) == 0 {
+                self.queued_bytes.remove(dst);
+            }
+        }
         res
     }
 
-    pub fn drop_queue(&mut self, addr: &SocketAddr) {
-        self.priority_queue.remove(addr);
-        self.inner.remove(addr);
+    /// Removes all frames queued for the SocketAddr, returning them in the order they would have been sent
+    pub fn drop_queue(&mut self, addr: &SocketAddr) -> Vec<SerialisedFrame> {
+        // Queues are popped from the back
+        let mut dropped = Vec::new();
+        if let Some(priority) = self.priority_queue.remove(addr) {
+            dropped.extend(priority.into_iter().rev());
+        }
+        if let Some(normal) = self.inner.remove(addr) {
+            dropped.extend(normal.into_iter().rev());
+        }
+        self.queued_bytes.remove(addr);
+        dropped
+    }
+
+    /// Moves all frames queued for `from` over to the queue for `to`,
+    /// ahead of any frames already queued for `to`
+    ///
+    /// The moved frames may exceed the limits of the queue for `to`.
+    pub fn move_queue(&mut self, from: &SocketAddr, to: SocketAddr) {
+        for queues in [&mut self.priority_queue, &mut self.inner].iter_mut() {
+            if let Some(moved) = queues.remove(from) {
+                queues.entry(to).or_insert_with(VecDeque::new).extend(moved);
+            }
+        }
+        if let Some(bytes) = self.queued_bytes.remove(from) {
+            *self.queued_bytes.entry(to).or_insert(0) += bytes;
+        }
     }
 
     /*
@@ -105,3 +241,75 @@ impl QueueManager {
         self.inner.get(dst).map_or(false, |q| !q.is_empty())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    fn frame(id: u8, len: usize) -> SerialisedFrame {
+        SerialisedFrame::Bytes(Bytes::from(vec![id; len]))
+    }
+
+    fn frame_ids(overflow: &[SerialisedFrame]) -> Vec<u8> {
+        overflow.iter().map(|f| f.bytes()[0]).collect()
+    }
+
+    #[test]
+    fn frame_limit_overflow_policies() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+
+        let mut drop_oldest =
+            QueueManager::with_limits(Some(2), None, QueueOverflowPolicy::DropOldest);
+        let mut drop_newest =
+            QueueManager::with_limits(Some(2), None, QueueOverflowPolicy::DropNewest);
+        for id in 0..2 {
+            assert!(drop_oldest.enqueue_frame(frame(id, 10), addr).is_empty());
+            assert!(drop_newest.enqueue_frame(frame(id, 10), addr).is_empty());
+        }
+        assert_eq!(
+            frame_ids(&drop_oldest.enqueue_frame(frame(2, 10), addr)),
+            vec![0]
+        );
+        assert_eq!(
+            frame_ids(&drop_newest.enqueue_frame(frame(2, 10), addr)),
+            vec![2]
+        );
+
+        assert_eq!(drop_oldest.queued_frames(&addr), 2);
+        assert_eq!(drop_oldest.queued_bytes(&addr), 20);
+        let remaining: Vec<u8> = std::iter::from_fn(|| drop_oldest.pop_frame(&addr))
+            .map(|f| f.bytes()[0])
+            .collect();
+        assert_eq!(remaining, vec![1, 2]);
+        assert_eq!(drop_oldest.queued_bytes(&addr), 0);
+    }
+
+    #[test]
+    fn byte_limit_counts_priority_frames() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let other: SocketAddr = "127.0.0.1:4001".parse().unwrap();
+        let mut queues =
+            QueueManager::with_limits(None, Some(100), QueueOverflowPolicy::DropOldest);
+
+        queues.enqueue_priority_frame(frame(0, 40), addr);
+        assert!(queues.enqueue_frame(frame(1, 40), addr).is_empty());
+        // Evicts the oldest frame, which is the priority frame
+        assert_eq!(
+            frame_ids(&queues.enqueue_frame(frame(2, 40), addr)),
+            vec![0]
+        );
+        assert_eq!(queues.queued_bytes(&addr), 80);
+        // Can never fit
+        assert_eq!(
+            frame_ids(&queues.enqueue_frame(frame(3, 101), addr)),
+            vec![3]
+        );
+
+        queues.move_queue(&addr, other);
+        assert_eq!(queues.queued_bytes(&addr), 0);
+        assert_eq!(queues.queued_bytes(&other), 80);
+        assert_eq!(frame_ids(&queues.drop_queue(&other)), vec![1, 2]);
+        assert_eq!(queues.queued_bytes(&other), 0);
+    }
+}
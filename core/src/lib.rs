@@ -189,6 +189,7 @@ pub mod prelude {
             ActorRef,
             ActorRefFactory,
             ActorRefStrong,
+            Address,
             Dispatcher,
             DispatcherRef,
             Dispatching,
@@ -228,7 +229,14 @@ pub mod prelude {
             Require,
             RequireRef,
         },
-        net::buffers::{BufferConfig, ChunkLease, ChunkRef},
+        net::{
+            auth::ClusterSecret,
+            buffers::{BufferConfig, ChunkLease, ChunkRef},
+            failure_detector::FailureDetector,
+            memory::MemoryNetwork,
+            transport::{NetworkTransport, TransportContext},
+            RetryStrategy,
+        },
         ports::{Port, ProvidedPort, ProvidedRef, RequiredPort, RequiredRef},
         runtime::{KompactConfig, KompactSystem, SystemHandle},
         supervision::{FaultContext, RecoveryHandler},
@@ -237,10 +245,21 @@ pub mod prelude {
 
     pub use crate::{
         default_components::{CustomComponents, DeadletterBox, LocalDispatcher},
-        dispatch::{NetworkConfig, NetworkDispatcher},
+        dispatch::{
+            delivery_failure::{DeliveryFailure, DeliveryFailureReason, DeliveryFailureReporting},
+            fault_injection::{FaultDelay, FaultInjector},
+            network_status_port::{NetworkStatus, NetworkStatusPort},
+            queue_manager::QueueOverflowPolicy,
+            NetworkConfig,
+            NetworkDispatcher,
+        },
         messaging::{
+            ConnectionInfo,
+            ConnectionRequest,
+            Departure,
             DispatchEnvelope,
             MsgEnvelope,
+            MulticastMembership,
             NetMessage,
             PathResolvable,
             RegistrationError,
@@ -274,6 +293,9 @@ pub mod prelude {
 
     pub use crate::routing::groups::StorePolicy;
 
+    #[cfg(feature = "tls")]
+    pub use crate::net::tls::TlsConfig;
+
     #[cfg(all(nightly, feature = "type_erasure"))]
     pub use crate::utils::erased::CreateErased;
 }
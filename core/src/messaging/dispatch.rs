@@ -1,4 +1,6 @@
 use super::*;
+use crate::net::ConnectionState;
+use std::net::SocketAddr;
 
 /// An abstraction over lazy or eagerly serialised data sent to the dispatcher
 #[derive(Debug)]
@@ -77,4 +79,62 @@ pub enum DispatchEnvelope {
     Event(EventEnvelope),
     /// Killed components send their BufferChunks to the Dispatcher for safe de-allocation
     LockedChunk(BufferChunk),
+    /// A request to join or leave a UDP multicast group
+    Multicast(MulticastMembership),
+    /// A request to gracefully leave remote systems
+    Leave(Departure),
+    /// A request to manage the connections to remote systems
+    Connection(ConnectionRequest),
+}
+
+/// A change in the UDP multicast groups a system is a member of
+///
+/// Messages sent to a [multicast path](crate::prelude::NamedPath::multicast) are received
+/// by all systems which joined its group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MulticastMembership {
+    /// Join the multicast group with the given address and port
+    Join(SocketAddr),
+    /// Leave the multicast group with the given address and port
+    Leave(SocketAddr),
+}
+
+/// The remote systems a system gracefully leaves
+///
+/// Messages which were sent to them before are still delivered, and then the connections are closed.
+/// The remote systems are told that this system left, so they do not try to reconnect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Departure {
+    /// Leave the remote system with the given path
+    Remote(SystemPath),
+    /// Leave all remote systems
+    All,
+}
+
+/// A request to manage the connections of a system's dispatcher to remote systems
+///
+/// Connections are usually established when the first message is sent to a remote system,
+/// and only closed when they fail or the system shuts down.
+#[derive(Debug)]
+pub enum ConnectionRequest {
+    /// Establish a connection to the remote system with the given path, without sending a message
+    Connect(SystemPath),
+    /// Fulfil the promise with the current connections
+    List(utils::KPromise<Vec<ConnectionInfo>>),
+    /// Close the connection to the remote system with the given path right away,
+    /// dropping the messages queued for it
+    Close(SystemPath),
+}
+
+/// The state of a connection of a system's dispatcher to a remote system
+#[derive(Debug)]
+pub struct ConnectionInfo {
+    /// The path of the remote system
+    pub system: SystemPath,
+    /// The state of the connection
+    pub state: ConnectionState,
+    /// The number of messages queued until the connection is established
+    pub queued_frames: usize,
+    /// The number of bytes of the queued messages
+    pub queued_bytes: usize,
 }
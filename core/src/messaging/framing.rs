@@ -1,12 +1,21 @@
 //! Message framing (serialization and deserialization into and from byte buffers)
 
 use crate::{
-    actors::{ActorPath, NamedPath, SystemField, SystemPath, Transport, UniquePath},
+    actors::{
+        is_valid_domain,
+        ActorPath,
+        Address,
+        NamedPath,
+        SystemField,
+        SystemPath,
+        Transport,
+        UniquePath,
+    },
     serialisation::{serialisation_ids, Deserialiser, SerError, SerId, Serialisable},
 };
 use bitfields::BitField;
 use bytes::{Buf, BufMut};
-use std::{any::Any, convert::TryFrom, net::IpAddr};
+use std::{any::Any, convert::TryFrom, net::IpAddr, path::PathBuf};
 use uuid::Uuid;
 
 /// The type of address used
@@ -19,6 +28,8 @@ pub enum AddressType {
     IPv6 = 1,
     /// A domain name
     Domain = 2,
+    /// The path of a Unix domain socket
+    Unix = 3,
 }
 
 /// The type of path used
@@ -41,9 +52,12 @@ impl BitField for PathType {
     const WIDTH: usize = 1;
 }
 
+/// The transport field spans the address type bits, which are masked out of it,
+/// so that only the lowest two bits and the three bits above the address type encode the transport.
+/// This keeps the encoding of the original transports and address types as it was.
 impl BitField for Transport {
     const POS: usize = 0;
-    const WIDTH: usize = 5;
+    const WIDTH: usize = 7;
 }
 
 impl Into<u8> for AddressType {
@@ -58,9 +72,30 @@ impl Into<u8> for PathType {
     }
 }
 
+/// Marks a [custom transport](Transport::Custom) in the transport code, which holds its id in the lower bits
+const CUSTOM_TRANSPORT: u8 = 0b10000;
+
+/// The bits of the transport field that hold the address type
+const ADDRESS_TYPE_BITS: u8 = 0b1100;
+
 impl Into<u8> for Transport {
     fn into(self) -> u8 {
-        self as u8
+        let code = match self {
+            Transport::LOCAL => 0b00,
+            Transport::TCP => 0b01,
+            Transport::UDP => 0b10,
+            Transport::RUDP => 0b11,
+            Transport::UDS => 0b100,
+            Transport::Custom(id) => {
+                debug_assert!(
+                    id <= Transport::MAX_CUSTOM_ID,
+                    "Invalid custom transport id"
+                );
+                CUSTOM_TRANSPORT | id
+            }
+        };
+        // Skip over the address type bits
+        (code & 0b11) | ((code >> 2) << 4)
     }
 }
 
@@ -71,6 +106,8 @@ impl TryFrom<u8> for AddressType {
         match x {
             x if x == AddressType::IPv4 as u8 => Ok(AddressType::IPv4),
             x if x == AddressType::IPv6 as u8 => Ok(AddressType::IPv6),
+            x if x == AddressType::Domain as u8 => Ok(AddressType::Domain),
+            x if x == AddressType::Unix as u8 => Ok(AddressType::Unix),
             _ => Err(SerError::InvalidType("Unsupported AddressType".into())),
         }
     }
@@ -92,10 +129,17 @@ impl TryFrom<u8> for Transport {
     type Error = SerError;
 
     fn try_from(x: u8) -> Result<Self, Self::Error> {
-        match x {
-            x if x == Transport::LOCAL as u8 => Ok(Transport::LOCAL),
-            x if x == Transport::UDP as u8 => Ok(Transport::UDP),
-            x if x == Transport::TCP as u8 => Ok(Transport::TCP),
+        let x = x & !ADDRESS_TYPE_BITS;
+        let code = (x & 0b11) | ((x >> 4) << 2);
+        match code {
+            0b00 => Ok(Transport::LOCAL),
+            0b01 => Ok(Transport::TCP),
+            0b10 => Ok(Transport::UDP),
+            0b11 => Ok(Transport::RUDP),
+            0b100 => Ok(Transport::UDS),
+            x if x & CUSTOM_TRANSPORT != 0 && x & !CUSTOM_TRANSPORT <= Transport::MAX_CUSTOM_ID => {
+                Ok(Transport::Custom(x & !CUSTOM_TRANSPORT))
+            }
             _ => Err(SerError::InvalidType(
                 "Unsupported transport protocol".into(),
             )),
@@ -112,6 +156,16 @@ impl<'a> From<&'a IpAddr> for AddressType {
     }
 }
 
+impl<'a> From<&'a Address> for AddressType {
+    fn from(addr: &'a Address) -> Self {
+        match addr {
+            Address::Ip(ip) => ip.into(),
+            Address::Domain(_) => AddressType::Domain,
+            Address::Unix(_) => AddressType::Unix,
+        }
+    }
+}
+
 /// The header for a [system path](SystemPath)
 #[derive(Debug)]
 pub struct SystemPathHeader {
@@ -130,7 +184,7 @@ impl SystemPathHeader {
             ActorPath::Unique(_) => PathType::Unique,
             ActorPath::Named(_) => PathType::Named,
         };
-        let address_type: AddressType = sys.address().into();
+        let address_type: AddressType = sys.host().into();
 
         let mut storage = [0u8];
         storage
@@ -147,7 +201,7 @@ impl SystemPathHeader {
             storage,
             path_type,
             protocol: sys.protocol(),
-            address_type: sys.address().into(),
+            address_type: sys.host().into(),
         }
     }
 
@@ -156,7 +210,7 @@ impl SystemPathHeader {
         use bitfields::BitFieldExt;
 
         let path_type = PathType::Unique; // doesn't matter, will be ignored anyway
-        let address_type: AddressType = sys.address().into();
+        let address_type: AddressType = sys.host().into();
 
         let mut storage = [0u8];
         storage
@@ -173,7 +227,7 @@ impl SystemPathHeader {
             storage,
             path_type,
             protocol: sys.protocol(),
-            address_type: sys.address().into(),
+            address_type: sys.host().into(),
         }
     }
 
@@ -233,6 +287,9 @@ impl TryFrom<u8> for SystemPathHeader {
 /// |                   Address (4/16/ * bytes)                  ...| Port (2 bytes) |
 /// +---------------------------------------------------------------+----------------+
 /// ```
+///
+/// Domain name addresses are length-prefixed (1 byte), followed by the ASCII encoded name.
+/// Unix socket addresses are length-prefixed (1 byte) as well, followed by the UTF-8 encoded path.
 impl Serialisable for SystemPath {
     fn ser_id(&self) -> SerId {
         serialisation_ids::SYSTEM_PATH
@@ -241,9 +298,11 @@ impl Serialisable for SystemPath {
     fn size_hint(&self) -> Option<usize> {
         let mut size: usize = 0;
         size += 1; // header
-        size += match self.address() {
-            IpAddr::V4(_) => 4,  // IPv4 uses 4 bytes
-            IpAddr::V6(_) => 16, // IPv4 uses 16 bytes
+        size += match self.host() {
+            Address::Ip(IpAddr::V4(_)) => 4,             // IPv4 uses 4 bytes
+            Address::Ip(IpAddr::V6(_)) => 16,            // IPv4 uses 16 bytes
+            Address::Domain(domain) => 1 + domain.len(), // length prefix + name
+            Address::Unix(path) => 1 + path.as_os_str().len(), // length prefix + path
         };
         size += 2; // port # (0-65_535)
         Some(size)
@@ -265,10 +324,26 @@ impl Serialisable for SystemPath {
 
 #[inline(always)]
 fn system_path_put_into_buf(path: &SystemPath, buf: &mut dyn BufMut) -> Result<(), SerError> {
-    match *path.address() {
-        IpAddr::V4(ref ip) => buf.put_slice(&ip.octets()),
-        IpAddr::V6(ref ip) => buf.put_slice(&ip.octets()),
-        // TODO support named Domain
+    match path.host() {
+        Address::Ip(IpAddr::V4(ref ip)) => buf.put_slice(&ip.octets()),
+        Address::Ip(IpAddr::V6(ref ip)) => buf.put_slice(&ip.octets()),
+        Address::Domain(ref domain) => {
+            let len = u8::try_from(domain.len()).map_err(|_| {
+                SerError::InvalidData("Domain name overflows designated 1 byte length.".into())
+            })?;
+            buf.put_u8(len);
+            buf.put_slice(domain.as_bytes());
+        }
+        Address::Unix(ref path) => {
+            let path = path.to_str().ok_or_else(|| {
+                SerError::InvalidData("Unix socket path is not valid UTF-8.".into())
+            })?;
+            let len = u8::try_from(path.len()).map_err(|_| {
+                SerError::InvalidData("Unix socket path overflows designated 1 byte length.".into())
+            })?;
+            buf.put_u8(len);
+            buf.put_slice(path.as_bytes());
+        }
     }
     buf.put_u16(path.port());
     Ok(())
@@ -278,7 +353,7 @@ fn system_path_from_buf(buf: &mut dyn Buf) -> Result<(SystemPathHeader, SystemPa
     // Deserialize system path
     let fields: u8 = buf.get_u8();
     let header = SystemPathHeader::try_from(fields)?;
-    let address: IpAddr = match header.address_type {
+    let address: Address = match header.address_type {
         AddressType::IPv4 => {
             if buf.remaining() < 4 {
                 return Err(SerError::InvalidData(
@@ -287,7 +362,7 @@ fn system_path_from_buf(buf: &mut dyn Buf) -> Result<(SystemPathHeader, SystemPa
             } else {
                 let mut ip_bytes = [0u8; 4];
                 buf.copy_to_slice(&mut ip_bytes);
-                IpAddr::from(ip_bytes)
+                Address::Ip(IpAddr::from(ip_bytes))
             }
         }
         AddressType::IPv6 => {
@@ -298,15 +373,58 @@ fn system_path_from_buf(buf: &mut dyn Buf) -> Result<(SystemPathHeader, SystemPa
             } else {
                 let mut ip_bytes = [0u8; 16];
                 buf.copy_to_slice(&mut ip_bytes);
-                IpAddr::from(ip_bytes)
+                Address::Ip(IpAddr::from(ip_bytes))
             }
         }
         AddressType::Domain => {
-            unimplemented!();
+            let len = if buf.has_remaining() {
+                buf.get_u8() as usize
+            } else {
+                0
+            };
+            if len == 0 || buf.remaining() < len {
+                return Err(SerError::InvalidData(format!(
+                    "Could not parse {} bytes for domain name",
+                    len
+                )));
+            }
+            let mut domain_bytes = vec![0u8; len];
+            buf.copy_to_slice(&mut domain_bytes);
+            match String::from_utf8(domain_bytes) {
+                Ok(domain) if is_valid_domain(&domain) => Address::Domain(domain),
+                _ => {
+                    return Err(SerError::InvalidData("Invalid domain name".into()));
+                }
+            }
+        }
+        AddressType::Unix => {
+            let len = if buf.has_remaining() {
+                buf.get_u8() as usize
+            } else {
+                0
+            };
+            if len == 0 || buf.remaining() < len {
+                return Err(SerError::InvalidData(format!(
+                    "Could not parse {} bytes for Unix socket path",
+                    len
+                )));
+            }
+            let mut path_bytes = vec![0u8; len];
+            buf.copy_to_slice(&mut path_bytes);
+            match String::from_utf8(path_bytes) {
+                Ok(path) => Address::Unix(PathBuf::from(path)),
+                Err(_) => {
+                    return Err(SerError::InvalidData("Invalid Unix socket path".into()));
+                }
+            }
         }
     };
     let port = buf.get_u16();
-    let system_path = SystemPath::new(header.protocol, address, port);
+    let system_path = match address {
+        Address::Ip(ip) => SystemPath::new(header.protocol, ip, port),
+        Address::Domain(domain) => SystemPath::with_domain(header.protocol, domain, port),
+        unix @ Address::Unix(_) => SystemPath::with_address(header.protocol, unix, port),
+    };
     Ok((header, system_path))
 }
 
@@ -430,6 +548,56 @@ impl Deserialiser<ActorPath> for ActorPath {
     }
 }
 
+/// Passes each byte of the serialised [ActorPath](ActorPath) at the start of `buf` to `visit`,
+/// leaving `buf` right after it, without deserialising the path
+pub(crate) fn visit_serialised_path(
+    buf: &mut dyn Buf,
+    visit: &mut dyn FnMut(u8),
+) -> Result<(), SerError> {
+    fn take(buf: &mut dyn Buf, len: usize, visit: &mut dyn FnMut(u8)) -> Result<(), SerError> {
+        if buf.remaining() < len {
+            return Err(SerError::InvalidData(format!(
+                "Could not get {} bytes of actor path",
+                len
+            )));
+        }
+        for _ in 0..len {
+            visit(buf.get_u8());
+        }
+        Ok(())
+    }
+    fn take_len(
+        buf: &mut dyn Buf,
+        width: usize,
+        visit: &mut dyn FnMut(u8),
+    ) -> Result<usize, SerError> {
+        let mut len = 0usize;
+        take(buf, width, &mut |byte| {
+            len = (len << 8) | byte as usize;
+            visit(byte);
+        })?;
+        Ok(len)
+    }
+
+    let mut fields = 0u8;
+    take(buf, 1, &mut |byte| {
+        fields = byte;
+        visit(byte);
+    })?;
+    let header = SystemPathHeader::try_from(fields)?;
+    let address_len = match header.address_type {
+        AddressType::IPv4 => 4,
+        AddressType::IPv6 => 16,
+        AddressType::Domain | AddressType::Unix => take_len(buf, 1, visit)?,
+    };
+    take(buf, address_len + 2, visit)?; // address + port
+    let path_len = match header.path_type {
+        PathType::Unique => 16,
+        PathType::Named => take_len(buf, 2, visit)?,
+    };
+    take(buf, path_len, visit)
+}
+
 #[cfg(test)]
 mod serialisation_tests {
     use super::*;
@@ -476,6 +644,37 @@ mod serialisation_tests {
         assert_eq!(system_path, deserialised);
     }
 
+    #[test]
+    fn serialised_paths_are_visited_whole() {
+        let paths = vec![
+            ActorPath::Unique(UniquePath::with_system(
+                SystemPath::new(Transport::TCP, "127.0.0.1".parse().unwrap(), 8080u16),
+                Uuid::new_v4(),
+            )),
+            ActorPath::Named(NamedPath::with_system(
+                SystemPath::new(Transport::UDP, "::1".parse().unwrap(), 8080u16),
+                vec!["parent".into(), "child".into()],
+            )),
+            ActorPath::Named(NamedPath::with_system(
+                SystemPath::with_domain(Transport::TCP, "example.com".into(), 8080u16),
+                vec!["actor-name".into()],
+            )),
+        ];
+        for path in paths {
+            let mut buf = BytesMut::with_capacity(path.size_hint().unwrap() + 1);
+            path.serialise(&mut buf)
+                .expect("ActorPath should serialise!");
+            buf.put_u8(0xFF);
+            let serialised = buf.to_vec();
+
+            let mut visited = Vec::new();
+            visit_serialised_path(&mut buf, &mut |byte| visited.push(byte))
+                .expect("ActorPath should be visited!");
+            assert_eq!(visited[..], serialised[..serialised.len() - 1]);
+            assert_eq!(buf.remaining(), 1);
+        }
+    }
+
     #[test]
     fn actor_path_serequiv() {
         let expected_transport: Transport = Transport::TCP;
@@ -514,7 +713,7 @@ mod serialisation_tests {
                 .expect("UUID ActorPath Deserialisation should succeed");
             assert_eq!(buf.len(), 0);
             let deser_sys: &SystemPath = SystemField::system(&deser_path);
-            assert_eq!(deser_sys.address(), &expected_addr);
+            assert_eq!(deser_sys.host().ip(), Some(&expected_addr));
             match deser_path {
                 ActorPath::Unique(ref up) => {
                     assert_eq!(up.id(), unique_id);
@@ -536,7 +735,7 @@ mod serialisation_tests {
                 .expect("Named ActorPath Deserialisation should succeed");
             assert_eq!(buf.len(), 0);
             let deser_sys: &SystemPath = SystemField::system(&deser_path);
-            assert_eq!(deser_sys.address(), &expected_addr);
+            assert_eq!(deser_sys.host().ip(), Some(&expected_addr));
             match deser_path {
                 ActorPath::Unique(_) => panic!("expected Named path, got Unique path"),
                 ActorPath::Named(ref np) => {
@@ -545,4 +744,113 @@ mod serialisation_tests {
             }
         }
     }
+
+    #[test]
+    fn unix_socket_path_serequiv() {
+        let system_path = SystemPath::with_unix_socket(PathBuf::from("/tmp/node-a.sock"));
+        let named_path = ActorPath::Named(NamedPath::with_system(
+            system_path.clone(),
+            vec!["foo".to_string()],
+        ));
+        assert_eq!(
+            SystemPathHeader::from_path(&named_path).address_type,
+            AddressType::Unix
+        );
+
+        let size = Serialisable::size_hint(&named_path).expect("Paths should have size hints");
+        let mut buf = BytesMut::with_capacity(size);
+        Serialisable::serialise(&named_path, &mut buf)
+            .expect("ActorPath Serialisation should succeed");
+        assert_eq!(buf.len(), size);
+        let mut buf = buf.copy_to_bytes(buf.remaining());
+        let deser_path =
+            ActorPath::deserialise(&mut buf).expect("ActorPath Deserialisation should succeed");
+        assert_eq!(named_path, deser_path);
+        assert_eq!(deser_path.protocol(), Transport::UDS);
+    }
+
+    #[test]
+    fn custom_transport_path_serequiv() {
+        for id in &[0, 7, Transport::MAX_CUSTOM_ID] {
+            let system_path =
+                SystemPath::new(Transport::Custom(*id), "127.0.0.1".parse().unwrap(), 4000);
+            let unique_path =
+                ActorPath::Unique(UniquePath::with_system(system_path, Uuid::new_v4()));
+            let mut buf = BytesMut::with_capacity(
+                Serialisable::size_hint(&unique_path).expect("Paths should have size hints"),
+            );
+            Serialisable::serialise(&unique_path, &mut buf)
+                .expect("ActorPath Serialisation should succeed");
+            let mut buf = buf.copy_to_bytes(buf.remaining());
+            let deser_path =
+                ActorPath::deserialise(&mut buf).expect("ActorPath Deserialisation should succeed");
+            assert_eq!(unique_path, deser_path);
+            assert_eq!(deser_path.protocol(), Transport::Custom(*id));
+        }
+    }
+
+    #[test]
+    fn system_path_header_encoding() {
+        let ipv4 = SystemPath::new(Transport::TCP, "127.0.0.1".parse().unwrap(), 4000);
+        let ipv6 = SystemPath::new(Transport::UDP, "::1".parse().unwrap(), 4000);
+        let domain = SystemPath::with_domain(Transport::TCP, "node-a".into(), 4000);
+        let unix = SystemPath::with_unix_socket(PathBuf::from("/tmp/node-a.sock"));
+        let custom = SystemPath::new(
+            Transport::Custom(Transport::MAX_CUSTOM_ID),
+            "127.0.0.1".parse().unwrap(),
+            4000,
+        );
+        let expected: [(&SystemPath, u8); 5] = [
+            (&ipv4, 0b0000_0001),
+            (&ipv6, 0b0000_0110),
+            (&domain, 0b0000_1001),
+            (&unix, 0b0001_1100),
+            (&custom, 0b0111_0011),
+        ];
+        for (system_path, encoded) in expected.iter() {
+            let header = SystemPathHeader::from_system(system_path);
+            assert_eq!(header.storage[0], *encoded, "{}", system_path);
+            let decoded = SystemPathHeader::try_from(*encoded).expect("Header should deserialise");
+            assert_eq!(decoded.protocol, system_path.protocol());
+            assert_eq!(decoded.address_type, system_path.host().into());
+        }
+    }
+
+    #[test]
+    fn domain_path_serequiv() {
+        let system_path =
+            SystemPath::with_domain(Transport::TCP, "node-a.example.com".into(), 4000);
+        let named_path = ActorPath::Named(NamedPath::with_system(
+            system_path.clone(),
+            vec!["foo".to_string()],
+        ));
+        let unique_path =
+            ActorPath::Unique(UniquePath::with_system(system_path.clone(), Uuid::new_v4()));
+        assert_eq!(
+            SystemPathHeader::from_path(&named_path).address_type,
+            AddressType::Domain
+        );
+
+        let mut buf = BytesMut::with_capacity(system_path.size_hint().unwrap());
+        system_path
+            .serialise(&mut buf)
+            .expect("SystemPath should serialise!");
+        assert_eq!(buf.len(), system_path.size_hint().unwrap());
+        let deserialised =
+            SystemPath::deserialise(&mut buf).expect("SystemPath should deserialise!");
+        assert_eq!(system_path, deserialised);
+
+        for path in [named_path, unique_path].iter() {
+            let size = Serialisable::size_hint(path).expect("Paths should have size hints");
+            let mut buf = BytesMut::with_capacity(size);
+            Serialisable::serialise(path, &mut buf)
+                .expect("ActorPath Serialisation should succeed");
+            assert_eq!(buf.len(), size);
+            let mut buf = buf.copy_to_bytes(buf.remaining());
+            let deser_path =
+                ActorPath::deserialise(&mut buf).expect("ActorPath Deserialisation should succeed");
+            assert_eq!(buf.len(), 0);
+            assert_eq!(path, &deser_path);
+        }
+    }
 }
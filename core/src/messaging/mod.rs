@@ -1,7 +1,7 @@
 //! Messaging types for sending and receiving messages between remote actors.
 
 use crate::{
-    actors::{ActorPath, DynActorRef, MessageBounds, PathParseError},
+    actors::{ActorPath, DynActorRef, MessageBounds, PathParseError, SystemPath},
     net::{
         buffers::{BufferChunk, BufferEncoder, ChunkLease, ChunkRef},
         events::NetworkEvent,
@@ -45,6 +45,8 @@ pub enum EventEnvelope {
 
 /// A message that is accepted by an actor's mailbox
 #[derive(Debug)]
+// Network messages are too common to box them
+#[allow(clippy::large_enum_variant)]
 pub enum MsgEnvelope<M: MessageBounds> {
     /// A message of the actor's `Message` type
     Typed(M),
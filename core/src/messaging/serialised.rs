@@ -87,6 +87,15 @@ impl SerialisedFrame {
         }
     }
 
+    /// Discards the first `cnt` bytes of this frame, e.g. once they have been written
+    pub fn advance(&mut self, cnt: usize) {
+        match self {
+            SerialisedFrame::ChunkLease(chunk) => chunk.advance(cnt),
+            SerialisedFrame::ChunkRef(chunk) => chunk.advance(cnt),
+            SerialisedFrame::Bytes(bytes) => bytes.advance(cnt),
+        }
+    }
+
     /// Used by UDP sending which requires the frame to be a contiguous byte-sequence.
     /// Does nothing if it's already contiguous.
     pub fn make_contiguous(&mut self) {
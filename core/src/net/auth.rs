@@ -0,0 +1,185 @@
+//! Shared secret authentication for TCP channels
+
+use super::frames::{AUTH_MAC_LEN, AUTH_NONCE_LEN};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{fmt, net::SocketAddr};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Domain separation labels, such that a Start response can never be replayed as an Ack response
+const START_LABEL: &[u8] = b"kompact-start";
+const ACK_LABEL: &[u8] = b"kompact-ack";
+
+/// A secret shared by all systems of a cluster, used to authenticate TCP channels
+///
+/// When set via [NetworkConfig::set_cluster_secret](crate::prelude::NetworkConfig::set_cluster_secret),
+/// both ends of every TCP channel prove that they know the secret during the handshake,
+/// by answering a random challenge from the other end with an HMAC-SHA256 over it.
+/// Channels to hosts which fail to do so are closed before any messages are delivered.
+///
+/// The secret itself is never sent, but traffic is neither encrypted nor authenticated after the handshake.
+/// Enable the `tls` feature and use `TlsConfig` if that is required.
+/// UDP traffic is not authenticated.
+///
+/// # Example
+///
+/// ```
+/// use kompact::prelude::*;
+///
+/// let mut net_config = NetworkConfig::default();
+/// net_config.set_cluster_secret(ClusterSecret::new(b"correct horse battery staple"));
+/// let mut conf = KompactConfig::default();
+/// conf.system_components(DeadletterBox::new, net_config.build());
+/// let system = conf.build().expect("system");
+/// # system.shutdown().expect("shutdown");
+/// ```
+#[derive(Clone)]
+pub struct ClusterSecret {
+    mac: HmacSha256,
+}
+
+impl ClusterSecret {
+    /// Creates a new cluster secret from the raw `secret`
+    ///
+    /// # Panics
+    ///
+    /// If `secret` is empty.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        let secret = secret.as_ref();
+        assert!(!secret.is_empty(), "The cluster secret must not be empty");
+        ClusterSecret {
+            mac: HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length"),
+        }
+    }
+
+    /// The response to `challenge` proving that the requester of a channel knows the secret
+    ///
+    /// Binds the response to the `nonce` of the requester and the `addr` and `id` it announces in its Start.
+    pub(crate) fn start_response(
+        &self,
+        challenge: &[u8; AUTH_NONCE_LEN],
+        nonce: &[u8; AUTH_NONCE_LEN],
+        addr: SocketAddr,
+        id: Uuid,
+    ) -> [u8; AUTH_MAC_LEN] {
+        self.sign(&start_message(challenge, nonce, addr, id))
+    }
+
+    /// Returns `true` if `response` is a valid [start_response](ClusterSecret::start_response)
+    pub(crate) fn verify_start(
+        &self,
+        challenge: &[u8; AUTH_NONCE_LEN],
+        nonce: &[u8; AUTH_NONCE_LEN],
+        addr: SocketAddr,
+        id: Uuid,
+        response: &[u8; AUTH_MAC_LEN],
+    ) -> bool {
+        self.verify(&start_message(challenge, nonce, addr, id), response)
+    }
+
+    /// The response to `nonce` proving that the receiver of a channel knows the secret
+    pub(crate) fn ack_response(
+        &self,
+        challenge: &[u8; AUTH_NONCE_LEN],
+        nonce: &[u8; AUTH_NONCE_LEN],
+    ) -> [u8; AUTH_MAC_LEN] {
+        self.sign(&ack_message(challenge, nonce))
+    }
+
+    /// Returns `true` if `response` is a valid [ack_response](ClusterSecret::ack_response)
+    pub(crate) fn verify_ack(
+        &self,
+        challenge: &[u8; AUTH_NONCE_LEN],
+        nonce: &[u8; AUTH_NONCE_LEN],
+        response: &[u8; AUTH_MAC_LEN],
+    ) -> bool {
+        self.verify(&ack_message(challenge, nonce), response)
+    }
+
+    fn sign(&self, message: &[u8]) -> [u8; AUTH_MAC_LEN] {
+        let mut mac = self.mac.clone();
+        mac.update(message);
+        mac.finalize().into_bytes().into()
+    }
+
+    fn verify(&self, message: &[u8], response: &[u8; AUTH_MAC_LEN]) -> bool {
+        let mut mac = self.mac.clone();
+        mac.update(message);
+        // Constant time comparison
+        mac.verify_slice(response).is_ok()
+    }
+}
+
+impl fmt::Debug for ClusterSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret
+        f.write_str("ClusterSecret(..)")
+    }
+}
+
+/// Creates a new random nonce to challenge the remote host of a channel with
+pub(crate) fn new_nonce() -> [u8; AUTH_NONCE_LEN] {
+    rand::random()
+}
+
+fn start_message(
+    challenge: &[u8; AUTH_NONCE_LEN],
+    nonce: &[u8; AUTH_NONCE_LEN],
+    addr: SocketAddr,
+    id: Uuid,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(START_LABEL.len() + 2 * AUTH_NONCE_LEN + 16 + 18);
+    message.extend_from_slice(START_LABEL);
+    message.extend_from_slice(challenge);
+    message.extend_from_slice(nonce);
+    message.extend_from_slice(id.as_bytes());
+    match addr {
+        SocketAddr::V4(v4) => message.extend_from_slice(&v4.ip().octets()),
+        SocketAddr::V6(v6) => message.extend_from_slice(&v6.ip().octets()),
+    }
+    message.extend_from_slice(&addr.port().to_be_bytes());
+    message
+}
+
+fn ack_message(challenge: &[u8; AUTH_NONCE_LEN], nonce: &[u8; AUTH_NONCE_LEN]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ACK_LABEL.len() + 2 * AUTH_NONCE_LEN);
+    message.extend_from_slice(ACK_LABEL);
+    message.extend_from_slice(challenge);
+    message.extend_from_slice(nonce);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_verify_with_same_secret_only() {
+        let secret = ClusterSecret::new(b"secret");
+        let other = ClusterSecret::new(b"other secret");
+        let challenge = new_nonce();
+        let nonce = new_nonce();
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let id = Uuid::new_v4();
+
+        let start = secret.start_response(&challenge, &nonce, addr, id);
+        assert!(secret.verify_start(&challenge, &nonce, addr, id, &start));
+        assert!(!other.verify_start(&challenge, &nonce, addr, id, &start));
+        let other_addr: SocketAddr = "127.0.0.1:4001".parse().unwrap();
+        assert!(!secret.verify_start(&challenge, &nonce, other_addr, id, &start));
+        assert!(!secret.verify_start(&nonce, &challenge, addr, id, &start));
+
+        let ack = secret.ack_response(&challenge, &nonce);
+        assert!(secret.verify_ack(&challenge, &nonce, &ack));
+        assert!(!other.verify_ack(&challenge, &nonce, &ack));
+        assert!(!secret.verify_ack(&challenge, &nonce, &start));
+    }
+
+    #[test]
+    fn debug_hides_secret() {
+        let secret = ClusterSecret::new(b"hunter2");
+        assert_eq!(format!("{:?}", secret), "ClusterSecret(..)");
+    }
+}
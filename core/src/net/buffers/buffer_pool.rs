@@ -53,6 +53,16 @@ impl BufferPool {
         self.pool.push_back(buffer);
     }
 
+    /// Stops accounting for a buffer allocated by this pool, which was moved to another pool
+    pub fn release_buffer(&mut self) -> () {
+        self.pool_size -= 1;
+    }
+
+    /// Accounts for a buffer allocated by another pool, which will be returned to this pool
+    pub fn adopt_buffer(&mut self) -> () {
+        self.pool_size += 1;
+    }
+
     pub fn drain_returned(&mut self) -> Drain<BufferChunk> {
         self.pool.drain(0..)
     }
@@ -81,6 +91,12 @@ impl BufferPool {
         Some(self.new_buffer())
     }
 
+    /// Returns `true` if a call to `get_buffer()` would currently succeed,
+    /// either by reclaiming a returned buffer or by allocating a new one.
+    pub fn has_available(&mut self) -> bool {
+        self.pool_size < self.max_pool_size || self.pool.iter_mut().any(|b| b.free())
+    }
+
     /// Returns the number of allocated buffers and the current number of buffers in the pool
     #[allow(dead_code)]
     pub(crate) fn get_pool_sizes(&self) -> (usize, usize) {
@@ -158,6 +158,7 @@ impl DecodeBuffer {
                             Err(FramingError::InvalidFrame)
                         }
                     }
+                    FrameType::CompressedData => Ok(Frame::CompressedData(Data::new(chunk_lease))),
                     FrameType::StreamRequest => {
                         if let Ok(data) = StreamRequest::decode_from(chunk_lease) {
                             Ok(data)
@@ -165,6 +166,13 @@ impl DecodeBuffer {
                             Err(FramingError::InvalidFrame)
                         }
                     }
+                    FrameType::CreditUpdate => {
+                        if let Ok(data) = CreditUpdate::decode_from(chunk_lease) {
+                            Ok(data)
+                        } else {
+                            Err(FramingError::InvalidFrame)
+                        }
+                    }
                     FrameType::Hello => {
                         if let Ok(hello) = Hello::decode_from(chunk_lease) {
                             Ok(hello)
@@ -186,6 +194,34 @@ impl DecodeBuffer {
                             Err(FramingError::InvalidFrame)
                         }
                     }
+                    FrameType::Fragment => {
+                        if let Ok(fragment) = Fragment::decode_from(chunk_lease) {
+                            Ok(fragment)
+                        } else {
+                            Err(FramingError::InvalidFrame)
+                        }
+                    }
+                    FrameType::ReliableData => {
+                        if let Ok(data) = ReliableData::decode_from(chunk_lease) {
+                            Ok(data)
+                        } else {
+                            Err(FramingError::InvalidFrame)
+                        }
+                    }
+                    FrameType::ReliableAck => {
+                        if let Ok(ack) = ReliableAck::decode_from(chunk_lease) {
+                            Ok(ack)
+                        } else {
+                            Err(FramingError::InvalidFrame)
+                        }
+                    }
+                    FrameType::Bye => {
+                        if let Ok(bye) = Bye::decode_from(chunk_lease) {
+                            Ok(bye)
+                        } else {
+                            Err(FramingError::InvalidFrame)
+                        }
+                    }
                     _ => Err(FramingError::UnsupportedFrameType),
                 }
             } else {
@@ -197,7 +233,9 @@ impl DecodeBuffer {
             if head.content_length() == 0 {
                 match head.frame_type() {
                     // Frames without content match here for expediency, Decoder doesn't allow 0 length.
-                    FrameType::Bye => Ok(Frame::Bye()),
+                    FrameType::Bye => Ok(Frame::Bye(Bye::new(ByeReason::Left))),
+                    FrameType::Heartbeat => Ok(Frame::Heartbeat()),
+                    FrameType::HeartbeatAck => Ok(Frame::HeartbeatAck()),
                     _ => Err(FramingError::NoData),
                 }
             } else {
@@ -357,4 +395,38 @@ mod tests {
             }
         }
     }
+
+    /// Leaving is encoded like a Bye without reason, while closing an idle channel gives its reason
+    #[test]
+    fn decode_bye_reasons() {
+        let cfg = BufferConfig::default();
+        let mut pool = BufferPool::with_config(&cfg, &None);
+        let mut decode_buffer = DecodeBuffer::new(pool.get_buffer().unwrap(), &cfg);
+        for reason in [ByeReason::Left, ByeReason::Idle].iter() {
+            let mut bye = Frame::Bye(Bye::new(*reason));
+            let mut bye_bytes = BytesMut::with_capacity(FRAME_HEAD_LEN as usize + 1);
+            bye.encode_into(&mut bye_bytes).unwrap();
+            decode_buffer
+                .get_writeable()
+                .unwrap()
+                .put_slice(bye_bytes.as_ref());
+            decode_buffer.advance_writeable(bye_bytes.len());
+        }
+        assert!(matches!(
+            decode_buffer.get_frame(),
+            Ok(Frame::Bye(Bye {
+                reason: ByeReason::Left
+            }))
+        ));
+        assert!(matches!(
+            decode_buffer.get_frame(),
+            Ok(Frame::Bye(Bye {
+                reason: ByeReason::Idle
+            }))
+        ));
+        assert!(matches!(
+            decode_buffer.get_frame(),
+            Err(FramingError::NoData)
+        ));
+    }
 }
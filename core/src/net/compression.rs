@@ -0,0 +1,116 @@
+//! LZ4 compression of data frames, negotiated during the channel handshake
+//!
+//! Both hosts advertise in their [Hello](Hello) and [Start](Start) frames that they can receive
+//! compressed data frames. A host only compresses frames for a remote host that advertised it,
+//! so hosts without compression support keep receiving plain data frames.
+
+use super::*;
+use crate::{
+    messaging::NetMessage,
+    serialisation::{ser_helpers::deserialise_bytes, SerError},
+};
+use bytes::Bytes;
+use std::convert::TryInto;
+
+/// Returns a compressed copy of the data `frame`, if its payload is at least `threshold` bytes long.
+///
+/// Returns `None`, if it is shorter or compression does not make it any shorter.
+/// The frame itself is left intact, but may be made contiguous.
+pub(crate) fn compress_frame(
+    frame: &mut SerialisedFrame,
+    threshold: usize,
+) -> Option<SerialisedFrame> {
+    let head_len = FRAME_HEAD_LEN as usize;
+    if frame.len() < head_len + threshold {
+        return None;
+    }
+    frame.make_contiguous();
+    let payload = &frame.bytes()[head_len..];
+    let compressed = lz4_flex::compress_prepend_size(payload);
+    if compressed.len() >= payload.len() {
+        return None;
+    }
+    let mut buf = BytesMut::with_capacity(head_len + compressed.len());
+    FrameHead::new(FrameType::CompressedData, compressed.len()).encode_into(&mut buf);
+    buf.put_slice(&compressed);
+    Some(SerialisedFrame::Bytes(buf.freeze()))
+}
+
+/// Decompresses the `payload` of a compressed data frame and extracts the message it contains.
+///
+/// Fails if the payload claims to decompress into more than `max_len` bytes.
+pub(crate) fn decompress_payload(payload: Bytes, max_len: usize) -> Result<NetMessage, SerError> {
+    let len = payload
+        .get(..4)
+        .map(|prefix| u32::from_le_bytes(prefix.try_into().unwrap()) as usize)
+        .ok_or_else(|| SerError::InvalidData("Compressed payload is too short".into()))?;
+    if len > max_len {
+        return Err(SerError::InvalidData(format!(
+            "Compressed payload decompresses into {} bytes, more than the maximum of {}",
+            len, max_len
+        )));
+    }
+    let decompressed = lz4_flex::decompress_size_prepended(&payload)
+        .map_err(|e| SerError::InvalidData(format!("Could not decompress payload: {}", e)))?;
+    deserialise_bytes(Bytes::from(decompressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::HeapOrSer;
+    use uuid::Uuid;
+
+    fn data_frame(payload: &[u8]) -> SerialisedFrame {
+        let mut buf = BytesMut::with_capacity(FRAME_HEAD_LEN as usize + payload.len());
+        FrameHead::new(FrameType::Data, payload.len()).encode_into(&mut buf);
+        buf.put_slice(payload);
+        SerialisedFrame::Bytes(buf.freeze())
+    }
+
+    fn message_payload(data: &[u8]) -> Vec<u8> {
+        let path = ActorPath::Unique(UniquePath::new(
+            Transport::TCP,
+            "127.0.0.1".parse().unwrap(),
+            4000,
+            Uuid::new_v4(),
+        ));
+        let mut buf = BytesMut::new();
+        path.serialise(&mut buf).expect("serialise src");
+        path.serialise(&mut buf).expect("serialise dst");
+        buf.put_ser_id(42);
+        buf.put_slice(data);
+        buf.to_vec()
+    }
+
+    #[test]
+    fn compressed_frames_round_trip() {
+        let payload = message_payload(&[7u8; 4096]);
+        let mut original = data_frame(&payload);
+        let frame = compress_frame(&mut original, 1024).expect("compressed frame");
+        assert_eq!(original.bytes(), data_frame(&payload).bytes());
+        let mut bytes = Bytes::copy_from_slice(frame.bytes());
+        let head = FrameHead::decode_from(&mut bytes).expect("frame head");
+        assert_eq!(head.frame_type(), FrameType::CompressedData);
+        assert_eq!(head.content_length(), bytes.len());
+        assert!(bytes.len() < payload.len());
+
+        let msg = decompress_payload(bytes.clone(), payload.len()).expect("decompress");
+        assert_eq!(msg.ser_id(), &42);
+        match msg.data.data {
+            HeapOrSer::Serialised(data) => assert_eq!(data.as_ref(), &[7u8; 4096][..]),
+            other => panic!("Expected serialised data, got {:?}", other),
+        }
+
+        assert!(decompress_payload(bytes, payload.len() - 1).is_err());
+    }
+
+    #[test]
+    fn small_and_incompressible_frames_stay_uncompressed() {
+        let small = message_payload(&[7u8; 16]);
+        assert!(compress_frame(&mut data_frame(&small), 1024).is_none());
+
+        let random: Vec<u8> = (0..4096).map(|_| rand::random::<u8>()).collect();
+        assert!(compress_frame(&mut data_frame(&random), 1024).is_none());
+    }
+}
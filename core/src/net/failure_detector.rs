@@ -0,0 +1,391 @@
+//! Heartbeat based failure detection for TCP channels
+
+use super::config_millis;
+use hocon::Hocon;
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+/// Determines how often heartbeats are sent on each TCP channel,
+/// and when a remote system which has not sent one in a while is suspected to have failed.
+///
+/// Suspicion and recovery of remote systems are indicated on the
+/// [NetworkStatusPort](crate::prelude::NetworkStatusPort).
+///
+/// Heartbeats are sent as control frames, and thus are neither delayed by flow control,
+/// nor by large amounts of queued messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FailureDetector {
+    /// Suspect a remote system if no heartbeat was received from it for `timeout_ms` milliseconds.
+    Timeout {
+        /// The time between heartbeats sent to each remote system, in milliseconds
+        heartbeat_interval_ms: u64,
+        /// The time without heartbeats after which a remote system is suspected, in milliseconds
+        timeout_ms: u64,
+    },
+    /// Suspect a remote system once the suspicion level `phi` exceeds `threshold`,
+    /// as described in "The φ Accrual Failure Detector" by Hayashibara et al.
+    ///
+    /// `phi` is derived from the time since the last heartbeat, given the mean and standard deviation
+    /// of the last `window_size` intervals between heartbeats from the same system.
+    /// A `phi` of 1 corresponds to a 10% chance of wrongly suspecting the remote system,
+    /// a `phi` of 2 to 1%, a `phi` of 3 to 0.1%, and so on.
+    ///
+    /// The standard deviation is assumed to be at least a quarter of the heartbeat interval,
+    /// so that perfectly regular heartbeats do not make the detector overly sensitive.
+    PhiAccrual {
+        /// The time between heartbeats sent to each remote system, in milliseconds
+        heartbeat_interval_ms: u64,
+        /// The level of `phi` above which a remote system is suspected
+        threshold: f64,
+        /// An additional delay of heartbeats which is tolerated without raising `phi`, in milliseconds
+        ///
+        /// Covers pauses that are not reflected in the observed intervals, e.g. garbage collection
+        /// or scheduling delays at the remote system.
+        acceptable_pause_ms: u64,
+        /// The number of recent heartbeat intervals to base the estimate on
+        window_size: usize,
+    },
+}
+
+impl FailureDetector {
+    /// Tries to deserialise a failure detector from the `network.failure_detector` section of `config`.
+    ///
+    /// The `type` key selects the model and must be either `"timeout"` or `"phi_accrual"`.
+    /// Any omitted parameters keep their default values.
+    ///
+    /// Returns `None` if the section or its `type` is absent, and an error if the `type` is not recognised,
+    /// or any of the durations or the `window_size` is negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kompact::prelude::*;
+    /// use hocon::HoconLoader;
+    ///
+    /// let config = HoconLoader::new()
+    ///     .load_str(
+    ///         r#"{
+    ///             network.failure_detector {
+    ///                 type = "timeout",
+    ///                 heartbeat_interval_ms = 500,
+    ///                 timeout_ms = 3000,
+    ///             }
+    ///         }"#,
+    ///     )
+    ///     .expect("config")
+    ///     .hocon()
+    ///     .expect("hocon");
+    /// assert_eq!(
+    ///     FailureDetector::from_config(&config),
+    ///     Ok(Some(FailureDetector::Timeout {
+    ///         heartbeat_interval_ms: 500,
+    ///         timeout_ms: 3000,
+    ///     }))
+    /// );
+    /// ```
+    pub fn from_config(config: &Hocon) -> Result<Option<Self>, hocon::Error> {
+        let section = &config["network"]["failure_detector"];
+        let mut detector = match section["type"].as_string().as_deref() {
+            Some("timeout") => FailureDetector::timeout(),
+            Some("phi_accrual") => FailureDetector::default(),
+            Some(other) => {
+                return Err(hocon::Error::Deserialization {
+                    message: format!(
+                        "network.failure_detector.type must be \"timeout\" or \"phi_accrual\", but was \"{}\"",
+                        other
+                    ),
+                })
+            }
+            None => return Ok(None),
+        };
+        match detector {
+            FailureDetector::Timeout {
+                ref mut heartbeat_interval_ms,
+                ref mut timeout_ms,
+            } => {
+                if let Some(interval) =
+                    config_millis(section, "network.failure_detector", "heartbeat_interval_ms")?
+                {
+                    *heartbeat_interval_ms = interval;
+                }
+                if let Some(timeout) =
+                    config_millis(section, "network.failure_detector", "timeout_ms")?
+                {
+                    *timeout_ms = timeout;
+                }
+            }
+            FailureDetector::PhiAccrual {
+                ref mut heartbeat_interval_ms,
+                ref mut threshold,
+                ref mut acceptable_pause_ms,
+                ref mut window_size,
+            } => {
+                if let Some(interval) =
+                    config_millis(section, "network.failure_detector", "heartbeat_interval_ms")?
+                {
+                    *heartbeat_interval_ms = interval;
+                }
+                if let Some(phi) = section["threshold"].as_f64() {
+                    *threshold = phi;
+                }
+                if let Some(pause) =
+                    config_millis(section, "network.failure_detector", "acceptable_pause_ms")?
+                {
+                    *acceptable_pause_ms = pause;
+                }
+                if let Some(size) = section["window_size"].as_i64() {
+                    *window_size =
+                        usize::try_from(size).map_err(|_| hocon::Error::Deserialization {
+                            message: format!(
+                                "network.failure_detector.window_size must not be negative, but was {}",
+                                size
+                            ),
+                        })?;
+                }
+            }
+        }
+        Ok(Some(detector))
+    }
+
+    /// A [Timeout](FailureDetector::Timeout) detector with the default parameters,
+    /// sending heartbeats every 1000 ms and suspecting remote systems after 5000 ms.
+    pub fn timeout() -> Self {
+        FailureDetector::Timeout {
+            heartbeat_interval_ms: 1000,
+            timeout_ms: 5000,
+        }
+    }
+
+    /// Returns the time between heartbeats sent to each remote system.
+    pub fn heartbeat_interval(&self) -> Duration {
+        match *self {
+            FailureDetector::Timeout {
+                heartbeat_interval_ms,
+                ..
+            } => Duration::from_millis(heartbeat_interval_ms),
+            FailureDetector::PhiAccrual {
+                heartbeat_interval_ms,
+                ..
+            } => Duration::from_millis(heartbeat_interval_ms),
+        }
+    }
+}
+
+/// Defaults to phi accrual detection with heartbeats every 1000 ms, a threshold of 8,
+/// an acceptable pause of 2000 ms, and a window of 100 intervals.
+impl Default for FailureDetector {
+    fn default() -> Self {
+        FailureDetector::PhiAccrual {
+            heartbeat_interval_ms: 1000,
+            threshold: 8.0,
+            acceptable_pause_ms: 2000,
+            window_size: 100,
+        }
+    }
+}
+
+/// Tracks the heartbeats received from a single remote system
+#[derive(Debug)]
+pub(crate) struct HeartbeatHistory {
+    last_heartbeat: Instant,
+    /// The most recent intervals between heartbeats, in milliseconds
+    intervals: VecDeque<f64>,
+    interval_sum: f64,
+    interval_squared_sum: f64,
+    suspected: bool,
+}
+
+impl HeartbeatHistory {
+    /// Starts tracking a remote system we just connected to at `now`.
+    ///
+    /// The history is seeded with the expected heartbeat interval,
+    /// so that the first heartbeats are judged reasonably.
+    pub fn new(detector: &FailureDetector, now: Instant) -> Self {
+        let mut history = HeartbeatHistory {
+            last_heartbeat: now,
+            intervals: VecDeque::new(),
+            interval_sum: 0.0,
+            interval_squared_sum: 0.0,
+            suspected: false,
+        };
+        let expected = detector.heartbeat_interval().as_secs_f64() * 1000.0;
+        history.record_interval(detector, expected);
+        history
+    }
+
+    /// Records a heartbeat received at `now`.
+    pub fn heartbeat(&mut self, detector: &FailureDetector, now: Instant) -> () {
+        let interval = now.saturating_duration_since(self.last_heartbeat);
+        self.last_heartbeat = now;
+        self.record_interval(detector, interval.as_secs_f64() * 1000.0);
+    }
+
+    fn record_interval(&mut self, detector: &FailureDetector, interval_ms: f64) -> () {
+        let window_size = match *detector {
+            FailureDetector::PhiAccrual { window_size, .. } => window_size.max(1),
+            FailureDetector::Timeout { .. } => return, // Only the last heartbeat matters
+        };
+        if self.intervals.len() >= window_size {
+            if let Some(oldest) = self.intervals.pop_front() {
+                self.interval_sum -= oldest;
+                self.interval_squared_sum -= oldest * oldest;
+            }
+        }
+        self.intervals.push_back(interval_ms);
+        self.interval_sum += interval_ms;
+        self.interval_squared_sum += interval_ms * interval_ms;
+    }
+
+    /// Returns the suspicion level at `now` for the phi accrual model.
+    pub fn phi(&self, heartbeat_interval_ms: u64, acceptable_pause_ms: u64, now: Instant) -> f64 {
+        let elapsed = now
+            .saturating_duration_since(self.last_heartbeat)
+            .as_secs_f64()
+            * 1000.0;
+        let samples = self.intervals.len().max(1) as f64;
+        let mean = self.interval_sum / samples;
+        let variance = (self.interval_squared_sum / samples - mean * mean).max(0.0);
+        let min_std_deviation = heartbeat_interval_ms as f64 / 4.0;
+        let std_deviation = variance.sqrt().max(min_std_deviation).max(1.0);
+        phi(elapsed, mean + acceptable_pause_ms as f64, std_deviation)
+    }
+
+    /// Returns `true` if the remote system should be suspected at `now`.
+    pub fn is_suspect(&self, detector: &FailureDetector, now: Instant) -> bool {
+        match *detector {
+            FailureDetector::Timeout { timeout_ms, .. } => {
+                now.saturating_duration_since(self.last_heartbeat)
+                    > Duration::from_millis(timeout_ms)
+            }
+            FailureDetector::PhiAccrual {
+                heartbeat_interval_ms,
+                threshold,
+                acceptable_pause_ms,
+                ..
+            } => self.phi(heartbeat_interval_ms, acceptable_pause_ms, now) > threshold,
+        }
+    }
+
+    /// Re-evaluates the remote system at `now`.
+    ///
+    /// Returns `Some(true)` if it just became suspected, `Some(false)` if it just recovered
+    /// from being suspected, and `None` if nothing changed.
+    pub fn update_suspicion(&mut self, detector: &FailureDetector, now: Instant) -> Option<bool> {
+        let suspect = self.is_suspect(detector, now);
+        if suspect == self.suspected {
+            None
+        } else {
+            self.suspected = suspect;
+            Some(suspect)
+        }
+    }
+}
+
+/// The probability of a heartbeat arriving later than `elapsed`, as a negative base-10 logarithm,
+/// using a logistic approximation of the cumulative normal distribution.
+fn phi(elapsed: f64, mean: f64, std_deviation: f64) -> f64 {
+    let y = (elapsed - mean) / std_deviation;
+    let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+    if elapsed > mean {
+        -(e / (1.0 + e)).log10()
+    } else {
+        -(1.0 - 1.0 / (1.0 + e)).log10()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_suspicion() {
+        let detector = FailureDetector::Timeout {
+            heartbeat_interval_ms: 100,
+            timeout_ms: 500,
+        };
+        let start = Instant::now();
+        let mut history = HeartbeatHistory::new(&detector, start);
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(history.update_suspicion(&detector, at(400)), None);
+        assert_eq!(history.update_suspicion(&detector, at(600)), Some(true));
+        assert_eq!(history.update_suspicion(&detector, at(700)), None);
+        history.heartbeat(&detector, at(800));
+        assert_eq!(history.update_suspicion(&detector, at(900)), Some(false));
+    }
+
+    #[test]
+    fn phi_accrual_suspicion() {
+        let detector = FailureDetector::PhiAccrual {
+            heartbeat_interval_ms: 100,
+            threshold: 8.0,
+            acceptable_pause_ms: 0,
+            window_size: 10,
+        };
+        let start = Instant::now();
+        let mut history = HeartbeatHistory::new(&detector, start);
+        let at = |ms| start + Duration::from_millis(ms);
+        for i in 1..=20 {
+            history.heartbeat(&detector, at(i * 100));
+        }
+        assert_eq!(history.intervals.len(), 10);
+        // phi grows the longer the next heartbeat is overdue
+        let on_time = history.phi(100, 0, at(2100));
+        let late = history.phi(100, 0, at(2200));
+        let very_late = history.phi(100, 0, at(2400));
+        assert!(on_time < 1.0, "phi {} for an on-time heartbeat", on_time);
+        assert!(on_time < late && late < very_late);
+        assert!(!history.is_suspect(&detector, at(2150)));
+        assert_eq!(history.update_suspicion(&detector, at(2400)), Some(true));
+        history.heartbeat(&detector, at(2450));
+        assert_eq!(history.update_suspicion(&detector, at(2500)), Some(false));
+    }
+
+    #[test]
+    fn failure_detector_from_config() {
+        let config = hocon::HoconLoader::new()
+            .load_str(
+                r#"{
+                    network.failure_detector {
+                        type = "phi_accrual",
+                        threshold = 10.5,
+                        window_size = 50,
+                    }
+                }"#,
+            )
+            .expect("config")
+            .hocon()
+            .expect("hocon");
+        assert_eq!(
+            FailureDetector::from_config(&config),
+            Ok(Some(FailureDetector::PhiAccrual {
+                heartbeat_interval_ms: 1000,
+                threshold: 10.5,
+                acceptable_pause_ms: 2000,
+                window_size: 50,
+            }))
+        );
+        let empty = hocon::HoconLoader::new()
+            .load_str("{}")
+            .expect("config")
+            .hocon()
+            .expect("hocon");
+        assert_eq!(FailureDetector::from_config(&empty), Ok(None));
+        for invalid in [
+            r#"{ network.failure_detector { type = "phi_acrual" } }"#,
+            r#"{ network.failure_detector { type = "timeout", timeout_ms = -1 } }"#,
+            r#"{ network.failure_detector { type = "phi_accrual", window_size = -1 } }"#,
+        ]
+        .iter()
+        {
+            let invalid = hocon::HoconLoader::new()
+                .load_str(invalid)
+                .expect("config")
+                .hocon()
+                .expect("hocon");
+            assert!(FailureDetector::from_config(&invalid).is_err());
+        }
+    }
+}
@@ -7,7 +7,10 @@ use bytes::{Buf, BufMut};
 use std::{self, fmt::Debug};
 
 use crate::net::buffers::ChunkLease;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+};
 use uuid::Uuid;
 
 //use stream::StreamId;
@@ -16,6 +19,20 @@ pub const MAGIC_NUM: u32 = 0xC0A1BA11;
 // 192, 161, 186, 17
 /// Framehead has constant size: (frame length) + (magic) + (frame type)
 pub const FRAME_HEAD_LEN: u32 = 4 + 4 + 1;
+/// The version of the wire protocol spoken by this host, sent in Hello and Start frames
+pub const PROTOCOL_VERSION: u16 = 1;
+/// The oldest version of the wire protocol this host can still communicate with
+pub const MIN_PROTOCOL_VERSION: u16 = LEGACY_PROTOCOL_VERSION;
+/// The version of the wire protocol spoken by hosts predating protocol versioning
+///
+/// These hosts support no optional features, and neither credit-based flow control nor heartbeats.
+pub const LEGACY_PROTOCOL_VERSION: u16 = 0;
+/// Length of the protocol fields of Hello and Start frames: version + capabilities + incarnation
+const PROTOCOL_LEN: usize = 2 + 4 + 16;
+/// Length of the random challenges exchanged in Hello and Start frames to authenticate channels
+pub const AUTH_NONCE_LEN: usize = 16;
+/// Length of the HMACs answering authentication challenges in Start and Ack frames
+pub const AUTH_MAC_LEN: usize = 32;
 
 /// Error messages for encoding/decoding
 #[derive(Debug)]
@@ -53,6 +70,8 @@ pub enum Frame {
     CreditUpdate(CreditUpdate),
     /// Frame of Data
     Data(Data),
+    /// Frame of Data, whose payload is compressed with LZ4
+    CompressedData(Data),
     /// Hello, used to initiate network channels
     Hello(Hello),
     /// Start, used to initiate network channels
@@ -60,7 +79,17 @@ pub enum Frame {
     /// Ack to acknowledge that the connection is started.
     Ack(Ack),
     /// Bye to signal that a channel is closing.
-    Bye(),
+    Bye(Bye),
+    /// Heartbeat, which the remote host must answer with a HeartbeatAck.
+    Heartbeat(),
+    /// HeartbeatAck, answers a Heartbeat to signal that the remote host is alive.
+    HeartbeatAck(),
+    /// Fragment of a frame which is too large to be sent in a single UDP datagram
+    Fragment(Fragment),
+    /// Frame of Data sent over reliable UDP, which the receiver must acknowledge
+    ReliableData(ReliableData),
+    /// Acknowledges the ReliableData frames received from a remote host
+    ReliableAck(ReliableAck),
 }
 
 impl Frame {
@@ -70,10 +99,16 @@ impl Frame {
             Frame::StreamRequest(_) => FrameType::StreamRequest,
             Frame::CreditUpdate(_) => FrameType::CreditUpdate,
             Frame::Data(_) => FrameType::Data,
+            Frame::CompressedData(_) => FrameType::CompressedData,
             Frame::Hello(_) => FrameType::Hello,
             Frame::Start(_) => FrameType::Start,
             Frame::Ack(_) => FrameType::Ack,
-            Frame::Bye() => FrameType::Bye,
+            Frame::Bye(_) => FrameType::Bye,
+            Frame::Heartbeat() => FrameType::Heartbeat,
+            Frame::HeartbeatAck() => FrameType::HeartbeatAck,
+            Frame::Fragment(_) => FrameType::Fragment,
+            Frame::ReliableData(_) => FrameType::ReliableData,
+            Frame::ReliableAck(_) => FrameType::ReliableAck,
         }
     }
 
@@ -95,11 +130,15 @@ impl Frame {
         match self {
             Frame::StreamRequest(frame) => frame.encode_into(dst),
             Frame::CreditUpdate(frame) => frame.encode_into(dst),
-            Frame::Data(frame) => frame.encode_into(dst),
+            Frame::Data(frame) | Frame::CompressedData(frame) => frame.encode_into(dst),
             Frame::Hello(frame) => frame.encode_into(dst),
             Frame::Start(frame) => frame.encode_into(dst),
             Frame::Ack(frame) => frame.encode_into(dst),
-            Frame::Bye() => Ok(()),
+            Frame::Fragment(frame) => frame.encode_into(dst),
+            Frame::ReliableData(frame) => frame.encode_into(dst),
+            Frame::ReliableAck(frame) => frame.encode_into(dst),
+            Frame::Bye(frame) => frame.encode_into(dst),
+            Frame::Heartbeat() | Frame::HeartbeatAck() => Ok(()),
         }
     }
 
@@ -108,10 +147,14 @@ impl Frame {
         match *self {
             Frame::StreamRequest(ref frame) => frame.encoded_len(),
             Frame::CreditUpdate(ref frame) => frame.encoded_len(),
-            Frame::Data(ref frame) => frame.encoded_len(),
+            Frame::Data(ref frame) | Frame::CompressedData(ref frame) => frame.encoded_len(),
             Frame::Hello(ref frame) => frame.encoded_len(),
             Frame::Start(ref frame) => frame.encoded_len(),
             Frame::Ack(ref frame) => frame.encoded_len(),
+            Frame::Fragment(ref frame) => frame.encoded_len(),
+            Frame::ReliableData(ref frame) => frame.encoded_len(),
+            Frame::ReliableAck(ref frame) => frame.encoded_len(),
+            Frame::Bye(ref frame) => frame.encoded_len(),
             _ => 0,
         }
     }
@@ -145,11 +188,61 @@ pub struct Data {
     pub payload: ChunkLease,
 }
 
+/// Fragment of a frame which is too large to be sent in a single UDP datagram
+///
+/// The receiver reassembles the original frame once all `count` fragments
+/// with the same `message_id` have arrived.
+#[derive(Debug)]
+pub struct Fragment {
+    /// Identifies the fragmented frame among those sent by the same host
+    pub message_id: u32,
+    /// The position of this fragment within the frame
+    pub index: u16,
+    /// The number of fragments the frame was split into
+    pub count: u16,
+    /// The bytes of the frame carried by this fragment
+    pub payload: ChunkLease,
+}
+
+/// Frame of Data sent over reliable UDP, which the receiver must acknowledge
+///
+/// The receiver delivers the messages of a session in the order of their sequence numbers.
+#[derive(Debug)]
+pub struct ReliableData {
+    /// Identifies the sender's session, which starts over at sequence number 0
+    pub session: u32,
+    /// The position of this message among those sent in the session
+    pub seq: u64,
+    /// The contents of the Frame
+    pub payload: ChunkLease,
+}
+
+/// Acknowledges the ReliableData frames received from a remote host
+#[derive(Debug)]
+pub struct ReliableAck {
+    /// The session of the acknowledged frames
+    pub session: u32,
+    /// The sequence number of the next frame expected, all earlier ones have been received
+    pub next: u64,
+    /// Bit `i` is set if the frame with sequence number `next + 1 + i` has been received as well
+    pub received: u64,
+}
+
 /// Hello, used to initiate network channels
 #[derive(Debug)]
 pub struct Hello {
     /// The Cannonical Address of the host saying Hello
     pub addr: SocketAddr,
+    /// The wire protocol version of the host saying Hello, 0 if it predates versioning
+    pub version: u16,
+    /// The optional features supported by the host saying Hello
+    pub capabilities: Capabilities,
+    /// The incarnation of the system saying Hello, nil if it predates protocol versioning
+    pub incarnation: Uuid,
+    /// The number of lanes the host saying Hello is willing to accept, 1 if it does not support lanes
+    pub lanes: u8,
+    /// A random challenge, which the receiver must answer in its Start to authenticate itself
+    pub challenge: Option<[u8; AUTH_NONCE_LEN]>,
 }
 
 /// Hello, used to initiate network channels
@@ -159,6 +252,21 @@ pub struct Start {
     pub addr: SocketAddr,
     /// "Channel ID", used as a tie-breaker in mutual connection requests
     pub id: Uuid,
+    /// The wire protocol version of the host sending the Start message, 0 if it predates versioning
+    pub version: u16,
+    /// The optional features supported by the host sending the Start message
+    pub capabilities: Capabilities,
+    /// The incarnation of the system sending the Start message, nil if it predates protocol versioning
+    pub incarnation: Uuid,
+    /// The number of lanes the host sending the Start message is going to open, 1 if it does not support lanes
+    pub lanes: u8,
+    /// The lane this channel is, 0 for the first channel to the host, which opens the others
+    pub lane: u8,
+    /// A random challenge for the Ack, and the answer to the challenge of the Hello
+    pub auth: Option<([u8; AUTH_NONCE_LEN], [u8; AUTH_MAC_LEN])>,
+    /// The Unix domain socket of the host sending the Start message,
+    /// if it requested the channel over a Unix domain socket and listens on one itself
+    pub unix_path: Option<PathBuf>,
 }
 
 /// Hello, used to initiate network channels
@@ -166,6 +274,99 @@ pub struct Start {
 pub struct Ack {
     /// Ack where we're ready to start receiving from.
     pub offset: u128,
+    /// The answer to the challenge of the Start
+    pub response: Option<[u8; AUTH_MAC_LEN]>,
+}
+
+/// Bye to signal that a channel is closing, and why
+#[derive(Debug)]
+pub struct Bye {
+    /// Why the host saying Bye closes the channel
+    pub reason: ByeReason,
+}
+
+/// Why a host closes a channel with a Bye
+///
+/// Hosts which do not support [giving the reason](Capabilities::BYE_REASON) always leave.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByeReason {
+    /// The host leaves the remote host, e.g. as it shuts down, and does not want to be reconnected to
+    Left,
+    /// The host closes the channel, as no data was exchanged on it for its idle timeout
+    Idle,
+}
+
+/// Optional features of the wire protocol, advertised in Hello and Start frames
+///
+/// A feature may only be used on a channel if both hosts support it,
+/// i.e. it is contained in the [intersection](Capabilities::intersection) of both advertised sets.
+/// Unknown features advertised by newer hosts are thus ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Giving the reason for closing a channel in Bye frames
+    ///
+    /// Without it, a Bye always means that the host left.
+    pub const BYE_REASON: Capabilities = Capabilities(0x08);
+    /// Multiple channels, called lanes, to the same host
+    ///
+    /// Hosts supporting lanes announce their lanes in Hello and Start frames.
+    pub const LANES: Capabilities = Capabilities(0x02);
+    /// Receiving LZ4 compressed data frames
+    pub const LZ4: Capabilities = Capabilities(0x01);
+    /// No optional features
+    pub const NONE: Capabilities = Capabilities(0);
+    /// Identifying channels over Unix domain sockets by the socket of the requesting host
+    ///
+    /// Hosts supporting this announce their Unix domain socket in Start frames, if any.
+    pub const UNIX_PATH: Capabilities = Capabilities(0x04);
+
+    /// All optional features supported by this host
+    pub fn supported() -> Self {
+        Capabilities::LZ4 | Capabilities::LANES | Capabilities::UNIX_PATH | Capabilities::BYE_REASON
+    }
+
+    /// Creates a set of capabilities from its wire representation, keeping unknown features
+    pub fn from_bits(bits: u32) -> Self {
+        Capabilities(bits)
+    }
+
+    /// Returns the wire representation of this set of capabilities
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if all features in `other` are contained in this set
+    pub fn contains(self, other: Capabilities) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Returns the features contained in both this set and `other`
+    pub fn intersection(self, other: Capabilities) -> Self {
+        Capabilities(self.0 & other.0)
+    }
+}
+
+impl std::ops::BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, rhs: Capabilities) -> Self::Output {
+        Capabilities(self.0 | rhs.0)
+    }
+}
+
+/// Returns the protocol version to use with a host speaking `remote_version`,
+/// or `None` if it is not compatible with this host.
+///
+/// Both hosts use the older of their versions, if the other host still supports it.
+pub fn negotiate_version(remote_version: u16) -> Option<u16> {
+    let version = std::cmp::min(PROTOCOL_VERSION, remote_version);
+    if (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        Some(version)
+    } else {
+        None
+    }
 }
 
 /// Byte-mappings for frame types
@@ -188,6 +389,18 @@ pub enum FrameType {
     Bye = 0x07,
     /// Unknown frame type
     Unknown = 0x08,
+    /// Heartbeat, which the remote host must answer with a HeartbeatAck.
+    Heartbeat = 0x09,
+    /// HeartbeatAck, answers a Heartbeat to signal that the remote host is alive.
+    HeartbeatAck = 0x0A,
+    /// Frame of Data, whose payload is compressed with LZ4
+    CompressedData = 0x0B,
+    /// Fragment of a frame which is too large to be sent in a single UDP datagram
+    Fragment = 0x0C,
+    /// Frame of Data sent over reliable UDP, which the receiver must acknowledge
+    ReliableData = 0x0D,
+    /// Acknowledges the ReliableData frames received from a remote host
+    ReliableAck = 0x0E,
 }
 
 impl From<u8> for FrameType {
@@ -200,6 +413,12 @@ impl From<u8> for FrameType {
             0x05 => FrameType::Start,
             0x06 => FrameType::Ack,
             0x07 => FrameType::Bye,
+            0x09 => FrameType::Heartbeat,
+            0x0A => FrameType::HeartbeatAck,
+            0x0B => FrameType::CompressedData,
+            0x0C => FrameType::Fragment,
+            0x0D => FrameType::ReliableData,
+            0x0E => FrameType::ReliableAck,
             _ => FrameType::Unknown,
         }
     }
@@ -267,16 +486,60 @@ impl FrameHead {
 }
 
 impl StreamRequest {
-    #[allow(dead_code)]
-    pub(crate) fn new(credit_capacity: u32) -> Self {
+    /// Create a new request for `credit_capacity` credits
+    pub fn new(credit_capacity: u32) -> Self {
         StreamRequest { credit_capacity }
     }
 }
 
+impl CreditUpdate {
+    /// Create a new update granting `credit` credits
+    pub fn new(credit: u32) -> Self {
+        CreditUpdate { credit }
+    }
+}
+
 impl Hello {
     /// Create a new hello message
     pub fn new(addr: SocketAddr) -> Self {
-        Hello { addr }
+        Hello {
+            addr,
+            version: PROTOCOL_VERSION,
+            capabilities: Capabilities::NONE,
+            incarnation: Uuid::nil(),
+            lanes: 1,
+            challenge: None,
+        }
+    }
+
+    /// Advertise the optional features supported by this host
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Announce a different protocol version than [PROTOCOL_VERSION](PROTOCOL_VERSION)
+    pub fn with_version(mut self, version: u16) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Announce the incarnation of this system
+    pub fn with_incarnation(mut self, incarnation: Uuid) -> Self {
+        self.incarnation = incarnation;
+        self
+    }
+
+    /// Announce the number of lanes this host is willing to accept
+    pub fn with_lanes(mut self, lanes: u8) -> Self {
+        self.lanes = lanes;
+        self
+    }
+
+    /// Challenge the receiver to authenticate itself
+    pub fn with_challenge(mut self, challenge: Option<[u8; AUTH_NONCE_LEN]>) -> Self {
+        self.challenge = challenge;
+        self
     }
 
     /// Get the address sent in the Hello message
@@ -288,7 +551,59 @@ impl Hello {
 impl Start {
     /// Create a new hello message
     pub fn new(addr: SocketAddr, id: Uuid) -> Self {
-        Start { addr, id }
+        Start {
+            addr,
+            id,
+            version: PROTOCOL_VERSION,
+            capabilities: Capabilities::NONE,
+            incarnation: Uuid::nil(),
+            lanes: 1,
+            lane: 0,
+            auth: None,
+            unix_path: None,
+        }
+    }
+
+    /// Advertise the optional features supported by this host
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Announce a different protocol version than [PROTOCOL_VERSION](PROTOCOL_VERSION)
+    pub fn with_version(mut self, version: u16) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Announce the incarnation of this system
+    pub fn with_incarnation(mut self, incarnation: Uuid) -> Self {
+        self.incarnation = incarnation;
+        self
+    }
+
+    /// Announce the number of lanes this host is going to open
+    pub fn with_lanes(mut self, lanes: u8) -> Self {
+        self.lanes = lanes;
+        self
+    }
+
+    /// Announce which lane this channel is
+    pub fn with_lane(mut self, lane: u8) -> Self {
+        self.lane = lane;
+        self
+    }
+
+    /// Answer the challenge of the Hello and challenge the receiver in turn
+    pub fn with_auth(mut self, auth: Option<([u8; AUTH_NONCE_LEN], [u8; AUTH_MAC_LEN])>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Announce the Unix domain socket this host listens on
+    pub fn with_unix_path(mut self, unix_path: Option<PathBuf>) -> Self {
+        self.unix_path = unix_path;
+        self
     }
 
     /// Get the address sent in the Start message
@@ -343,9 +658,136 @@ impl FrameExt for Data {
     }
 }
 
+impl Fragment {
+    /// Length of the fields preceding the payload of a fragment
+    pub(crate) const HEADER_LEN: usize = 4 + 2 + 2;
+
+    /// Encodes the fields preceding the payload of a fragment into `dst`
+    pub(crate) fn encode_header<B: BufMut>(message_id: u32, index: u16, count: u16, dst: &mut B) {
+        dst.put_u32(message_id);
+        dst.put_u16(index);
+        dst.put_u16(count);
+    }
+}
+
+impl FrameExt for Fragment {
+    fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
+        if src.remaining() < Fragment::HEADER_LEN {
+            return Err(FramingError::InvalidFrame);
+        }
+        let message_id = src.get_u32();
+        let index = src.get_u16();
+        let count = src.get_u16();
+        if index >= count {
+            return Err(FramingError::InvalidFrame);
+        }
+        Ok(Frame::Fragment(Fragment {
+            message_id,
+            index,
+            count,
+            payload: src,
+        }))
+    }
+
+    fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
+        assert!(dst.remaining_mut() >= self.encoded_len());
+        Fragment::encode_header(self.message_id, self.index, self.count, dst);
+        while self.payload.has_remaining() {
+            let len = self.payload.bytes().len();
+            dst.put_slice(self.payload.bytes());
+            self.payload.advance(len);
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        Fragment::HEADER_LEN + self.payload.remaining()
+    }
+}
+
+impl ReliableData {
+    /// Length of the fields preceding the payload of a reliable data frame
+    pub(crate) const HEADER_LEN: usize = 4 + 8;
+
+    /// Encodes the fields preceding the payload of a reliable data frame into `dst`
+    pub(crate) fn encode_header<B: BufMut>(session: u32, seq: u64, dst: &mut B) {
+        dst.put_u32(session);
+        dst.put_u64(seq);
+    }
+}
+
+impl FrameExt for ReliableData {
+    fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
+        if src.remaining() < ReliableData::HEADER_LEN {
+            return Err(FramingError::InvalidFrame);
+        }
+        let session = src.get_u32();
+        let seq = src.get_u64();
+        Ok(Frame::ReliableData(ReliableData {
+            session,
+            seq,
+            payload: src,
+        }))
+    }
+
+    fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
+        assert!(dst.remaining_mut() >= self.encoded_len());
+        ReliableData::encode_header(self.session, self.seq, dst);
+        while self.payload.has_remaining() {
+            let len = self.payload.bytes().len();
+            dst.put_slice(self.payload.bytes());
+            self.payload.advance(len);
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        ReliableData::HEADER_LEN + self.payload.remaining()
+    }
+}
+
+impl ReliableAck {
+    /// Create a new acknowledgement of the frames received in `session`
+    pub fn new(session: u32, next: u64, received: u64) -> Self {
+        ReliableAck {
+            session,
+            next,
+            received,
+        }
+    }
+}
+
+impl FrameExt for ReliableAck {
+    fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
+        if src.remaining() < 20 {
+            return Err(FramingError::InvalidFrame);
+        }
+        let session = src.get_u32();
+        let next = src.get_u64();
+        let received = src.get_u64();
+        Ok(Frame::ReliableAck(ReliableAck {
+            session,
+            next,
+            received,
+        }))
+    }
+
+    fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
+        assert!(dst.remaining_mut() >= self.encoded_len());
+        dst.put_u32(self.session);
+        dst.put_u64(self.next);
+        dst.put_u64(self.received);
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        4 + 8 + 8 // session + next + received
+    }
+}
+
 impl FrameExt for StreamRequest {
     fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
-        if src.remaining() < 8 {
+        if src.remaining() < 4 {
             return Err(FramingError::InvalidFrame);
         }
         //let stream_id: StreamId = src.get_u32_be().into();
@@ -370,23 +812,18 @@ impl FrameExt for StreamRequest {
 
 impl FrameExt for Hello {
     fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
-        match src.get_u8() {
-            4 => {
-                let ip = Ipv4Addr::from(src.get_u32());
-                let port = src.get_u16();
-                let addr = SocketAddr::new(IpAddr::V4(ip), port);
-                Ok(Frame::Hello(Hello::new(addr)))
-            }
-            6 => {
-                let ip = Ipv6Addr::from(src.get_u128());
-                let port = src.get_u16();
-                let addr = SocketAddr::new(IpAddr::V6(ip), port);
-                Ok(Frame::Hello(Hello::new(addr)))
-            }
-            _ => {
-                panic!("Faulty Hello Message!");
-            }
-        }
+        let addr = decode_addr(&mut src)?;
+        let (version, capabilities, incarnation) = decode_protocol(&mut src);
+        let lanes = decode_lanes(&mut src, capabilities).map_or(1, |[lanes]| lanes);
+        let challenge = decode_array(&mut src);
+        Ok(Frame::Hello(
+            Hello::new(addr)
+                .with_version(version)
+                .with_capabilities(capabilities)
+                .with_incarnation(incarnation)
+                .with_lanes(lanes)
+                .with_challenge(challenge),
+        ))
     }
 
     fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
@@ -395,24 +832,40 @@ impl FrameExt for Hello {
                 dst.put_u8(4); // version
                 dst.put_slice(&v4.ip().octets()); // ip
                 dst.put_u16(v4.port()); // port
+                encode_protocol(dst, self.version, self.capabilities, self.incarnation);
+                if self.capabilities.contains(Capabilities::LANES) {
+                    dst.put_u8(self.lanes); // lanes
+                }
+                if let Some(challenge) = self.challenge {
+                    dst.put_slice(&challenge);
+                }
                 Ok(())
             }
             SocketAddr::V6(v6) => {
                 dst.put_u8(6); // version
                 dst.put_slice(&v6.ip().octets()); // ip
                 dst.put_u16(v6.port()); // port
+                encode_protocol(dst, self.version, self.capabilities, self.incarnation);
+                if self.capabilities.contains(Capabilities::LANES) {
+                    dst.put_u8(self.lanes); // lanes
+                }
+                if let Some(challenge) = self.challenge {
+                    dst.put_slice(&challenge);
+                }
                 Ok(())
             }
         }
     }
 
     fn encoded_len(&self) -> usize {
+        let lanes = lanes_len(self.capabilities, 1);
+        let challenge = self.challenge.map_or(0, |_| AUTH_NONCE_LEN);
         match self.addr {
             SocketAddr::V4(_v4) => {
-                1 + 4 + 2 // version + ip + port
+                1 + 4 + 2 + PROTOCOL_LEN + lanes + challenge // ip version + ip + port + protocol + lanes + challenge
             }
             SocketAddr::V6(_v6) => {
-                1 + 16 + 2 // version + ip + port
+                1 + 16 + 2 + PROTOCOL_LEN + lanes + challenge // ip version + ip + port + protocol + lanes + challenge
             }
         }
     }
@@ -420,25 +873,26 @@ impl FrameExt for Hello {
 
 impl FrameExt for Start {
     fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
-        match src.get_u8() {
-            4 => {
-                let ip = Ipv4Addr::from(src.get_u32());
-                let port = src.get_u16();
-                let addr = SocketAddr::new(IpAddr::V4(ip), port);
-                let uuid = Uuid::from_u128(src.get_u128());
-                Ok(Frame::Start(Start::new(addr, uuid)))
-            }
-            6 => {
-                let ip = Ipv6Addr::from(src.get_u128());
-                let port = src.get_u16();
-                let addr = SocketAddr::new(IpAddr::V6(ip), port);
-                let uuid = Uuid::from_u128(src.get_u128());
-                Ok(Frame::Start(Start::new(addr, uuid)))
-            }
-            _ => {
-                panic!("Faulty Hello Message!");
-            }
+        let addr = decode_addr(&mut src)?;
+        if src.remaining() < 16 {
+            return Err(FramingError::InvalidFrame);
         }
+        let uuid = Uuid::from_u128(src.get_u128());
+        let (version, capabilities, incarnation) = decode_protocol(&mut src);
+        let (lanes, lane) =
+            decode_lanes(&mut src, capabilities).map_or((1, 0), |[lanes, lane]| (lanes, lane));
+        let unix_path = decode_unix_path(&mut src, capabilities)?;
+        let auth = decode_array(&mut src).zip(decode_array(&mut src));
+        Ok(Frame::Start(
+            Start::new(addr, uuid)
+                .with_version(version)
+                .with_capabilities(capabilities)
+                .with_incarnation(incarnation)
+                .with_lanes(lanes)
+                .with_lane(lane)
+                .with_auth(auth)
+                .with_unix_path(unix_path),
+        ))
     }
 
     fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
@@ -448,6 +902,16 @@ impl FrameExt for Start {
                 dst.put_slice(&v4.ip().octets()); // ip
                 dst.put_u16(v4.port()); // port
                 dst.put_u128(self.id.as_u128()); //id
+                encode_protocol(dst, self.version, self.capabilities, self.incarnation);
+                if self.capabilities.contains(Capabilities::LANES) {
+                    dst.put_u8(self.lanes); // lanes
+                    dst.put_u8(self.lane); // lane
+                }
+                encode_unix_path(dst, self.capabilities, &self.unix_path);
+                if let Some((nonce, response)) = self.auth {
+                    dst.put_slice(&nonce);
+                    dst.put_slice(&response);
+                }
                 Ok(())
             }
             SocketAddr::V6(v6) => {
@@ -455,47 +919,246 @@ impl FrameExt for Start {
                 dst.put_slice(&v6.ip().octets()); // ip
                 dst.put_u16(v6.port()); // port
                 dst.put_u128(self.id.as_u128()); //id
+                encode_protocol(dst, self.version, self.capabilities, self.incarnation);
+                if self.capabilities.contains(Capabilities::LANES) {
+                    dst.put_u8(self.lanes); // lanes
+                    dst.put_u8(self.lane); // lane
+                }
+                encode_unix_path(dst, self.capabilities, &self.unix_path);
+                if let Some((nonce, response)) = self.auth {
+                    dst.put_slice(&nonce);
+                    dst.put_slice(&response);
+                }
                 Ok(())
             }
         }
     }
 
     fn encoded_len(&self) -> usize {
+        let lanes =
+            lanes_len(self.capabilities, 2) + unix_path_len(self.capabilities, &self.unix_path);
+        let auth = self.auth.map_or(0, |_| AUTH_NONCE_LEN + AUTH_MAC_LEN);
         match self.addr {
             SocketAddr::V4(_v4) => {
-                1 + 4 + 2 + 16 // version + ip + port + uuid
+                1 + 4 + 2 + 16 + PROTOCOL_LEN + lanes + auth // ip version + ip + port + uuid + protocol + lanes + unix path + auth
             }
             SocketAddr::V6(_v6) => {
-                1 + 16 + 2 + 16 // version + ip + port + uuid
+                1 + 16 + 2 + 16 + PROTOCOL_LEN + lanes + auth // ip version + ip + port + uuid + protocol + lanes + unix path + auth
             }
         }
     }
 }
 
+/// Decodes the address of Hello and Start frames
+fn decode_addr(src: &mut ChunkLease) -> Result<SocketAddr, FramingError> {
+    if !src.has_remaining() {
+        return Err(FramingError::InvalidFrame);
+    }
+    match src.get_u8() {
+        4 if src.remaining() >= 4 + 2 => {
+            let ip = Ipv4Addr::from(src.get_u32());
+            let port = src.get_u16();
+            Ok(SocketAddr::new(IpAddr::V4(ip), port))
+        }
+        6 if src.remaining() >= 16 + 2 => {
+            let ip = Ipv6Addr::from(src.get_u128());
+            let port = src.get_u16();
+            Ok(SocketAddr::new(IpAddr::V6(ip), port))
+        }
+        _ => Err(FramingError::InvalidFrame),
+    }
+}
+
+/// Encodes the protocol version, capabilities and incarnation of Hello and Start frames
+///
+/// They are always sent together, right after the fields understood by hosts predating protocol versioning.
+fn encode_protocol<B: BufMut>(
+    dst: &mut B,
+    version: u16,
+    capabilities: Capabilities,
+    incarnation: Uuid,
+) {
+    dst.put_u16(version); // protocol version
+    dst.put_u32(capabilities.bits()); // capabilities
+    dst.put_u128(incarnation.as_u128()); // incarnation
+}
+
+/// Decodes the protocol version, capabilities and incarnation of Hello and Start frames
+///
+/// Hosts predating protocol versioning send none of them, and are reported as
+/// [LEGACY_PROTOCOL_VERSION](LEGACY_PROTOCOL_VERSION) without capabilities and with a nil incarnation.
+fn decode_protocol(src: &mut ChunkLease) -> (u16, Capabilities, Uuid) {
+    if src.remaining() < PROTOCOL_LEN {
+        return (LEGACY_PROTOCOL_VERSION, Capabilities::NONE, Uuid::nil());
+    }
+    let version = src.get_u16();
+    let capabilities = Capabilities::from_bits(src.get_u32());
+    let incarnation = Uuid::from_u128(src.get_u128());
+    (version, capabilities, incarnation)
+}
+
+/// Decodes the lane fields of Hello and Start frames
+///
+/// Only hosts supporting [lanes](Capabilities::LANES) send them.
+fn decode_lanes<const N: usize>(
+    src: &mut ChunkLease,
+    capabilities: Capabilities,
+) -> Option<[u8; N]> {
+    if capabilities.contains(Capabilities::LANES) {
+        decode_array(src)
+    } else {
+        None
+    }
+}
+
+/// The length of the `fields` lane fields of Hello and Start frames, if they are sent at all
+fn lanes_len(capabilities: Capabilities, fields: usize) -> usize {
+    if capabilities.contains(Capabilities::LANES) {
+        fields
+    } else {
+        0
+    }
+}
+
+/// Encodes the Unix domain socket of Start frames, as a length-prefixed UTF-8 string which is empty if there is none.
+///
+/// It is only sent to hosts which support [UNIX_PATH](Capabilities::UNIX_PATH).
+fn encode_unix_path<B: BufMut>(
+    dst: &mut B,
+    capabilities: Capabilities,
+    unix_path: &Option<PathBuf>,
+) {
+    if capabilities.contains(Capabilities::UNIX_PATH) {
+        let path = unix_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        dst.put_u16(path.len() as u16);
+        dst.put_slice(path.as_bytes());
+    }
+}
+
+/// Decodes the Unix domain socket of Start frames, if the sending host supports announcing one
+fn decode_unix_path(
+    src: &mut ChunkLease,
+    capabilities: Capabilities,
+) -> Result<Option<PathBuf>, FramingError> {
+    if !capabilities.contains(Capabilities::UNIX_PATH) {
+        return Ok(None);
+    }
+    if src.remaining() < 2 {
+        return Err(FramingError::InvalidFrame);
+    }
+    let len = src.get_u16() as usize;
+    if src.remaining() < len {
+        return Err(FramingError::InvalidFrame);
+    }
+    let mut path = vec![0u8; len];
+    src.copy_to_slice(&mut path);
+    match String::from_utf8(path) {
+        Ok(path) if path.is_empty() => Ok(None),
+        Ok(path) => Ok(Some(PathBuf::from(path))),
+        Err(_) => Err(FramingError::InvalidFrame),
+    }
+}
+
+/// The length of the Unix domain socket field of Start frames, if it is sent at all
+fn unix_path_len(capabilities: Capabilities, unix_path: &Option<PathBuf>) -> usize {
+    if capabilities.contains(Capabilities::UNIX_PATH) {
+        2 + unix_path
+            .as_ref()
+            .map_or(0, |path| path.to_string_lossy().len())
+    } else {
+        0
+    }
+}
+
+/// Decodes an optional trailing field of handshake frames
+///
+/// Hosts without authentication do not send challenges or responses.
+fn decode_array<const N: usize>(src: &mut ChunkLease) -> Option<[u8; N]> {
+    if src.remaining() < N {
+        return None;
+    }
+    let mut array = [0u8; N];
+    src.copy_to_slice(&mut array);
+    Some(array)
+}
+
 impl FrameExt for Ack {
     fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
-        Ok(Frame::Ack(Ack {
-            offset: src.get_u128(),
-        }))
+        if src.remaining() < 16 {
+            return Err(FramingError::InvalidFrame);
+        }
+        let offset = src.get_u128();
+        let response = decode_array(&mut src);
+        Ok(Frame::Ack(Ack { offset, response }))
     }
 
     fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
         dst.put_u128(self.offset);
+        if let Some(response) = self.response {
+            dst.put_slice(&response);
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        16 + self.response.map_or(0, |_| AUTH_MAC_LEN) // offset + response
+    }
+}
+
+impl Bye {
+    /// Creates a new Bye, closing the channel for `reason`
+    pub fn new(reason: ByeReason) -> Self {
+        Bye { reason }
+    }
+}
+
+impl FrameExt for Bye {
+    // A Bye without content is decoded along with its head, so this one gives a reason
+    fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
+        if src.remaining() < 1 {
+            return Err(FramingError::InvalidFrame);
+        }
+        // Unknown reasons given by newer hosts are treated as leaving
+        let reason = match src.get_u8() {
+            0x01 => ByeReason::Idle,
+            _ => ByeReason::Left,
+        };
+        Ok(Frame::Bye(Bye { reason }))
+    }
+
+    // Leaving is encoded without content, such that hosts which do not support reasons understand it
+    fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
+        assert!(dst.remaining_mut() >= self.encoded_len());
+        if let ByeReason::Idle = self.reason {
+            dst.put_u8(0x01);
+        }
         Ok(())
     }
 
     fn encoded_len(&self) -> usize {
-        16
+        match self.reason {
+            ByeReason::Left => 0,
+            ByeReason::Idle => 1, // reason
+        }
     }
 }
 
 impl FrameExt for CreditUpdate {
-    fn decode_from(_src: ChunkLease) -> Result<Frame, FramingError> {
-        unimplemented!()
+    fn decode_from(mut src: ChunkLease) -> Result<Frame, FramingError> {
+        if src.remaining() < 4 {
+            return Err(FramingError::InvalidFrame);
+        }
+        let credit = src.get_u32();
+        Ok(Frame::CreditUpdate(CreditUpdate { credit }))
     }
 
-    fn encode_into<B: BufMut>(&mut self, _dst: &mut B) -> Result<(), FramingError> {
-        unimplemented!()
+    fn encode_into<B: BufMut>(&mut self, dst: &mut B) -> Result<(), FramingError> {
+        assert!(dst.remaining_mut() >= self.encoded_len());
+        dst.put_u32(self.credit);
+        Ok(())
     }
 
     fn encoded_len(&self) -> usize {
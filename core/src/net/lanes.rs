@@ -0,0 +1,178 @@
+//! Multiple TCP channels, called lanes, to the same remote host
+//!
+//! The host which requested the first channel to a remote host opens the additional lanes
+//! once it has been acknowledged, announcing the lane in their [Start](Start) frames.
+//! Both hosts then send each message on the lane picked by [lane_of](lane_of),
+//! such that messages between the same pair of actors are never reordered.
+
+use super::*;
+use crate::messaging::{framing::visit_serialised_path, SerialisedFrame};
+use std::collections::VecDeque;
+
+/// The lanes to a remote host, tracked by the network thread owning its first channel
+pub(crate) struct Lanes {
+    /// The keys of the lanes in the channel map, `None` for lanes which have not been started yet
+    channels: Vec<Option<SocketAddr>>,
+    /// Data frames for lanes which have not been started yet
+    pending: Vec<VecDeque<SerialisedFrame>>,
+}
+
+impl Lanes {
+    /// Creates `lanes` lanes to a remote host, of which only the first one, with key `addr`, is started
+    pub fn new(addr: SocketAddr, lanes: u8) -> Self {
+        let lanes = lanes as usize;
+        let mut channels = vec![None; lanes];
+        channels[0] = Some(addr);
+        Lanes {
+            channels,
+            pending: (0..lanes).map(|_| VecDeque::new()).collect(),
+        }
+    }
+
+    /// Returns the number of lanes to the remote host
+    pub fn count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns the key of the channel of `lane`, if it has been started
+    pub fn channel(&self, lane: usize) -> Option<SocketAddr> {
+        self.channels[lane]
+    }
+
+    /// Queues `frame` until `lane` is started
+    pub fn enqueue(&mut self, lane: usize, frame: SerialisedFrame) -> () {
+        self.pending[lane].push_back(frame);
+    }
+
+    /// Starts `lane` as the channel with `key`, returning the frames queued for it
+    pub fn start(&mut self, lane: usize, key: SocketAddr) -> VecDeque<SerialisedFrame> {
+        self.channels[lane] = Some(key);
+        std::mem::take(&mut self.pending[lane])
+    }
+
+    /// Returns the keys of the additional lanes which have been started
+    pub fn additional(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.channels[1..].iter().flatten().copied()
+    }
+
+    /// Returns `true` if frames are queued for lanes which have not been started yet
+    pub fn has_pending(&self) -> bool {
+        self.pending.iter().any(|frames| !frames.is_empty())
+    }
+
+    /// Removes all frames queued for lanes which have not been started yet
+    pub fn take_pending(&mut self) -> Vec<SerialisedFrame> {
+        self.pending
+            .iter_mut()
+            .flat_map(|queue| queue.drain(..))
+            .collect()
+    }
+}
+
+/// Returns the lane, out of `lanes`, on which the data `frame` must be sent, along with the frame itself
+///
+/// The lane is picked by hashing the serialised sender and receiver paths of the message,
+/// falling back to the first lane if they can not be read.
+pub(crate) fn lane_of(frame: SerialisedFrame, lanes: usize) -> (SerialisedFrame, usize) {
+    if lanes <= 1 {
+        return (frame, 0);
+    }
+    // Read the paths from a cheap clone, leaving the frame itself untouched
+    let (frame, mut paths) = match frame {
+        SerialisedFrame::Bytes(bytes) => {
+            let paths = SerialisedFrame::Bytes(bytes.clone());
+            (SerialisedFrame::Bytes(bytes), paths)
+        }
+        SerialisedFrame::ChunkLease(chunk) => {
+            let chunk = chunk.into_chunk_ref();
+            let paths = SerialisedFrame::ChunkRef(chunk.clone());
+            (SerialisedFrame::ChunkRef(chunk), paths)
+        }
+        SerialisedFrame::ChunkRef(chunk) => {
+            let paths = SerialisedFrame::ChunkRef(chunk.clone());
+            (SerialisedFrame::ChunkRef(chunk), paths)
+        }
+    };
+    if paths.len() < FRAME_HEAD_LEN as usize {
+        return (frame, 0);
+    }
+    let hash = match paths {
+        SerialisedFrame::Bytes(ref mut bytes) => hash_paths(bytes),
+        SerialisedFrame::ChunkLease(ref mut chunk) => hash_paths(chunk),
+        SerialisedFrame::ChunkRef(ref mut chunk) => hash_paths(chunk),
+    };
+    match hash {
+        Some(hash) => (frame, (hash % lanes as u64) as usize),
+        None => (frame, 0),
+    }
+}
+
+/// Hashes the serialised sender and receiver paths following the frame head in `buf`
+///
+/// The bytes are hashed one at a time, such that the hash does not depend on how the frame is chunked.
+fn hash_paths(buf: &mut dyn Buf) -> Option<u64> {
+    buf.advance(FRAME_HEAD_LEN as usize);
+    let mut hasher = FxHasher::default();
+    let mut visit = |byte| hasher.write_u8(byte);
+    visit_serialised_path(buf, &mut visit).ok()?; // sender
+    visit_serialised_path(buf, &mut visit).ok()?; // receiver
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actors::{NamedPath, SystemPath},
+        net::buffers::{BufferConfig, EncodeBuffer},
+        serialisation::ser_helpers::serialise_msg,
+    };
+    use bytes::Bytes;
+
+    fn path(name: &str) -> ActorPath {
+        ActorPath::Named(NamedPath::with_system(
+            SystemPath::with_socket(Transport::TCP, "127.0.0.1:4000".parse().unwrap()),
+            vec![name.into()],
+        ))
+    }
+
+    #[test]
+    fn lane_of_is_stable_per_actor_pair() {
+        let mut encode_buffer = EncodeBuffer::with_config(&BufferConfig::default(), &None);
+        let mut lane_for = |src: &ActorPath, dst: &ActorPath, msg: u64| {
+            let frame = serialise_msg(src, dst, &msg, &mut encode_buffer.get_buffer_encoder())
+                .expect("serialise");
+            lane_of(SerialisedFrame::ChunkLease(frame), 8).1
+        };
+        let dst = path("dst");
+        let lanes: Vec<usize> = (0..16)
+            .map(|i| path(&format!("src{}", i)))
+            .map(|src| {
+                let first = lane_for(&src, &dst, 0);
+                assert_eq!(lane_for(&src, &dst, 1), first);
+                assert_eq!(lane_for(&src, &dst, u64::MAX), first);
+                first
+            })
+            .collect();
+        assert!(lanes.iter().all(|&lane| lane < 8));
+        // The pairs do not all end up on the same lane
+        assert!(lanes.iter().any(|&lane| lane != lanes[0]));
+    }
+
+    #[test]
+    fn lanes_queue_frames_until_started() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let key: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let mut lanes = Lanes::new(addr, 3);
+        assert_eq!(lanes.count(), 3);
+        assert_eq!(lanes.channel(0), Some(addr));
+        assert_eq!(lanes.channel(2), None);
+        lanes.enqueue(2, SerialisedFrame::Bytes(Bytes::from_static(b"first")));
+        lanes.enqueue(1, SerialisedFrame::Bytes(Bytes::from_static(b"other")));
+        let started = lanes.start(2, key);
+        assert_eq!(started.len(), 1);
+        assert_eq!(lanes.channel(2), Some(key));
+        assert_eq!(lanes.additional().collect::<Vec<_>>(), vec![key]);
+        assert_eq!(lanes.take_pending().len(), 1);
+    }
+}
@@ -0,0 +1,371 @@
+//! An in-process transport for connecting systems without sockets
+
+use super::*;
+use crate::{
+    messaging::{DispatchEnvelope, EventEnvelope},
+    serialisation::ser_helpers::deserialise_frame,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{fmt, sync::Mutex};
+use uuid::Uuid;
+
+/// A network of [KompactSystems](crate::prelude::KompactSystem) in the same process,
+/// which exchange messages through shared queues instead of sockets
+///
+/// Systems join the network by setting it on their [NetworkConfig](NetworkConfig::set_memory_network).
+/// Each system is bound to a virtual socket address, which is only meaningful within the network.
+/// If the configured address has port 0, a free port is assigned, like for a real socket.
+///
+/// Messages are still serialised and connections still go through the usual
+/// [ConnectionState](ConnectionState) transitions, including retries and queuing,
+/// with FIFO ordering between each pair of systems.
+/// When a system shuts down, its connections are closed on all other systems.
+/// Like frames read by a network thread, received messages are delivered straight to the actors
+/// of the receiving system, without passing through its dispatcher.
+///
+/// TLS, [cluster secrets](crate::prelude::ClusterSecret) and [failure detection](crate::prelude::FailureDetector)
+/// are not supported and are ignored if configured.
+///
+/// # Example
+///
+/// ```
+/// use kompact::prelude::*;
+///
+/// let network = MemoryNetwork::new();
+/// let mut systems = Vec::new();
+/// for _ in 0..2 {
+///     let mut net_config = NetworkConfig::default();
+///     net_config.set_memory_network(network.clone());
+///     let mut conf = KompactConfig::default();
+///     conf.system_components(DeadletterBox::new, net_config.build());
+///     systems.push(conf.build().expect("system"));
+/// }
+/// assert_ne!(systems[0].system_path(), systems[1].system_path());
+/// # for system in systems {
+/// #     system.shutdown().expect("shutdown");
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MemoryNetwork {
+    inner: Arc<Mutex<MemoryNetworkState>>,
+}
+
+#[derive(Default)]
+struct MemoryNetworkState {
+    endpoints: FxHashMap<SocketAddr, MemoryEndpoint>,
+    /// The last port which was assigned to a system binding to port 0
+    last_port: u16,
+}
+
+/// A system bound to a MemoryNetwork
+struct MemoryEndpoint {
+    incarnation: Uuid,
+    lookup: Arc<ArcSwap<ActorStore>>,
+    dispatcher: DispatcherRef,
+    /// The addresses of the systems this system is connected to
+    connections: FxHashSet<SocketAddr>,
+}
+
+impl MemoryNetwork {
+    /// Creates a new network without any systems
+    pub fn new() -> Self {
+        MemoryNetwork::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryNetworkState> {
+        self.inner.lock().expect("MemoryNetwork lock poisoned")
+    }
+
+    /// Binds a system to `addr`, returning the address it is actually bound to
+    pub(crate) fn bind(
+        &self,
+        addr: SocketAddr,
+        incarnation: Uuid,
+        lookup: Arc<ArcSwap<ActorStore>>,
+        dispatcher: DispatcherRef,
+    ) -> io::Result<SocketAddr> {
+        let mut state = self.state();
+        let mut bound_addr = addr;
+        if addr.port() == 0 {
+            let port = (1..=u16::MAX)
+                .map(|offset| state.last_port.wrapping_add(offset).max(1))
+                .find(|port| {
+                    bound_addr.set_port(*port);
+                    !state.endpoints.contains_key(&bound_addr)
+                })
+                .ok_or_else(|| io::Error::new(io::ErrorKind::AddrInUse, "no free ports"))?;
+            state.last_port = port;
+            bound_addr.set_port(port);
+        } else if state.endpoints.contains_key(&addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is already bound", addr),
+            ));
+        }
+        let endpoint = MemoryEndpoint {
+            incarnation,
+            lookup,
+            dispatcher,
+            connections: FxHashSet::default(),
+        };
+        state.endpoints.insert(bound_addr, endpoint);
+        Ok(bound_addr)
+    }
+
+    /// Removes the system at `addr`, closing all its connections
+    ///
+    /// The connected systems are told that the system left, as it shuts down deliberately.
+    pub(crate) fn unbind(&self, addr: &SocketAddr) -> () {
+        let mut state = self.state();
+        if let Some(endpoint) = state.endpoints.remove(addr) {
+            for remote in endpoint.connections {
+                if let Some(remote_endpoint) = state.endpoints.get_mut(&remote) {
+                    remote_endpoint.connections.remove(addr);
+                    tell_peer_left(&remote_endpoint.dispatcher, *addr);
+                }
+            }
+        }
+    }
+
+    /// Disconnects the system at `addr` from the system at `remote`
+    ///
+    /// All messages between them have already been delivered, so the connection is closed right away.
+    /// If the system at `addr` `left`, the system at `remote` is told so, otherwise it sees its connection closed.
+    pub(crate) fn disconnect(&self, addr: SocketAddr, remote: SocketAddr, left: bool) -> () {
+        let mut state = self.state();
+        if let Some(endpoint) = state.endpoints.get_mut(&addr) {
+            endpoint.connections.remove(&remote);
+            tell_connection_state(&endpoint.dispatcher, remote, ConnectionState::Closed);
+        }
+        if let Some(remote_endpoint) = state.endpoints.get_mut(&remote) {
+            if remote_endpoint.connections.remove(&addr) {
+                if left {
+                    tell_peer_left(&remote_endpoint.dispatcher, addr);
+                } else {
+                    tell_connection_state(
+                        &remote_endpoint.dispatcher,
+                        addr,
+                        ConnectionState::Closed,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Connects the system at `addr` to the system at `remote`, if it exists
+    ///
+    /// Both systems are informed about the new connection.
+    /// Returns `false` if there is no system bound to `remote`,
+    /// in which case the system at `addr` is told that the connection closed, like a refused one.
+    pub(crate) fn connect(&self, addr: SocketAddr, remote: SocketAddr) -> bool {
+        let mut state = self.state();
+        let remote_incarnation = match state.endpoints.get(&remote) {
+            Some(remote_endpoint) => remote_endpoint.incarnation,
+            None => {
+                if let Some(endpoint) = state.endpoints.get(&addr) {
+                    tell_connection_state(&endpoint.dispatcher, remote, ConnectionState::Closed);
+                }
+                return false;
+            }
+        };
+        let newly_connected = match state.endpoints.get_mut(&addr) {
+            Some(endpoint) => {
+                let newly_connected = endpoint.connections.insert(remote);
+                tell_incarnation(&endpoint.dispatcher, remote, remote_incarnation);
+                tell_connection_state(
+                    &endpoint.dispatcher,
+                    remote,
+                    ConnectionState::Connected(remote),
+                );
+                newly_connected
+            }
+            None => return false,
+        };
+        if newly_connected {
+            let incarnation = state.endpoints[&addr].incarnation;
+            if let Some(remote_endpoint) = state.endpoints.get_mut(&remote) {
+                remote_endpoint.connections.insert(addr);
+                tell_incarnation(&remote_endpoint.dispatcher, addr, incarnation);
+                tell_connection_state(
+                    &remote_endpoint.dispatcher,
+                    addr,
+                    ConnectionState::Connected(addr),
+                );
+            }
+        }
+        true
+    }
+
+    /// Returns the actor lookup of the system at `remote`,
+    /// if the system at `addr` is connected to it
+    fn connected_lookup(
+        &self,
+        addr: &SocketAddr,
+        remote: &SocketAddr,
+    ) -> Option<Arc<ArcSwap<ActorStore>>> {
+        let state = self.state();
+        let connected = state
+            .endpoints
+            .get(addr)
+            .map(|endpoint| endpoint.connections.contains(remote))
+            .unwrap_or(false);
+        if connected {
+            state
+                .endpoints
+                .get(remote)
+                .map(|endpoint| endpoint.lookup.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the actor lookup of the system at `remote`, if it exists
+    fn lookup(&self, remote: &SocketAddr) -> Option<Arc<ArcSwap<ActorStore>>> {
+        self.state()
+            .endpoints
+            .get(remote)
+            .map(|endpoint| endpoint.lookup.clone())
+    }
+}
+
+impl fmt::Debug for MemoryNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        let mut addrs: Vec<&SocketAddr> = state.endpoints.keys().collect();
+        addrs.sort();
+        f.debug_struct("MemoryNetwork")
+            .field("systems", &addrs)
+            .finish()
+    }
+}
+
+fn tell_incarnation(dispatcher: &DispatcherRef, addr: SocketAddr, incarnation: Uuid) {
+    dispatcher.tell(DispatchEnvelope::Event(EventEnvelope::Network(
+        NetworkEvent::Incarnation(addr, incarnation),
+    )));
+}
+
+fn tell_connection_state(dispatcher: &DispatcherRef, addr: SocketAddr, state: ConnectionState) {
+    dispatcher.tell(DispatchEnvelope::Event(EventEnvelope::Network(
+        NetworkEvent::Connection(addr, state),
+    )));
+}
+
+fn tell_peer_left(dispatcher: &DispatcherRef, addr: SocketAddr) {
+    dispatcher.tell(DispatchEnvelope::Event(EventEnvelope::Network(
+        NetworkEvent::PeerLeft(addr),
+    )));
+}
+
+/// The bridge of a single system into a [MemoryNetwork](MemoryNetwork)
+pub(crate) struct MemoryBridge {
+    log: KompactLogger,
+    network: MemoryNetwork,
+    addr: SocketAddr,
+}
+
+impl MemoryBridge {
+    /// Binds a new system to `addr` in `network`
+    ///
+    /// # Panics
+    ///
+    /// If `addr` is already bound in the `network`, like binding a socket would.
+    pub fn new(
+        log: KompactLogger,
+        network: MemoryNetwork,
+        addr: SocketAddr,
+        incarnation: Uuid,
+        lookup: Arc<ArcSwap<ActorStore>>,
+        dispatcher: DispatcherRef,
+    ) -> Self {
+        let addr = match network.bind(addr, incarnation, lookup, dispatcher.clone()) {
+            Ok(bound_addr) => bound_addr,
+            Err(e) => panic!(
+                "MemoryBridge failed to bind to address: {:?}, addr {:?}",
+                e, &addr
+            ),
+        };
+        debug!(log, "Bound to {} in memory network", addr);
+        MemoryBridge { log, network, addr }
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn set_dispatcher(&self, dispatcher: DispatcherRef) -> () {
+        if let Some(endpoint) = self.network.state().endpoints.get_mut(&self.addr) {
+            endpoint.dispatcher = dispatcher;
+        }
+    }
+
+    pub fn stop(&self) -> () {
+        debug!(self.log, "Unbinding {} from memory network", self.addr);
+        self.network.unbind(&self.addr);
+    }
+
+    pub fn connect(&self, remote: SocketAddr) -> () {
+        if !self.network.connect(self.addr, remote) {
+            // Like a refused connection, the dispatcher will retry later
+            debug!(self.log, "No system bound to {} in memory network", remote);
+        }
+    }
+
+    pub fn leave(&self, remote: SocketAddr) -> () {
+        debug!(self.log, "Leaving {} in memory network", remote);
+        self.network.disconnect(self.addr, remote, true);
+    }
+
+    pub fn close(&self, remote: SocketAddr) -> () {
+        debug!(
+            self.log,
+            "Closing connection to {} in memory network", remote
+        );
+        self.network.disconnect(self.addr, remote, false);
+    }
+
+    pub fn route(
+        &self,
+        remote: SocketAddr,
+        frame: SerialisedFrame,
+        protocol: Protocol,
+    ) -> Result<(), NetworkBridgeErr> {
+        let lookup = match protocol {
+            Protocol::TCP => self.network.connected_lookup(&self.addr, &remote),
+            // Memory networks never lose messages, so reliable UDP needs no acknowledgements
+            Protocol::UDP | Protocol::ReliableUDP => self.network.lookup(&remote),
+            Protocol::Custom(id) => {
+                return Err(NetworkBridgeErr::Other(format!(
+                    "Custom transport {} does not use the memory network",
+                    id
+                )));
+            }
+        };
+        match (lookup, protocol) {
+            (Some(lookup), _) => match deserialise_frame(frame) {
+                Ok(msg) => transport::deliver_to_lookup(&lookup, msg, &self.log),
+                Err(e) => error!(self.log, "Could not deserialise frame: {:?}", e),
+            },
+            (None, Protocol::TCP) => {
+                debug!(
+                    self.log,
+                    "Routing to non connected system {}, rejecting the message", remote
+                );
+                if let Some(endpoint) = self.network.state().endpoints.get(&self.addr) {
+                    endpoint
+                        .dispatcher
+                        .tell(DispatchEnvelope::Event(EventEnvelope::Network(
+                            NetworkEvent::RejectedFrame(remote, frame),
+                        )));
+                }
+            }
+            (None, _) => {
+                debug!(
+                    self.log,
+                    "No system bound to {} in memory network, dropping UDP message", remote
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -4,23 +4,43 @@ use arc_swap::ArcSwap;
 use dispatch::lookup::ActorStore;
 use net::events::NetworkEvent;
 
-use std::{io, net::SocketAddr, sync::Arc, thread};
+use std::{fmt, io, net::SocketAddr, path::PathBuf, sync::Arc, thread};
 
 use crate::{
-    messaging::SerialisedFrame,
+    messaging::{MulticastMembership, SerialisedFrame},
     net::{events::DispatchEvent, frames::*, network_thread::NetworkThread},
     prelude::NetworkConfig,
 };
 use bytes::{Buf, BufMut, BytesMut};
 use crossbeam_channel::{unbounded as channel, RecvError, SendError, Sender};
+use hocon::Hocon;
 use mio::{Interest, Waker};
+use rand::Rng;
+use rustc_hash::{FxHashMap, FxHasher};
+use std::{
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+use uuid::Uuid;
 
+pub mod auth;
 #[allow(missing_docs)]
 pub mod buffers;
+pub(crate) mod compression;
+pub mod failure_detector;
 pub mod frames;
+pub(crate) mod lanes;
+pub mod memory;
 pub(crate) mod network_channel;
 pub(crate) mod network_thread;
+pub(crate) mod reliable_udp;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod transport;
+pub(crate) mod udp_fragments;
 pub(crate) mod udp_state;
+pub(crate) mod unix;
 
 /// The state of a connection
 #[derive(Debug)]
@@ -34,19 +54,88 @@ pub enum ConnectionState {
     /// Already closed
     Closed,
     /// Threw an error
+    ///
+    /// If the remote host was rejected during the handshake, the error wraps a [HandshakeError](HandshakeError).
     Error(std::io::Error),
 }
 
+/// The reason a TCP channel was rejected during its handshake
+///
+/// Reported to the [NetworkDispatcher](crate::prelude::NetworkDispatcher) as the source of a
+/// [ConnectionState::Error](ConnectionState::Error), before the channel is closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// The remote host speaks this wire protocol version, which is not compatible with ours
+    ///
+    /// See [PROTOCOL_VERSION](frames::PROTOCOL_VERSION) and [MIN_PROTOCOL_VERSION](frames::MIN_PROTOCOL_VERSION).
+    IncompatibleVersion(u16),
+    /// The remote host announced this address, but its TLS certificate is not valid for it
+    UnverifiedAddress(SocketAddr),
+    /// The remote host did not prove knowledge of the [ClusterSecret](auth::ClusterSecret)
+    Unauthenticated,
+    /// The remote host sent data before completing the handshake
+    DataBeforeHandshake,
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeError::IncompatibleVersion(version) => write!(
+                f,
+                "the remote host speaks incompatible protocol version {}, supported versions are {} to {}",
+                version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ),
+            HandshakeError::UnverifiedAddress(addr) => {
+                write!(f, "the remote host failed to authenticate as {}", addr)
+            }
+            HandshakeError::Unauthenticated => {
+                f.write_str("the remote host failed to prove knowledge of the cluster secret")
+            }
+            HandshakeError::DataBeforeHandshake => {
+                f.write_str("the remote host sent data before completing the handshake")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+impl From<HandshakeError> for io::Error {
+    fn from(error: HandshakeError) -> Self {
+        let kind = match error {
+            HandshakeError::IncompatibleVersion(_) | HandshakeError::DataBeforeHandshake => {
+                io::ErrorKind::InvalidData
+            }
+            HandshakeError::UnverifiedAddress(_) | HandshakeError::Unauthenticated => {
+                io::ErrorKind::PermissionDenied
+            }
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum Protocol {
     TCP,
     UDP,
+    ReliableUDP,
+    Custom(u8),
 }
-impl From<Transport> for Protocol {
-    fn from(t: Transport) -> Self {
+impl TryFrom<Transport> for Protocol {
+    type Error = NetworkBridgeErr;
+
+    fn try_from(t: Transport) -> Result<Self, Self::Error> {
         match t {
-            Transport::TCP => Protocol::TCP,
-            Transport::UDP => Protocol::UDP,
-            _ => unimplemented!("Unsupported Protocol"),
+            Transport::TCP => Ok(Protocol::TCP),
+            Transport::UDP => Ok(Protocol::UDP),
+            Transport::RUDP => Ok(Protocol::ReliableUDP),
+            // Unix domain sockets are framed like TCP, and share its channels
+            Transport::UDS => Ok(Protocol::TCP),
+            Transport::Custom(id) => Ok(Protocol::Custom(id)),
+            Transport::LOCAL => Err(NetworkBridgeErr::Other(format!(
+                "Unsupported protocol: {}",
+                t
+            ))),
         }
     }
 }
@@ -56,19 +145,53 @@ pub mod events {
 
     use super::ConnectionState;
     use crate::net::frames::*;
-    use std::net::SocketAddr;
+    use std::{net::SocketAddr, path::PathBuf};
+    use uuid::Uuid;
 
-    use crate::messaging::SerialisedFrame;
+    use crate::messaging::{MulticastMembership, SerialisedFrame};
 
     /// Network events emitted by the network `Bridge`
     #[derive(Debug)]
     pub enum NetworkEvent {
         /// The state of a connection changed
         Connection(SocketAddr, ConnectionState),
+        /// The remote host announced its system's incarnation during the handshake
+        ///
+        /// Sent right before the connection is reported as connected.
+        Incarnation(SocketAddr, Uuid),
         /// Data was received
         Data(Frame),
         /// The NetworkThread lost connection to the remote host and rejects the frame
         RejectedFrame(SocketAddr, SerialisedFrame),
+        /// The remote host requested a channel over its Unix domain socket at the path,
+        /// and the channel is keyed by the synthetic address of the socket
+        ///
+        /// Sent right before the channel is started.
+        UnixSocket(SocketAddr, PathBuf),
+        /// The remote host said Bye as it left, it must not be reconnected until the next frame is sent,
+        /// and the frames queued for it are dropped
+        PeerLeft(SocketAddr),
+        /// The NetworkThread or the remote host said Bye and closed the connection, as no data was exchanged
+        /// for the idle timeout; it must not be reconnected until the next frame is sent
+        Idle(SocketAddr),
+        /// The state of the reliable UDP session to the remote host changed
+        ReliableUdpConnection(SocketAddr, ConnectionState),
+        /// Too many reliable UDP messages were waiting to be sent to the remote host,
+        /// and the frames were dropped according to the queue overflow policy
+        ReliableUdpOverflow(SocketAddr, Vec<SerialisedFrame>),
+        /// The NetworkThread gave up on the reliable UDP session to the remote host,
+        /// and drops the frames which were not acknowledged
+        ReliableUdpDropped(SocketAddr, Vec<SerialisedFrame>),
+        /// The failure detector suspects the remote host to have failed
+        Suspected(SocketAddr),
+        /// The failure detector no longer suspects the remote host
+        Alive(SocketAddr),
+        /// This many UDP messages from the remote host were dropped, as not all of their fragments arrived in time
+        IncompleteUdpMessages(SocketAddr, usize),
+        /// The fault injector partitioned this system from the remote host
+        Partitioned(SocketAddr),
+        /// The fault injector healed the partition between this system and the remote host
+        Healed(SocketAddr),
     }
 
     /// BridgeEvents emitted to the network `Bridge`
@@ -78,12 +201,24 @@ pub mod events {
         SendTCP(SocketAddr, SerialisedFrame),
         /// Send the SerialisedFrame to receiver associated with the SocketAddr
         SendUDP(SocketAddr, SerialisedFrame),
+        /// Send the SerialisedFrame reliably to receiver associated with the SocketAddr, over UDP
+        SendReliableUDP(SocketAddr, SerialisedFrame),
         /// Tells the network thread to Stop
         Stop,
         /// Tells the network adress to open up a channel to the SocketAddr
         Connect(SocketAddr),
+        /// Tells the network thread to open up a channel to the Unix domain socket at the path,
+        /// keyed by the synthetic SocketAddr of the socket
+        ConnectUnix(SocketAddr, PathBuf),
         /// Acknowledges a closed channel, required to ensure FIFO ordering under connection loss
         ClosedAck(SocketAddr),
+        /// Tells the network thread to send the remaining frames to the SocketAddr,
+        /// and then to close the channel with a Bye
+        Leave(SocketAddr),
+        /// Tells the network thread to close the channel to the SocketAddr right away
+        Close(SocketAddr),
+        /// Tells the network thread to join or leave a multicast group
+        Multicast(MulticastMembership),
     }
 
     /// Errors emitted byt the network `Bridge`
@@ -120,22 +255,254 @@ impl BridgeConfig {
 }
 
 #[allow(dead_code)]
-enum RetryStrategy {
-    ExponentialBackoff { base_ms: u64, num_tries: usize },
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        let retry_strategy = RetryStrategy::default();
+        BridgeConfig { retry_strategy }
+    }
 }
 
-#[allow(dead_code)]
-impl Default for BridgeConfig {
+/// Determines how long to wait between attempts at re-establishing a lost connection,
+/// and how many attempts to make before giving up on a remote host.
+///
+/// Each remote host is tracked separately, so a flapping remote does not affect
+/// the reconnection schedule of any other remote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryStrategy {
+    /// Retry every `interval_ms` milliseconds, giving up after `num_tries` attempts.
+    FixedInterval {
+        /// The time to wait between attempts, in milliseconds
+        interval_ms: u64,
+        /// The number of attempts before giving up
+        num_tries: u8,
+    },
+    /// Wait `base_ms` milliseconds before the first attempt and double the delay
+    /// after each further attempt, up to at most `max_ms` milliseconds.
+    /// Giving up after `num_tries` attempts.
+    ///
+    /// A random jitter of up to half of the delay is subtracted from each delay,
+    /// so that remotes which were lost at the same time are not retried in lock-step.
+    ///
+    /// If a connection is lost again shortly after being re-established, the backoff
+    /// continues from the previous delay instead of starting over at `base_ms`.
+    ExponentialBackoff {
+        /// The delay before the first attempt, in milliseconds
+        base_ms: u64,
+        /// The upper bound for the delay between attempts, in milliseconds
+        max_ms: u64,
+        /// The number of attempts before giving up
+        num_tries: u8,
+    },
+}
+
+impl RetryStrategy {
+    /// Tries to deserialise a retry strategy from the `network.retry_strategy` section of `config`.
+    ///
+    /// The `type` key selects the strategy and must be either `"fixed_interval"` or
+    /// `"exponential_backoff"`. Any omitted parameters keep their default values.
+    ///
+    /// Returns `None` if the section or its `type` is absent, and an error if the `type` is not recognised,
+    /// `num_tries` does not fit into a `u8`, or any of the durations is negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kompact::prelude::*;
+    /// use hocon::HoconLoader;
+    ///
+    /// let config = HoconLoader::new()
+    ///     .load_str(
+    ///         r#"{
+    ///             network.retry_strategy {
+    ///                 type = "exponential_backoff",
+    ///                 base_ms = 50,
+    ///                 max_ms = 2000,
+    ///                 num_tries = 8,
+    ///             }
+    ///         }"#,
+    ///     )
+    ///     .expect("config")
+    ///     .hocon()
+    ///     .expect("hocon");
+    /// assert_eq!(
+    ///     RetryStrategy::from_config(&config),
+    ///     Ok(Some(RetryStrategy::ExponentialBackoff {
+    ///         base_ms: 50,
+    ///         max_ms: 2000,
+    ///         num_tries: 8
+    ///     }))
+    /// );
+    /// ```
+    pub fn from_config(config: &Hocon) -> Result<Option<Self>, hocon::Error> {
+        let section = &config["network"]["retry_strategy"];
+        let mut strategy = match section["type"].as_string().as_deref() {
+            Some("fixed_interval") => RetryStrategy::fixed_interval(),
+            Some("exponential_backoff") => RetryStrategy::default(),
+            Some(other) => {
+                return Err(hocon::Error::Deserialization {
+                    message: format!(
+                        "network.retry_strategy.type must be \"fixed_interval\" or \"exponential_backoff\", but was \"{}\"",
+                        other
+                    ),
+                })
+            }
+            None => return Ok(None),
+        };
+        if let Some(num_tries) = section["num_tries"].as_i64() {
+            let num_tries = u8::try_from(num_tries).map_err(|_| hocon::Error::Deserialization {
+                message: format!(
+                    "network.retry_strategy.num_tries must be between 0 and {}, but was {}",
+                    u8::MAX,
+                    num_tries
+                ),
+            })?;
+            strategy.set_num_tries(num_tries);
+        }
+        match strategy {
+            RetryStrategy::FixedInterval {
+                ref mut interval_ms,
+                ..
+            } => {
+                if let Some(interval) =
+                    config_millis(section, "network.retry_strategy", "interval_ms")?
+                {
+                    *interval_ms = interval;
+                }
+            }
+            RetryStrategy::ExponentialBackoff {
+                ref mut base_ms,
+                ref mut max_ms,
+                ..
+            } => {
+                if let Some(base) = config_millis(section, "network.retry_strategy", "base_ms")? {
+                    *base_ms = base;
+                }
+                if let Some(max) = config_millis(section, "network.retry_strategy", "max_ms")? {
+                    *max_ms = max;
+                }
+            }
+        }
+        Ok(Some(strategy))
+    }
+
+    /// A [FixedInterval](RetryStrategy::FixedInterval) strategy with the default parameters,
+    /// retrying every 5000 ms for 10 attempts.
+    pub fn fixed_interval() -> Self {
+        RetryStrategy::FixedInterval {
+            interval_ms: 5000,
+            num_tries: 10,
+        }
+    }
+
+    /// Returns the number of attempts made before giving up on a remote host.
+    pub fn num_tries(&self) -> u8 {
+        match *self {
+            RetryStrategy::FixedInterval { num_tries, .. } => num_tries,
+            RetryStrategy::ExponentialBackoff { num_tries, .. } => num_tries,
+        }
+    }
+
+    /// Sets the number of attempts made before giving up on a remote host.
+    pub fn set_num_tries(&mut self, count: u8) -> () {
+        match self {
+            RetryStrategy::FixedInterval { num_tries, .. } => *num_tries = count,
+            RetryStrategy::ExponentialBackoff { num_tries, .. } => *num_tries = count,
+        }
+    }
+
+    /// Returns the (initial) delay between attempts in milliseconds.
+    ///
+    /// This is the `interval_ms` for [FixedInterval](RetryStrategy::FixedInterval)
+    /// and the `base_ms` for [ExponentialBackoff](RetryStrategy::ExponentialBackoff).
+    pub fn interval_ms(&self) -> u64 {
+        match *self {
+            RetryStrategy::FixedInterval { interval_ms, .. } => interval_ms,
+            RetryStrategy::ExponentialBackoff { base_ms, .. } => base_ms,
+        }
+    }
+
+    /// Sets the (initial) delay between attempts in milliseconds.
+    ///
+    /// See [interval_ms](RetryStrategy::interval_ms) for details.
+    pub fn set_interval_ms(&mut self, milliseconds: u64) -> () {
+        match self {
+            RetryStrategy::FixedInterval { interval_ms, .. } => *interval_ms = milliseconds,
+            RetryStrategy::ExponentialBackoff { base_ms, .. } => *base_ms = milliseconds,
+        }
+    }
+
+    /// Returns the longest delay this strategy will wait between two attempts.
+    ///
+    /// A connection which is lost again within this time after being established
+    /// is considered to be flapping, and is not retried from the shortest delay again.
+    pub fn max_delay(&self) -> Duration {
+        match *self {
+            RetryStrategy::FixedInterval { interval_ms, .. } => Duration::from_millis(interval_ms),
+            RetryStrategy::ExponentialBackoff { max_ms, .. } => Duration::from_millis(max_ms),
+        }
+    }
+
+    /// Returns how long to wait before the given (zero-based) `attempt`.
+    pub fn delay(&self, attempt: u8) -> Duration {
+        match *self {
+            RetryStrategy::FixedInterval { interval_ms, .. } => Duration::from_millis(interval_ms),
+            RetryStrategy::ExponentialBackoff {
+                base_ms, max_ms, ..
+            } => {
+                let factor = 1u64.checked_shl(attempt as u32).unwrap_or(u64::MAX);
+                let delay = base_ms.saturating_mul(factor).min(max_ms);
+                let jitter = rand::thread_rng().gen_range(0, delay / 2 + 1);
+                Duration::from_millis(delay - jitter)
+            }
+        }
+    }
+}
+
+/// Reads the duration in milliseconds at `key` of the config `section` at `path`, if present.
+///
+/// Returns an error if it is negative.
+pub(crate) fn config_millis(
+    section: &Hocon,
+    path: &str,
+    key: &str,
+) -> Result<Option<u64>, hocon::Error> {
+    section[key]
+        .as_i64()
+        .map(|millis| {
+            u64::try_from(millis).map_err(|_| hocon::Error::Deserialization {
+                message: format!("{}.{} must not be negative, but was {}", path, key, millis),
+            })
+        })
+        .transpose()
+}
+
+/// Defaults to exponential backoff starting at 100 ms, capped at 5000 ms, for 10 attempts.
+impl Default for RetryStrategy {
     fn default() -> Self {
-        let retry_strategy = RetryStrategy::ExponentialBackoff {
+        RetryStrategy::ExponentialBackoff {
             base_ms: 100,
-            num_tries: 5,
-        };
-        BridgeConfig { retry_strategy }
+            max_ms: 5000,
+            num_tries: 10,
+        }
+    }
+}
+
+/// Returns the index of the network thread, out of `threads`, which is responsible for the channel to `addr`
+///
+/// The first thread (index 0) additionally accepts incoming connections and drives the UDP socket.
+pub(crate) fn shard_of(addr: &SocketAddr, threads: usize) -> usize {
+    if threads <= 1 {
+        return 0;
     }
+    let mut hasher = FxHasher::default();
+    addr.hash(&mut hasher);
+    (hasher.finish() % threads as u64) as usize
 }
 
-/// Bridge to Network Threads. Routes outbound messages to the correct network thread. Single threaded for now.
+/// Bridge to Network Threads. Routes outbound messages to the correct network thread.
+///
+/// Channels are sharded across the threads by their remote address, see
+/// [set_network_threads](crate::prelude::NetworkConfig::set_network_threads).
 pub struct Bridge {
     /// Network-specific configuration
     //cfg: BridgeConfig,
@@ -146,20 +513,97 @@ pub struct Bridge {
     /// Network Thread stuff:
     // network_thread: Box<NetworkThread>,
     // ^ Can we avoid storing this by moving it into itself?
-    network_input_queue: Sender<events::DispatchEvent>,
-    waker: Waker,
+    backend: Arc<BridgeBackend>,
+    /// The transports frames are sent over, including the built-in ones
+    transports: FxHashMap<Protocol, Box<dyn transport::NetworkTransport>>,
     /// Tokio Runtime
     // tokio_runtime: Option<Runtime>,
     /// Reference back to the Kompact dispatcher
     dispatcher: Option<DispatcherRef>,
     /// Socket the network actually bound on
     bound_addr: Option<SocketAddr>,
+}
+
+/// The sockets behind a `Bridge`, shared with its built-in transports
+pub(crate) enum BridgeBackend {
+    /// Sockets driven by one or more `NetworkThread`s, the first of which owns the listening sockets
+    Threads(Vec<NetworkThreadHandle>),
+    /// Shared queues in a `MemoryNetwork`
+    Memory(memory::MemoryBridge),
+}
+
+impl BridgeBackend {
+    /// Returns the NetworkThread responsible for the channel to `addr`
+    ///
+    /// # Errors
+    /// If the backend is a memory network, which has no NetworkThreads
+    fn thread_for(&self, addr: &SocketAddr) -> Result<&NetworkThreadHandle, NetworkBridgeErr> {
+        match self {
+            BridgeBackend::Threads(threads) => Ok(&threads[shard_of(addr, threads.len())]),
+            BridgeBackend::Memory(_) => Err(no_network_thread()),
+        }
+    }
+
+    /// Returns the first NetworkThread, which owns the listening sockets
+    ///
+    /// # Errors
+    /// If the backend is a memory network, which has no NetworkThreads
+    fn first_thread(&self) -> Result<&NetworkThreadHandle, NetworkBridgeErr> {
+        match self {
+            BridgeBackend::Threads(threads) => Ok(&threads[0]),
+            BridgeBackend::Memory(_) => Err(no_network_thread()),
+        }
+    }
+}
+
+/// The error for operations which require a NetworkThread, when there is none
+fn no_network_thread() -> NetworkBridgeErr {
+    NetworkBridgeErr::Other("Memory bridges have no NetworkThread".to_string())
+}
+
+/// The bridge's end of a running `NetworkThread`
+pub(crate) struct NetworkThreadHandle {
+    input_queue: Sender<events::DispatchEvent>,
+    waker: Arc<Waker>,
     shutdown_future: KFuture<()>,
 }
 
+impl NetworkThreadHandle {
+    /// Spawns a thread running `network_thread`
+    fn spawn(
+        mut network_thread: NetworkThread,
+        name: String,
+        input_queue: Sender<events::DispatchEvent>,
+        waker: Arc<Waker>,
+        shutdown_future: KFuture<()>,
+    ) -> Self {
+        if let Err(e) = thread::Builder::new().name(name).spawn(move || {
+            network_thread.run();
+        }) {
+            panic!("Failed to start a Network Thread, error: {:?}", e);
+        }
+        NetworkThreadHandle {
+            input_queue,
+            waker,
+            shutdown_future,
+        }
+    }
+
+    /// Sends `event` to the NetworkThread and makes sure that it will wake up.
+    fn send_event(&self, event: events::DispatchEvent) -> Result<(), NetworkBridgeErr> {
+        self.input_queue.send(event)?;
+        self.waker.wake()?;
+        Ok(())
+    }
+}
+
 impl Bridge {
     /// Creates a new bridge
     ///
+    /// Binds to a [MemoryNetwork](memory::MemoryNetwork) if one is set in the `network_config`,
+    /// and otherwise starts the configured number of network threads.
+    /// The system's `incarnation` is announced to the remote systems it connects to.
+    ///
     /// # Returns
     /// A tuple consisting of the new Bridge object and the network event receiver.
     /// The receiver will allow responding to [NetworkEvent]s for external state management.
@@ -168,55 +612,171 @@ impl Bridge {
         network_thread_log: KompactLogger,
         bridge_log: KompactLogger,
         addr: SocketAddr,
+        incarnation: Uuid,
         dispatcher_ref: DispatcherRef,
         network_config: &NetworkConfig,
     ) -> (Self, SocketAddr) {
+        if let Some(network) = network_config.get_memory_network() {
+            let memory_bridge = memory::MemoryBridge::new(
+                bridge_log.clone(),
+                network.clone(),
+                addr,
+                incarnation,
+                lookup.clone(),
+                dispatcher_ref.clone(),
+            );
+            let bound_addr = memory_bridge.local_addr();
+            let bridge = Bridge::with_backend(
+                bridge_log,
+                BridgeBackend::Memory(memory_bridge),
+                lookup,
+                dispatcher_ref,
+                bound_addr,
+                network_config,
+            );
+            return (bridge, bound_addr);
+        }
         let (sender, receiver) = channel();
         let (shutdown_p, shutdown_f) = promise();
         let (mut network_thread, waker) = NetworkThread::new(
             network_thread_log,
             addr,
-            lookup,
+            incarnation,
+            lookup.clone(),
             receiver,
             shutdown_p,
             dispatcher_ref.clone(),
             network_config.clone(),
         );
         let bound_addr = network_thread.addr;
-        let bridge = Bridge {
+        // Create the other threads first, as the first thread must know them all when it starts
+        let mut shards = Vec::new();
+        for index in 1..network_config.get_network_threads() {
+            let (shard_sender, shard_receiver) = channel();
+            let (shard_shutdown_p, shard_shutdown_f) = promise();
+            let (shard_thread, shard_waker) =
+                network_thread.new_shard(shard_receiver, shard_shutdown_p);
+            shards.push(NetworkThreadHandle::spawn(
+                shard_thread,
+                format!("network_thread-{}", index),
+                shard_sender,
+                shard_waker,
+                shard_shutdown_f,
+            ));
+        }
+        let mut threads = vec![NetworkThreadHandle::spawn(
+            network_thread,
+            "network_thread".to_string(),
+            sender,
+            Arc::new(waker),
+            shutdown_f,
+        )];
+        threads.append(&mut shards);
+        let bridge = Bridge::with_backend(
+            bridge_log,
+            BridgeBackend::Threads(threads),
+            lookup,
+            dispatcher_ref,
+            bound_addr,
+            network_config,
+        );
+        (bridge, bound_addr)
+    }
+
+    /// Creates the built-in transports over `backend`, and starts the custom transports of the `network_config`
+    ///
+    /// # Panics
+    ///
+    /// If a custom transport fails to start, like failing to bind a socket does.
+    fn with_backend(
+        log: KompactLogger,
+        backend: BridgeBackend,
+        lookup: Arc<ArcSwap<ActorStore>>,
+        dispatcher_ref: DispatcherRef,
+        bound_addr: SocketAddr,
+        network_config: &NetworkConfig,
+    ) -> Self {
+        let backend = Arc::new(backend);
+        let mut transports: FxHashMap<Protocol, Box<dyn transport::NetworkTransport>> =
+            FxHashMap::default();
+        transports.insert(
+            Protocol::TCP,
+            Box::new(transport::TcpTransport::new(backend.clone())),
+        );
+        transports.insert(
+            Protocol::UDP,
+            Box::new(transport::UdpTransport::new(backend.clone(), false)),
+        );
+        transports.insert(
+            Protocol::ReliableUDP,
+            Box::new(transport::UdpTransport::new(backend.clone(), true)),
+        );
+        for (id, factory) in network_config.custom_transports() {
+            let mut custom = factory();
+            let context = transport::TransportContext::new(
+                Transport::Custom(id),
+                bound_addr,
+                lookup.clone(),
+                dispatcher_ref.clone(),
+                log.new(o!("transport" => id)),
+            );
+            if let Err(e) = custom.start(context) {
+                panic!("Failed to start custom transport {}, error: {:?}", id, e);
+            }
+            transports.insert(Protocol::Custom(id), custom);
+        }
+        Bridge {
             // cfg: BridgeConfig::default(),
-            log: bridge_log,
+            log,
             // lookup,
-            network_input_queue: sender,
-            waker,
+            backend,
+            transports,
             dispatcher: Some(dispatcher_ref),
             bound_addr: Some(bound_addr),
-            shutdown_future: shutdown_f,
-        };
-        if let Err(e) = thread::Builder::new()
-            .name("network_thread".to_string())
-            .spawn(move || {
-                network_thread.run();
-            })
-        {
-            panic!("Failed to start a Network Thread, error: {:?}", e);
         }
-        (bridge, bound_addr)
     }
 
     /// Sets the dispatcher reference, returning the previously stored one
+    ///
+    /// Custom transports keep reporting to the dispatcher the bridge was created with.
     pub fn set_dispatcher(&mut self, dispatcher: DispatcherRef) -> Option<DispatcherRef> {
+        if let BridgeBackend::Memory(ref memory_bridge) = *self.backend {
+            memory_bridge.set_dispatcher(dispatcher.clone());
+        }
         std::mem::replace(&mut self.dispatcher, Some(dispatcher))
     }
 
     /// Stops the bridge
     pub fn stop(self) -> Result<(), NetworkBridgeErr> {
         debug!(self.log, "Stopping NetworkBridge...");
-        self.network_input_queue.send(DispatchEvent::Stop)?;
-        self.waker
-            .wake()
-            .expect("Network Bridge Waking NetworkThread in stop()");
-        self.shutdown_future.wait(); // should block until something is sent
+        for (protocol, mut transport) in self.transports {
+            if let Err(e) = transport.stop() {
+                warn!(
+                    self.log,
+                    "Transport {:?} did not stop cleanly: {:?}", protocol, e
+                );
+            }
+        }
+        let backend = Arc::try_unwrap(self.backend).map_err(|_| {
+            NetworkBridgeErr::Thread(
+                "The network threads are still shared with a transport".to_string(),
+            )
+        })?;
+        match backend {
+            BridgeBackend::Threads(threads) => {
+                for thread in threads.iter() {
+                    thread.input_queue.send(DispatchEvent::Stop)?;
+                    thread
+                        .waker
+                        .wake()
+                        .expect("Network Bridge Waking NetworkThread in stop()");
+                }
+                for thread in threads {
+                    thread.shutdown_future.wait(); // should block until something is sent
+                }
+            }
+            BridgeBackend::Memory(memory_bridge) => memory_bridge.stop(),
+        }
         debug!(self.log, "Stopped NetworkBridge.");
         Ok(())
     }
@@ -226,14 +786,15 @@ impl Bridge {
         &self.bound_addr
     }
 
-    /// Forwards `serialized` to the NetworkThread and makes sure that it will wake up.
+    /// Forwards `serialized` to the transport of `protocol`.
     pub(crate) fn route(
         &self,
         addr: SocketAddr,
         serialized: SerialisedFrame,
         protocol: Protocol,
     ) -> Result<(), NetworkBridgeErr> {
-        match serialized {
+        let transport = self.transport(protocol)?;
+        let serialized = match serialized {
             SerialisedFrame::Bytes(bytes) => {
                 let size = FrameHead::encoded_len() + bytes.len();
                 let mut buf = BytesMut::with_capacity(size);
@@ -241,87 +802,121 @@ impl Bridge {
                 head.encode_into(&mut buf);
                 // TODO: what is this used for?
                 buf.put_slice(bytes.bytes());
-                match protocol {
-                    Protocol::TCP => {
-                        self.network_input_queue
-                            .send(events::DispatchEvent::SendTCP(
-                                addr,
-                                SerialisedFrame::Bytes(buf.freeze()),
-                            ))?;
-                    }
-                    Protocol::UDP => {
-                        self.network_input_queue
-                            .send(events::DispatchEvent::SendUDP(
-                                addr,
-                                SerialisedFrame::Bytes(buf.freeze()),
-                            ))?;
-                    }
-                }
+                SerialisedFrame::Bytes(buf.freeze())
             }
-            SerialisedFrame::ChunkLease(chunk) => match protocol {
-                Protocol::TCP => {
-                    self.network_input_queue
-                        .send(events::DispatchEvent::SendTCP(
-                            addr,
-                            SerialisedFrame::ChunkLease(chunk),
-                        ))?;
-                }
-                Protocol::UDP => {
-                    self.network_input_queue
-                        .send(events::DispatchEvent::SendUDP(
-                            addr,
-                            SerialisedFrame::ChunkLease(chunk),
-                        ))?;
-                }
-            },
-            SerialisedFrame::ChunkRef(chunk) => match protocol {
-                Protocol::TCP => {
-                    self.network_input_queue
-                        .send(events::DispatchEvent::SendTCP(
-                            addr,
-                            SerialisedFrame::ChunkRef(chunk),
-                        ))?;
-                }
-                Protocol::UDP => {
-                    self.network_input_queue
-                        .send(events::DispatchEvent::SendUDP(
-                            addr,
-                            SerialisedFrame::ChunkRef(chunk),
-                        ))?;
-                }
-            },
-        }
-        self.waker.wake()?;
-        Ok(())
+            other => other,
+        };
+        transport.send(addr, serialized)
     }
 
-    /// Attempts to establish a TCP connection to the provided `addr`.
+    /// Returns the transport registered for `protocol`
+    fn transport(
+        &self,
+        protocol: Protocol,
+    ) -> Result<&dyn transport::NetworkTransport, NetworkBridgeErr> {
+        self.transports
+            .get(&protocol)
+            .map(|transport| transport.as_ref())
+            .ok_or_else(|| {
+                NetworkBridgeErr::Other(format!("No transport registered for {:?}", protocol))
+            })
+    }
+
+    /// Attempts to establish a connection to the provided `addr` over the transport of `proto`.
     ///
     /// # Side effects
     /// When the connection is successul:
     ///     - a `ConnectionState::Connected` is dispatched on the network bridge event queue
-    ///     - NetworkThread will listen for incoming messages and write outgoing messages on the channel
+    ///     - the transport will deliver incoming messages and send outgoing messages on the connection
+    ///
+    /// UDP is connectionless, so connecting over `UDP` or `RUDP` succeeds without doing anything.
     ///
     /// # Errors
-    /// If the provided protocol is not supported
+    /// If the provided protocol is not supported, or no transport is registered for it
     pub fn connect(&self, proto: Transport, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
         match proto {
-            Transport::TCP => {
-                self.network_input_queue
-                    .send(events::DispatchEvent::Connect(addr))?;
-                self.waker.wake()?;
-                Ok(())
+            Transport::UDP | Transport::RUDP => Ok(()),
+            Transport::TCP | Transport::Custom(_) => {
+                self.transport(Protocol::try_from(proto)?)?.connect(addr)
             }
             _other => Err(NetworkBridgeErr::Other("Bad Protocol".to_string())),
         }
     }
 
+    /// Gracefully closes the connection to `addr` over the transport of `proto`,
+    /// after sending all frames which were routed to it before.
+    ///
+    /// # Side effects
+    /// Once the connection is closed, a `ConnectionState::Closed` is dispatched on the network bridge event queue,
+    /// and the remote host is told that we left, such that it does not try to reconnect.
+    ///
+    /// # Errors
+    /// If the transport of the provided protocol does not support leaving
+    pub(crate) fn leave(&self, proto: Protocol, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        self.transport(proto)?.leave(addr)
+    }
+
+    /// Closes the connection to `addr` over the transport of `proto` right away.
+    ///
+    /// # Side effects
+    /// A `ConnectionState::Closed` is dispatched on the network bridge event queue,
+    /// and frames which were not sent yet are rejected.
+    ///
+    /// # Errors
+    /// If the transport of the provided protocol does not support closing connections
+    pub(crate) fn close(&self, proto: Protocol, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        self.transport(proto)?.close(addr)
+    }
+
+    /// Attempts to establish a channel to the Unix domain socket at `path`,
+    /// whose synthetic address is `addr`.
+    ///
+    /// Once connected, the channel is used exactly like a TCP channel to `addr`.
+    /// In-memory networks have no Unix domain sockets, so the connection attempt fails like a refused one.
+    pub fn connect_unix(&self, addr: SocketAddr, path: PathBuf) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .thread_for(&addr)?
+                .send_event(events::DispatchEvent::ConnectUnix(addr, path)),
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.connect(addr);
+                Ok(())
+            }
+        }
+    }
+
+    /// Joins or leaves a UDP multicast group, as requested by `membership`
+    ///
+    /// # Errors
+    /// If the bridge is backed by an in-memory network, which does not support multicast
+    pub fn multicast(&self, membership: MulticastMembership) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .first_thread()?
+                .send_event(events::DispatchEvent::Multicast(membership)),
+            BridgeBackend::Memory(_) => Err(NetworkBridgeErr::Other(
+                "Multicast is not supported by in-memory networks".to_string(),
+            )),
+        }
+    }
+
     /// Acknowledges a closed channel, required to ensure FIFO ordering under connection loss
     pub fn ack_closed(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
-        self.network_input_queue
-            .send(events::DispatchEvent::ClosedAck(addr))?;
-        self.waker.wake()?;
-        Ok(())
+        match *self.backend {
+            BridgeBackend::Threads(_) => {
+                let thread = self.backend.thread_for(&addr)?;
+                thread.send_event(events::DispatchEvent::ClosedAck(addr))?;
+                let first_thread = self.backend.first_thread()?;
+                if !std::ptr::eq(thread, first_thread) {
+                    // Channels which were closed before their Start never left the first thread
+                    first_thread.send_event(events::DispatchEvent::ClosedAck(addr))?;
+                }
+                Ok(())
+            }
+            BridgeBackend::Memory(_) => Ok(()), // Nothing is left behind by closed connections
+        }
     }
 }
 
@@ -383,6 +978,14 @@ pub(crate) fn broken_pipe(err: &io::Error) -> bool {
     err.kind() == io::ErrorKind::BrokenPipe
 }
 
+pub(crate) fn connection_aborted(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::ConnectionAborted
+}
+
+pub(crate) fn connection_refused(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::ConnectionRefused
+}
+
 /// A module with helper functions for testing network configurations/implementations
 pub mod net_test_helpers {
     use crate::prelude::*;
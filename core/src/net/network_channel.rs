@@ -1,24 +1,54 @@
 use super::*;
+#[cfg(feature = "tls")]
+use crate::net::tls::TlsSession;
 use crate::{
     messaging::SerialisedFrame,
     net::{
+        auth::{self, ClusterSecret},
         buffers::{BufferChunk, DecodeBuffer},
-        frames::{Ack, Frame, FramingError, Hello, Start, FRAME_HEAD_LEN},
+        failure_detector::{FailureDetector, HeartbeatHistory},
+        frames::{
+            negotiate_version,
+            Ack,
+            Bye,
+            ByeReason,
+            Capabilities,
+            CreditUpdate,
+            Frame,
+            FramingError,
+            Hello,
+            Start,
+            StreamRequest,
+            AUTH_NONCE_LEN,
+            FRAME_HEAD_LEN,
+            LEGACY_PROTOCOL_VERSION,
+            PROTOCOL_VERSION,
+        },
     },
 };
-use bytes::{Buf, BytesMut};
-use mio::{net::TcpStream, Token};
+use bytes::BytesMut;
+#[cfg(unix)]
+use mio::net::UnixStream;
+use mio::{event, net::TcpStream, Interest, Registry, Token};
 use network_thread::*;
 use std::{
-    cmp::Ordering,
+    cmp::{min, Ordering},
     collections::VecDeque,
     fmt::Formatter,
     io,
-    io::{Error, ErrorKind, Read, Write},
-    net::{Shutdown::Both, SocketAddr},
+    io::{Error, ErrorKind, IoSlice, Read, Write},
+    net::{
+        Shutdown::{self, Both},
+        SocketAddr,
+    },
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 use uuid::Uuid;
 
+/// The maximum number of frames gathered into a single vectored write, well below any platform's `IOV_MAX`
+const MAX_WRITE_SLICES: usize = 256;
+
 /// Received connection: Initialising -> Say Hello, Receive Start -> Connected, Send Ack
 /// Requested connection: Requested -> Receive Hello -> Initialised -> Send Start, Receive Ack -> Connected
 pub(crate) enum ChannelState {
@@ -34,49 +64,330 @@ pub(crate) enum ChannelState {
     Closed(SocketAddr, Uuid),
 }
 
+/// The stream underlying a [TcpChannel](TcpChannel)
+///
+/// Channels to systems on the same host may use a Unix domain socket instead of TCP,
+/// see [unix](crate::net::unix). Such channels are framed exactly like TCP channels,
+/// but never use TLS or additional lanes.
+#[derive(Debug)]
+pub(crate) enum ChannelStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl ChannelStream {
+    /// Returns `true` if this is a Unix domain socket
+    pub fn is_unix(&self) -> bool {
+        match self {
+            ChannelStream::Tcp(_) => false,
+            #[cfg(unix)]
+            ChannelStream::Unix(_) => true,
+        }
+    }
+
+    /// Returns the local address of a TCP stream, Unix domain sockets have none
+    #[cfg(test)]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.local_addr(),
+            #[cfg(unix)]
+            ChannelStream::Unix(_) => Err(Error::new(
+                ErrorKind::AddrNotAvailable,
+                "Unix domain sockets have no socket address",
+            )),
+        }
+    }
+
+    /// Returns the remote address of a TCP stream, Unix domain sockets have none
+    #[cfg(test)]
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.peer_addr(),
+            #[cfg(unix)]
+            ChannelStream::Unix(_) => Err(Error::new(
+                ErrorKind::AddrNotAvailable,
+                "Unix domain sockets have no socket address",
+            )),
+        }
+    }
+
+    /// Sets `TCP_NODELAY` on TCP streams, Unix domain sockets never delay writes
+    fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.set_nodelay(nodelay),
+            #[cfg(unix)]
+            ChannelStream::Unix(_) => Ok(()),
+        }
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.shutdown(how),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.shutdown(how),
+        }
+    }
+}
+
+impl Read for ChannelStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for ChannelStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.write_vectored(bufs),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.write_vectored(bufs),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+impl event::Source for ChannelStream {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.register(registry, token, interests),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.register(registry, token, interests),
+        }
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.reregister(registry, token, interests),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.reregister(registry, token, interests),
+        }
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        match self {
+            ChannelStream::Tcp(stream) => stream.deregister(registry),
+            #[cfg(unix)]
+            ChannelStream::Unix(stream) => stream.deregister(registry),
+        }
+    }
+}
+
 pub(crate) struct TcpChannel {
-    stream: TcpStream,
+    stream: ChannelStream,
+    /// Encrypts all traffic on the stream, if TLS is enabled
+    #[cfg(feature = "tls")]
+    tls: Option<TlsSession>,
+    /// Data frames, each of which requires one credit to be sent
     outbound_queue: VecDeque<SerialisedFrame>,
+    /// Handshake and flow-control frames, these are sent without credit and ahead of data frames
+    control_queue: VecDeque<SerialisedFrame>,
+    /// Frames which are being written, in order, the first of which may be partially written already
+    in_flight: VecDeque<InFlightFrame>,
+    /// The number of bytes of frames to gather into a single write
+    write_batch_size: usize,
     pub token: Token,
     input_buffer: DecodeBuffer,
     pub state: ChannelState,
     pub messages: u32,
     own_addr: SocketAddr,
+    /// The incarnation of our system, announced in the Hello or Start
+    incarnation: Uuid,
+    /// The incarnation of the remote system, received in the Hello or Start
+    remote_incarnation: Uuid,
     nodelay: bool,
+    /// Number of data frames we may still send to the remote host
+    credit: u32,
+    /// The maximum amount of credit we grant the remote host
+    credit_window: u32,
+    credit_update_threshold: u32,
+    /// Number of data frames received since we last granted credit to the remote host
+    consumed_credit: u32,
+    /// Number of data frames the remote host may still send us
+    granted_credit: u32,
+    failure_detector: Option<FailureDetector>,
+    /// Heartbeats answered by the remote host, tracked once the channel is connected
+    heartbeats: Option<HeartbeatHistory>,
+    /// Data frames with payloads of at least this many bytes are compressed, if the remote host supports it
+    compression_threshold: Option<usize>,
+    /// The wire protocol version spoken by both hosts, negotiated during the handshake
+    version: u16,
+    /// The optional features supported by both hosts, negotiated during the handshake
+    capabilities: Capabilities,
+    /// The number of lanes to the remote host, configured locally and negotiated during the handshake
+    lanes: u8,
+    /// Which of the lanes to the remote host this channel is
+    lane: u8,
+    /// The Unix domain socket this host listens on, announced in the Start of channels requested over one
+    unix_path: Option<PathBuf>,
+    /// The remote host must prove knowledge of this secret during the handshake, if set
+    secret: Option<ClusterSecret>,
+    /// Our challenge to the remote host, sent in the Hello or Start
+    local_nonce: [u8; AUTH_NONCE_LEN],
+    /// The challenge of the remote host, received in the Hello or Start
+    remote_nonce: Option<[u8; AUTH_NONCE_LEN]>,
+    /// When the last data frame was enqueued or received, or the channel was created
+    last_data: Instant,
 }
 
 impl TcpChannel {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        stream: TcpStream,
+        stream: ChannelStream,
         token: Token,
         buffer_chunk: BufferChunk,
         state: ChannelState,
         own_addr: SocketAddr,
+        incarnation: Uuid,
         network_config: &NetworkConfig,
     ) -> Self {
         let input_buffer = DecodeBuffer::new(buffer_chunk, network_config.get_buffer_config());
+        let (lanes, unix_path) = if stream.is_unix() {
+            (
+                1,
+                network_config.get_unix_socket_path().map(Path::to_path_buf),
+            )
+        } else {
+            (network_config.get_tcp_lanes(), None)
+        };
         TcpChannel {
             stream,
+            #[cfg(feature = "tls")]
+            tls: None,
             outbound_queue: VecDeque::new(),
+            control_queue: VecDeque::new(),
+            in_flight: VecDeque::new(),
+            write_batch_size: network_config.get_write_batch_size(),
             token,
             input_buffer,
             state,
             messages: 0,
             own_addr,
+            incarnation,
+            remote_incarnation: Uuid::nil(),
             nodelay: network_config.get_tcp_nodelay(),
+            credit: 0,
+            credit_window: network_config.get_credit_window(),
+            credit_update_threshold: network_config.get_credit_update_threshold(),
+            consumed_credit: 0,
+            granted_credit: 0,
+            failure_detector: network_config.get_failure_detector(),
+            heartbeats: None,
+            compression_threshold: network_config.get_compression_threshold(),
+            version: PROTOCOL_VERSION,
+            capabilities: Capabilities::NONE,
+            lanes,
+            lane: 0,
+            unix_path,
+            secret: network_config.get_cluster_secret().cloned(),
+            local_nonce: auth::new_nonce(),
+            remote_nonce: None,
+            last_data: Instant::now(),
         }
     }
 
     /// This is "network unsafe" to use. Please use the other interfaces for reading/writing.
-    pub fn stream_mut(&mut self) -> &mut TcpStream {
+    pub fn stream_mut(&mut self) -> &mut ChannelStream {
         &mut self.stream
     }
 
     #[allow(dead_code)]
-    pub fn stream(&self) -> &TcpStream {
+    pub fn stream(&self) -> &ChannelStream {
         &self.stream
     }
 
+    /// Encrypts all traffic on the stream with the `tls` session.
+    ///
+    /// Must be called before the channel is initialised.
+    #[cfg(feature = "tls")]
+    pub fn set_tls(&mut self, tls: TlsSession) -> () {
+        self.tls = Some(tls);
+    }
+
+    /// Returns `true` if the remote host may use `addr` as its address.
+    ///
+    /// Always `true`, unless TLS with mutual authentication is used.
+    #[cfg(feature = "tls")]
+    fn verify_peer_address(&self, addr: &SocketAddr) -> bool {
+        match self.tls {
+            Some(ref tls) => tls.verify_peer_address(addr),
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the remote host may use `addr` as its address.
+    #[cfg(not(feature = "tls"))]
+    fn verify_peer_address(&self, _addr: &SocketAddr) -> bool {
+        true
+    }
+
+    /// Returns `true` if data frames may be accepted from the remote host.
+    ///
+    /// If authentication is enabled, only once the handshake has completed.
+    pub fn accepts_data(&self) -> bool {
+        self.secret.is_none() || self.connected()
+    }
+
+    /// Returns the incarnation of the remote system, once the handshake has progressed far enough to know it
+    pub fn remote_incarnation(&self) -> Uuid {
+        self.remote_incarnation
+    }
+
+    /// Returns the wire protocol version spoken on this channel, once the handshake has progressed far enough to know it
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns `true` if the remote host predates protocol versioning,
+    /// and thus does not understand flow-control or heartbeat frames.
+    fn legacy(&self) -> bool {
+        self.version == LEGACY_PROTOCOL_VERSION
+    }
+
+    /// Returns which of the lanes to the remote host this channel is, 0 for the first channel
+    pub fn lane(&self) -> u8 {
+        self.lane
+    }
+
+    /// Makes this channel an additional lane to the remote host, must be set before the Start is sent
+    pub fn set_lane(&mut self, lane: u8) -> () {
+        self.lane = lane;
+    }
+
+    /// Returns the number of lanes to the remote host, once the handshake has progressed far enough to know it
+    pub fn lanes(&self) -> u8 {
+        self.lanes
+    }
+
     pub fn connected(&self) -> bool {
         matches!(self.state, ChannelState::Connected(_, _))
     }
@@ -91,7 +402,7 @@ impl TcpChannel {
         let mut bytes = BytesMut::with_capacity(len);
         bytes.truncate(len);
         if let Ok(()) = frame.encode_into(&mut bytes) {
-            self.outbound_queue
+            self.control_queue
                 .push_back(SerialisedFrame::Bytes(bytes.freeze()));
             // If there is a fatal error during a handshake the connection will be re-attempted
             let _ = self.try_drain();
@@ -112,19 +423,131 @@ impl TcpChannel {
     pub fn initialise(&mut self, addr: &SocketAddr) -> () {
         if let ChannelState::Initialising = self.state {
             // We must send enqueue Hello and await reply
-            let hello = Frame::Hello(Hello::new(*addr));
+            let challenge = self.secret.as_ref().map(|_| self.local_nonce);
+            let hello = Frame::Hello(
+                Hello::new(*addr)
+                    .with_capabilities(Capabilities::supported())
+                    .with_incarnation(self.incarnation)
+                    .with_lanes(self.lanes)
+                    .with_challenge(challenge),
+            );
             self.send_frame(hello);
         }
     }
 
     /// Must be called when a Hello frame is received on the channel.
-    pub fn handle_hello(&mut self, hello: Hello) -> () {
+    ///
+    /// Returns an error if the remote host is incompatible, or does not challenge us although authentication is enabled.
+    /// The channel must then be closed.
+    pub fn handle_hello(&mut self, hello: Hello) -> Result<(), HandshakeError> {
         if let ChannelState::Requested(_, id) = self.state {
+            self.negotiate(hello.version, hello.capabilities, hello.lanes)?;
+            self.remote_incarnation = hello.incarnation;
+            let auth = match (&self.secret, hello.challenge) {
+                (Some(secret), Some(challenge)) => {
+                    self.remote_nonce = Some(challenge);
+                    let response =
+                        secret.start_response(&challenge, &self.local_nonce, self.own_addr, id);
+                    Some((self.local_nonce, response))
+                }
+                (Some(_), None) => return Err(HandshakeError::Unauthenticated),
+                (None, _) => None,
+            };
             // Has now received Hello(addr), must send Start(addr, uuid) and await ack
-            let start = Frame::Start(Start::new(self.own_addr, id));
+            let start = Frame::Start(
+                Start::new(self.own_addr, id)
+                    .with_capabilities(Capabilities::supported())
+                    .with_incarnation(self.incarnation)
+                    .with_lanes(self.lanes)
+                    .with_lane(self.lane)
+                    .with_auth(auth)
+                    .with_unix_path(self.unix_path.clone()),
+            );
             self.send_frame(start);
             self.state = ChannelState::Initialised(hello.addr, id);
         }
+        Ok(())
+    }
+
+    /// Must be called when a Start frame is received on the channel, before it is started.
+    ///
+    /// Returns an error if the remote host is incompatible, or fails to authenticate itself.
+    /// The channel must then be closed.
+    pub fn verify_start(&mut self, start: &Start) -> Result<(), HandshakeError> {
+        self.negotiate(start.version, start.capabilities, start.lanes)?;
+        self.remote_incarnation = start.incarnation;
+        self.lane = start.lane;
+        if !self.verify_peer_address(&start.addr) {
+            return Err(HandshakeError::UnverifiedAddress(start.addr));
+        }
+        if self.authenticate_start(start) {
+            Ok(())
+        } else {
+            Err(HandshakeError::Unauthenticated)
+        }
+    }
+
+    /// Checks that the remote host speaks a compatible protocol version, and agrees on the version to use
+    /// and the optional features supported by both hosts.
+    ///
+    /// Both hosts use the smaller of their numbers of lanes.
+    fn negotiate(
+        &mut self,
+        remote_version: u16,
+        remote_capabilities: Capabilities,
+        remote_lanes: u8,
+    ) -> Result<(), HandshakeError> {
+        self.version = negotiate_version(remote_version)
+            .ok_or(HandshakeError::IncompatibleVersion(remote_version))?;
+        self.capabilities = Capabilities::supported().intersection(remote_capabilities);
+        self.lanes = if self.capabilities.contains(Capabilities::LANES) {
+            min(self.lanes, remote_lanes).max(1)
+        } else {
+            1
+        };
+        Ok(())
+    }
+
+    /// Returns `false` if authentication is enabled, but the remote host did not answer our challenge correctly.
+    fn authenticate_start(&mut self, start: &Start) -> bool {
+        let secret = match self.secret {
+            Some(ref secret) => secret,
+            None => return true,
+        };
+        match (&self.state, start.auth) {
+            (ChannelState::Initialising, Some((nonce, response)))
+                if secret.verify_start(
+                    &self.local_nonce,
+                    &nonce,
+                    start.addr,
+                    start.id,
+                    &response,
+                ) =>
+            {
+                self.remote_nonce = Some(nonce);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Must be called when an Ack frame is received on the channel, before it is handled.
+    ///
+    /// Returns an error if authentication is enabled, but the remote host did not answer our challenge correctly.
+    /// The channel must then be closed.
+    pub fn authenticate_ack(&self, ack: &Ack) -> Result<(), HandshakeError> {
+        let authenticated = match (&self.secret, self.remote_nonce, ack.response) {
+            (None, _, _) => true,
+            (Some(secret), Some(challenge), Some(response)) => {
+                secret.verify_ack(&challenge, &self.local_nonce, &response)
+            }
+            _ => false,
+        };
+        if authenticated {
+            Ok(())
+        } else {
+            Err(HandshakeError::Unauthenticated)
+        }
     }
 
     /// Must be called when we Ack the channel. This means that the sender can start using the channel
@@ -132,12 +555,21 @@ impl TcpChannel {
     pub fn handle_start(&mut self, addr: &SocketAddr, id: Uuid) -> () {
         if let ChannelState::Initialising = self.state {
             // Method called because we received Start and want to send Ack.
-            let ack = Frame::Ack(Ack { offset: 0 }); // we don't use offsets yet.
+            let response = match (&self.secret, self.remote_nonce) {
+                (Some(secret), Some(nonce)) => Some(secret.ack_response(&self.local_nonce, &nonce)),
+                _ => None,
+            };
+            let ack = Frame::Ack(Ack {
+                offset: 0, // we don't use offsets yet.
+                response,
+            });
             self.stream
                 .set_nodelay(self.nodelay)
                 .expect("set nodelay failed");
             self.send_frame(ack);
             self.state = ChannelState::Connected(*addr, id);
+            self.start_heartbeats();
+            self.request_credit();
         }
     }
 
@@ -149,6 +581,8 @@ impl TcpChannel {
                 .set_nodelay(self.nodelay)
                 .expect("set nodelay failed");
             self.state = ChannelState::Connected(addr, id);
+            self.start_heartbeats();
+            self.request_credit();
             true
         } else {
             eprintln!("Bad state reached during channel initialisation (handle_ack). Handshake went wrong.\
@@ -157,15 +591,137 @@ impl TcpChannel {
         }
     }
 
+    /// Asks the remote host for credit to start sending data frames.
+    ///
+    /// Legacy hosts neither grant nor expect credit, data frames are sent to them without it.
+    fn request_credit(&mut self) -> () {
+        if self.legacy() {
+            return;
+        }
+        let request = Frame::StreamRequest(StreamRequest::new(self.credit_window));
+        self.send_frame(request);
+    }
+
+    /// Must be called when a StreamRequest frame is received on the channel.
+    /// Grants the remote host its initial credit, at most one full credit window.
+    pub fn handle_stream_request(&mut self, request: StreamRequest) -> () {
+        let credit = min(request.credit_capacity, self.credit_window);
+        self.granted_credit = self.granted_credit.saturating_add(credit);
+        self.send_frame(Frame::CreditUpdate(CreditUpdate::new(credit)));
+    }
+
+    /// Must be called when a CreditUpdate frame is received on the channel.
+    /// The caller should try to drain the channel afterwards, as queued data may now be sent.
+    pub fn handle_credit_update(&mut self, update: CreditUpdate) -> () {
+        self.credit = self.credit.saturating_add(update.credit);
+    }
+
+    /// Must be called for every data frame received on the channel.
+    ///
+    /// Returns `false` if the remote host sent more data frames than we granted it credit for,
+    /// in which case the channel should be closed.
+    pub fn consume_credit(&mut self) -> bool {
+        self.last_data = Instant::now();
+        if self.legacy() {
+            return true;
+        }
+        if self.granted_credit == 0 {
+            return false;
+        }
+        self.granted_credit -= 1;
+        self.consumed_credit += 1;
+        true
+    }
+
+    /// Returns `true` if enough credit has been consumed to grant a `CreditUpdate` to the remote host.
+    pub fn has_credit_to_grant(&self) -> bool {
+        !self.legacy() && self.consumed_credit >= self.credit_update_threshold
+    }
+
+    /// Grants all consumed credit back to the remote host.
+    pub fn grant_credit(&mut self) -> () {
+        let credit = std::mem::replace(&mut self.consumed_credit, 0);
+        self.granted_credit = self.granted_credit.saturating_add(credit);
+        self.send_frame(Frame::CreditUpdate(CreditUpdate::new(credit)));
+    }
+
+    /// Starts tracking the heartbeats of the remote host, if failure detection is enabled.
+    ///
+    /// Legacy hosts do not answer heartbeats, and are never tracked.
+    fn start_heartbeats(&mut self) -> () {
+        if self.legacy() {
+            return;
+        }
+        self.heartbeats = self
+            .failure_detector
+            .as_ref()
+            .map(|detector| HeartbeatHistory::new(detector, Instant::now()));
+    }
+
+    /// Sends a heartbeat, which the remote host must answer, unless it is a legacy host.
+    pub fn send_heartbeat(&mut self) -> () {
+        if !self.legacy() {
+            self.send_frame(Frame::Heartbeat());
+        }
+    }
+
+    /// Must be called when a Heartbeat frame is received on the channel.
+    pub fn handle_heartbeat(&mut self) -> () {
+        self.send_frame(Frame::HeartbeatAck());
+    }
+
+    /// Must be called when a HeartbeatAck frame is received on the channel.
+    pub fn handle_heartbeat_ack(&mut self, now: Instant) -> () {
+        if let (Some(detector), Some(heartbeats)) = (&self.failure_detector, &mut self.heartbeats) {
+            heartbeats.heartbeat(detector, now);
+        }
+    }
+
+    /// Re-evaluates whether the remote host should be suspected at `now`.
+    ///
+    /// Returns `Some(true)` if it just became suspected, `Some(false)` if it is no longer suspected,
+    /// and `None` if nothing changed.
+    pub fn update_suspicion(&mut self, now: Instant) -> Option<bool> {
+        match (&self.failure_detector, &mut self.heartbeats) {
+            (Some(detector), Some(heartbeats)) => heartbeats.update_suspicion(detector, now),
+            _ => None,
+        }
+    }
+
     pub fn swap_buffer(&mut self, new_buffer: &mut BufferChunk) -> () {
         self.input_buffer.swap_buffer(new_buffer);
     }
 
+    /// Returns the number of data frames which have not been sent yet.
+    #[cfg(test)]
+    pub fn pending_outbound(&self) -> usize {
+        self.outbound_queue.len()
+    }
+
+    /// Returns the number of frames which are being written.
+    #[cfg(test)]
+    pub fn pending_in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Returns the number of data frames we may still send to the remote host.
+    #[cfg(test)]
+    pub fn available_credit(&self) -> u32 {
+        self.credit
+    }
+
+    /// Takes all data frames which have not been completely written yet, in order,
+    /// such that they can be sent on another channel.
+    ///
+    /// Pending control frames only make sense on this channel and are discarded.
     pub fn take_outbound(&mut self) -> Vec<SerialisedFrame> {
-        let mut ret = Vec::new();
-        while let Some(frame) = self.outbound_queue.pop_front() {
-            ret.push(frame);
-        }
+        self.control_queue.clear();
+        let mut ret: Vec<SerialisedFrame> = self
+            .in_flight
+            .drain(..)
+            .filter_map(InFlightFrame::into_data_frame)
+            .collect();
+        ret.extend(self.outbound_queue.drain(..));
         ret
     }
 
@@ -181,7 +737,14 @@ impl TcpChannel {
                 read_bytes = 0;
             }
             if let Some(mut buf) = self.input_buffer.get_writeable() {
-                match self.stream.read(&mut buf) {
+                #[cfg(feature = "tls")]
+                let result = match self.tls {
+                    Some(ref mut tls) => tls.read(&mut self.stream, buf),
+                    None => self.stream.read(&mut buf),
+                };
+                #[cfg(not(feature = "tls"))]
+                let result = self.stream.read(&mut buf);
+                match result {
                     Ok(0) => {
                         return Ok(sum_read_bytes);
                     }
@@ -210,14 +773,27 @@ impl TcpChannel {
         }
     }
 
-    pub fn graceful_shutdown(&mut self) -> () {
-        let mut bye = Frame::Bye();
+    /// Returns `true` if all frames enqueued on this channel have been written to the stream
+    pub fn drained(&self) -> bool {
+        self.outbound_queue.is_empty() && self.control_queue.is_empty() && self.in_flight.is_empty()
+    }
+
+    /// Says Bye for `reason`, and shuts the channel down
+    ///
+    /// Remote hosts which do not support reasons are told that this host left.
+    pub fn graceful_shutdown(&mut self, reason: ByeReason) -> () {
+        let reason = if self.capabilities.contains(Capabilities::BYE_REASON) {
+            reason
+        } else {
+            ByeReason::Left
+        };
+        let mut bye = Frame::Bye(Bye::new(reason));
         let mut bye_bytes = BytesMut::with_capacity(128);
         let len = bye.encoded_len() + FRAME_HEAD_LEN as usize;
         bye_bytes.truncate(len);
         //hello_bytes.extend_from_slice(&[0;hello.encoded_len()]);
         if let Ok(()) = bye.encode_into(&mut bye_bytes) {
-            self.outbound_queue
+            self.control_queue
                 .push_back(SerialisedFrame::Bytes(bye_bytes.freeze()));
             let _ = self.try_drain(); // Try to drain outgoing
             let _ = self.receive(); // Try to drain incoming
@@ -230,6 +806,10 @@ impl TcpChannel {
     /// Returns `true` if this channel was not in [ChannelState::Connected](ChannelState::Connected)
     /// `true` means that it can safely be dropped.
     pub fn shutdown(&mut self) -> bool {
+        #[cfg(feature = "tls")]
+        if let Some(ref mut tls) = self.tls {
+            tls.close(&mut self.stream);
+        }
         let _ = self.stream.shutdown(Both); // Discard errors while closing channels for now...
         match self.state {
             ChannelState::Connected(addr, id) => {
@@ -255,49 +835,84 @@ impl TcpChannel {
     /// Enquing to a non-connected channel is disallowed.
     pub fn enqueue_serialised(&mut self, serialized: SerialisedFrame) -> () {
         self.outbound_queue.push_back(serialized);
+        self.last_data = Instant::now();
+    }
+
+    /// Returns `true` if all frames have been sent, and no data frames have been sent or received
+    /// on the channel for `timeout` at `now`.
+    pub fn is_idle(&self, now: Instant, timeout: Duration) -> bool {
+        self.drained() && now.saturating_duration_since(self.last_data) >= timeout
+    }
+
+    /// Picks the next frame to write: control frames first,
+    /// and then data frames for as long as the remote host has granted us credit, or at all for legacy hosts.
+    ///
+    /// Data frames are compressed right before they are written, if they are large enough
+    /// and the remote host supports compression, such that rejected frames are never compressed.
+    fn next_outbound(&mut self) -> Option<InFlightFrame> {
+        if let Some(frame) = self.control_queue.pop_front() {
+            return Some(InFlightFrame::control(frame));
+        }
+        if self.credit > 0 || self.legacy() {
+            if let Some(mut frame) = self.outbound_queue.pop_front() {
+                self.credit = self.credit.saturating_sub(1);
+                let compressed = match self.compression_threshold {
+                    Some(threshold) if self.capabilities.contains(Capabilities::LZ4) => {
+                        compression::compress_frame(&mut frame, threshold)
+                    }
+                    _ => None,
+                };
+                return Some(match compressed {
+                    Some(compressed) => InFlightFrame::compressed(compressed, frame),
+                    None => InFlightFrame::data(frame),
+                });
+            }
+        }
+        None
     }
 
-    /// Tries to drain the outbound buffer into
+    /// Tries to drain the outbound buffer into the stream.
+    ///
+    /// Pending frames are gathered into vectored writes of up to the
+    /// [write batch size](crate::prelude::NetworkConfig::set_write_batch_size) each.
     pub fn try_drain(&mut self) -> io::Result<usize> {
         let mut sent_bytes: usize = 0;
         let mut interrupts = 0;
-        while let Some(mut serialized_frame) = self.outbound_queue.pop_front() {
-            match self.write_serialized(&serialized_frame) {
+        #[cfg(feature = "tls")]
+        if let Some(ref mut tls) = self.tls {
+            // Previously encrypted data, or handshake messages, must be written first
+            match tls.flush(&mut self.stream) {
+                Ok(_) => (),
+                Err(ref err) if would_block(err) => return Ok(sent_bytes),
+                Err(err) => return Err(err),
+            }
+        }
+        loop {
+            self.fill_in_flight();
+            if self.in_flight.is_empty() {
+                return Ok(sent_bytes);
+            }
+            match self.write_in_flight() {
+                Ok(0) => {
+                    // Nothing was written although there were bytes to write, the stream is closed
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write frames to the stream",
+                    ));
+                }
                 Ok(n) => {
                     sent_bytes += n;
-                    match &mut serialized_frame {
-                        // Split the data and continue sending the rest later if we sent less than the full frame
-                        SerialisedFrame::Bytes(bytes) => {
-                            if n < bytes.len() {
-                                let _ = bytes.split_to(n); // Discard the already sent split off part.
-                                self.outbound_queue.push_front(serialized_frame);
-                            }
-                        }
-                        SerialisedFrame::ChunkLease(chunk) => {
-                            if n < chunk.remaining() {
-                                chunk.advance(n);
-                                self.outbound_queue.push_front(serialized_frame);
-                            }
-                        }
-                        SerialisedFrame::ChunkRef(chunk) => {
-                            if n < chunk.remaining() {
-                                chunk.advance(n);
-                                self.outbound_queue.push_front(serialized_frame);
-                            }
-                        }
-                    }
-                    // Continue looping for the next message
+                    // Discard the written frames and continue sending the rest later
+                    self.advance_in_flight(n);
                 }
                 // Would block "errors" are the OS's way of saying that the
                 // connection is not actually ready to perform this I/O operation.
                 Err(ref err) if would_block(err) => {
-                    // re-insert the data at the front of the buffer and return
-                    self.outbound_queue.push_front(serialized_frame);
+                    // keep the frames in flight and return
                     return Ok(sent_bytes);
                 }
                 Err(err) if interrupted(&err) => {
-                    // re-insert the data at the front of the buffer
-                    self.outbound_queue.push_front(serialized_frame);
+                    // keep the frames in flight and retry
                     interrupts += 1;
                     if interrupts >= MAX_INTERRUPTS {
                         return Err(err);
@@ -305,21 +920,63 @@ impl TcpChannel {
                 }
                 // Other errors we'll consider fatal.
                 Err(err) => {
-                    self.outbound_queue.push_front(serialized_frame);
                     return Err(err);
                 }
             }
         }
-        Ok(sent_bytes)
+    }
+
+    /// Moves the frames to write next into `in_flight`, until they add up to the write batch size.
+    fn fill_in_flight(&mut self) -> () {
+        let mut batched: usize = self.in_flight.iter().map(InFlightFrame::remaining).sum();
+        while batched < self.write_batch_size && self.in_flight.len() < MAX_WRITE_SLICES {
+            match self.next_outbound() {
+                Some(frame) => {
+                    batched += frame.remaining();
+                    self.in_flight.push_back(frame);
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Marks the first `written` bytes of the frames in flight as written,
+    /// and discards the frames which have been written completely.
+    fn advance_in_flight(&mut self, mut written: usize) -> () {
+        while let Some(frame) = self.in_flight.front_mut() {
+            let remaining = frame.remaining();
+            if written >= remaining {
+                written -= remaining;
+                self.in_flight.pop_front();
+            } else {
+                frame.written += written;
+                return;
+            }
+        }
     }
 
     /// No direct writing allowed, Must use other interface.
-    fn write_serialized(&mut self, serialized: &SerialisedFrame) -> io::Result<usize> {
-        match serialized {
-            SerialisedFrame::ChunkLease(chunk) => self.stream.write(chunk.bytes()),
-            SerialisedFrame::Bytes(bytes) => self.stream.write(bytes.bytes()),
-            SerialisedFrame::ChunkRef(chunkref) => self.stream.write(chunkref.bytes()),
+    ///
+    /// Writes the frames in flight with a single vectored write.
+    /// In case of chained frames, only their front can be written,
+    /// so the following frames have to wait for the next write.
+    fn write_in_flight(&mut self) -> io::Result<usize> {
+        if let Some(frame) = self.in_flight.front_mut() {
+            frame.make_writeable();
+        }
+        let mut slices = Vec::with_capacity(self.in_flight.len());
+        for frame in self.in_flight.iter() {
+            let bytes = frame.unwritten();
+            slices.push(IoSlice::new(bytes));
+            if bytes.len() < frame.remaining() {
+                break;
+            }
         }
+        #[cfg(feature = "tls")]
+        if let Some(ref mut tls) = self.tls {
+            return tls.write_vectored(&mut self.stream, &slices);
+        }
+        self.stream.write_vectored(&slices)
     }
 
     /// Destroys the channel and returns the Buffer
@@ -328,6 +985,77 @@ impl TcpChannel {
     }
 }
 
+/// A frame which is being written to the stream.
+///
+/// The frame itself is not advanced while it is written, such that a data frame
+/// can still be handed back intact if the channel closes before it is completely written.
+struct InFlightFrame {
+    frame: SerialisedFrame,
+    /// The uncompressed data frame, if `frame` is its compressed form
+    ///
+    /// Handed back instead of `frame`, as the next channel may not support compression.
+    original: Option<SerialisedFrame>,
+    /// The number of bytes of the frame which have been written already
+    written: usize,
+    /// Data frames consumed credit and are handed back if the channel closes, control frames are discarded
+    data: bool,
+}
+
+impl InFlightFrame {
+    fn control(frame: SerialisedFrame) -> Self {
+        InFlightFrame {
+            frame,
+            original: None,
+            written: 0,
+            data: false,
+        }
+    }
+
+    fn data(frame: SerialisedFrame) -> Self {
+        InFlightFrame {
+            frame,
+            original: None,
+            written: 0,
+            data: true,
+        }
+    }
+
+    /// Writes the `compressed` form of the data frame `original`, which is kept until it has been written
+    fn compressed(compressed: SerialisedFrame, original: SerialisedFrame) -> Self {
+        InFlightFrame {
+            frame: compressed,
+            original: Some(original),
+            written: 0,
+            data: true,
+        }
+    }
+
+    /// Returns the number of bytes which have not been written yet
+    fn remaining(&self) -> usize {
+        self.frame.len() - self.written
+    }
+
+    /// Makes the frame contiguous, if its written bytes reach past the front of a chained frame.
+    fn make_writeable(&mut self) -> () {
+        if self.written > 0 && self.written >= self.frame.bytes().len() {
+            self.frame.make_contiguous();
+        }
+    }
+
+    /// Returns the bytes to write next, in case of chaining only the rest of the front is returned!
+    fn unwritten(&self) -> &[u8] {
+        &self.frame.bytes()[self.written..]
+    }
+
+    fn into_data_frame(self) -> Option<SerialisedFrame> {
+        if self.data {
+            Some(self.original.unwrap_or(self.frame))
+        } else {
+            None
+        }
+    }
+}
+
 impl std::fmt::Debug for TcpChannel {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TcpChannel")
@@ -335,6 +1063,7 @@ impl std::fmt::Debug for TcpChannel {
             .field("Messages", &self.messages)
             .field("Decode Buffer", &self.input_buffer)
             .field("Outbound Queue", &self.outbound_queue.len())
+            .field("Credit", &self.credit)
             .finish()
     }
 }
@@ -0,0 +1,468 @@
+//! Reliable, ordered delivery of messages over UDP
+//!
+//! Each message sent over [reliable UDP](Transport::RUDP) to a remote host is numbered within the
+//! sender's session and sent as a [ReliableData](ReliableData) frame. The receiver delivers the
+//! messages in order, holding back those which overtook earlier ones, and answers every frame
+//! with a [ReliableAck](ReliableAck). The sender retransmits frames which are not acknowledged in time,
+//! and gives up on the remote host after too many retransmissions.
+//! Sessions which neither sent nor received anything for a while are dropped on both sides.
+
+use super::*;
+use crate::dispatch::queue_manager::QueueOverflowPolicy;
+use bytes::Bytes;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Instant,
+};
+
+/// Retransmission timeouts double with each retransmission, up to this many times the initial timeout
+const MAX_BACKOFF_SHIFT: u32 = 3;
+
+/// How long a session without unacknowledged messages is kept by the sender.
+///
+/// Receivers keep sessions twice as long, so that the sender never continues a session
+/// which the receiver has already dropped.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The settings of reliable UDP, taken from the [NetworkConfig](NetworkConfig)
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReliableConfig {
+    pub window: usize,
+    pub retransmit_timeout: Duration,
+    pub max_retransmissions: u32,
+    pub max_pending: usize,
+    pub overflow_policy: QueueOverflowPolicy,
+    pub session_timeout: Duration,
+}
+
+impl ReliableConfig {
+    pub fn from_network_config(network_config: &NetworkConfig) -> Self {
+        ReliableConfig {
+            window: network_config.get_reliable_udp_window(),
+            retransmit_timeout: network_config.get_reliable_udp_retransmit_timeout(),
+            max_retransmissions: network_config.get_reliable_udp_max_retransmissions(),
+            max_pending: network_config.get_reliable_udp_max_pending(),
+            overflow_policy: network_config.get_queue_overflow_policy(),
+            session_timeout: SESSION_TIMEOUT,
+        }
+    }
+}
+
+/// A frame which has been sent, but not been acknowledged yet
+struct InFlight {
+    seq: u64,
+    frame: Bytes,
+    deadline: Instant,
+    retransmissions: u32,
+}
+
+/// The sending side of a reliable UDP session to a remote host
+pub(crate) struct ReliableSender {
+    session: u32,
+    next_seq: u64,
+    in_flight: VecDeque<InFlight>,
+    pending: VecDeque<Bytes>,
+    acknowledged: bool,
+    last_active: Instant,
+}
+
+impl ReliableSender {
+    /// Starts a new session with id `session` at `now`
+    pub fn new(session: u32, now: Instant) -> Self {
+        ReliableSender {
+            session,
+            next_seq: 0,
+            in_flight: VecDeque::new(),
+            pending: VecDeque::new(),
+            acknowledged: false,
+            last_active: now,
+        }
+    }
+
+    /// Queues the serialised message `payload`, without its frame head, to be sent
+    ///
+    /// If the messages waiting for room in the send window exceed the configured maximum,
+    /// returns the message which was dropped according to the overflow policy.
+    pub fn enqueue(&mut self, payload: Bytes, config: &ReliableConfig) -> Option<Bytes> {
+        if self.pending.len() < config.max_pending {
+            self.pending.push_back(payload);
+            return None;
+        }
+        match config.overflow_policy {
+            QueueOverflowPolicy::DropOldest => {
+                let oldest = self.pending.pop_front();
+                self.pending.push_back(payload);
+                oldest
+            }
+            QueueOverflowPolicy::DropNewest | QueueOverflowPolicy::RejectToDeadletter => {
+                Some(payload)
+            }
+        }
+    }
+
+    /// Returns the number of messages which have not been acknowledged yet
+    pub fn unacknowledged(&self) -> usize {
+        self.in_flight.len() + self.pending.len()
+    }
+
+    /// Numbers the queued messages which fit into the send window,
+    /// returning the frames which must be sent at `now`
+    pub fn send(&mut self, config: &ReliableConfig, now: Instant) -> Vec<Bytes> {
+        let window_start = self
+            .in_flight
+            .front()
+            .map_or(self.next_seq, |in_flight| in_flight.seq);
+        let mut frames = Vec::new();
+        while self.next_seq < window_start + config.window as u64 {
+            let payload = match self.pending.pop_front() {
+                Some(payload) => payload,
+                None => break,
+            };
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            let mut buf = BytesMut::with_capacity(
+                FRAME_HEAD_LEN as usize + ReliableData::HEADER_LEN + payload.len(),
+            );
+            FrameHead::new(
+                FrameType::ReliableData,
+                ReliableData::HEADER_LEN + payload.len(),
+            )
+            .encode_into(&mut buf);
+            ReliableData::encode_header(self.session, seq, &mut buf);
+            buf.put_slice(&payload);
+            let frame = buf.freeze();
+            frames.push(frame.clone());
+            self.in_flight.push_back(InFlight {
+                seq,
+                frame,
+                deadline: now + config.retransmit_timeout,
+                retransmissions: 0,
+            });
+            self.last_active = now;
+        }
+        frames
+    }
+
+    /// Removes the frames acknowledged by `ack` at `now` from the send window.
+    ///
+    /// Returns `true` if this is the first acknowledgement of the session.
+    pub fn handle_ack(&mut self, ack: &ReliableAck, now: Instant) -> bool {
+        if ack.session != self.session {
+            // Belongs to an earlier session
+            return false;
+        }
+        self.last_active = now;
+        self.in_flight.retain(|in_flight| {
+            if in_flight.seq < ack.next {
+                return false;
+            }
+            match (in_flight.seq - ack.next).checked_sub(1) {
+                Some(offset) if offset < 64 => ack.received & (1 << offset) == 0,
+                _ => true,
+            }
+        });
+        let first = !self.acknowledged;
+        self.acknowledged = true;
+        first
+    }
+
+    /// Returns the frames whose acknowledgement is overdue at `now`, which must be sent again.
+    ///
+    /// Fails with the payloads of all unacknowledged messages, which are given up on,
+    /// if a frame has already been retransmitted too often.
+    pub fn retransmit(
+        &mut self,
+        config: &ReliableConfig,
+        now: Instant,
+    ) -> Result<Vec<Bytes>, Vec<Bytes>> {
+        let given_up = self.in_flight.iter().any(|in_flight| {
+            in_flight.deadline <= now && in_flight.retransmissions >= config.max_retransmissions
+        });
+        if given_up {
+            return Err(self.drain());
+        }
+        let mut frames = Vec::new();
+        for in_flight in self.in_flight.iter_mut() {
+            if in_flight.deadline > now {
+                continue;
+            }
+            in_flight.retransmissions += 1;
+            let backoff = 1 << in_flight.retransmissions.min(MAX_BACKOFF_SHIFT);
+            in_flight.deadline = now + config.retransmit_timeout * backoff;
+            frames.push(in_flight.frame.clone());
+        }
+        Ok(frames)
+    }
+
+    /// Removes all unacknowledged messages, returning their payloads in order
+    fn drain(&mut self) -> Vec<Bytes> {
+        let head_len = FRAME_HEAD_LEN as usize + ReliableData::HEADER_LEN;
+        self.in_flight
+            .drain(..)
+            .map(|in_flight| in_flight.frame.slice(head_len..))
+            .chain(self.pending.drain(..))
+            .collect()
+    }
+
+    /// Returns `true` if nothing is unacknowledged and nothing was sent or acknowledged
+    /// for the session timeout at `now`, such that the session can be dropped.
+    pub fn is_idle(&self, config: &ReliableConfig, now: Instant) -> bool {
+        self.unacknowledged() == 0 && self.last_active + config.session_timeout <= now
+    }
+
+    /// Returns when the next frame must be retransmitted, if any are unacknowledged,
+    /// or otherwise when the session becomes idle.
+    pub fn next_deadline(&self, config: &ReliableConfig) -> Instant {
+        self.in_flight
+            .iter()
+            .map(|in_flight| in_flight.deadline)
+            .min()
+            .unwrap_or(self.last_active + config.session_timeout)
+    }
+}
+
+/// The receiving side of a reliable UDP session from a remote host
+pub(crate) struct ReliableReceiver {
+    session: u32,
+    next: u64,
+    buffered: BTreeMap<u64, Bytes>,
+    last_active: Instant,
+}
+
+impl ReliableReceiver {
+    /// Joins the session with id `session` of the remote host at `now`
+    pub fn new(session: u32, now: Instant) -> Self {
+        ReliableReceiver {
+            session,
+            next: 0,
+            buffered: BTreeMap::new(),
+            last_active: now,
+        }
+    }
+
+    /// Returns when the session is dropped, unless another frame is received before
+    pub fn expiry(&self, config: &ReliableConfig) -> Instant {
+        self.last_active + config.session_timeout * 2
+    }
+
+    /// Returns the id of the remote host's session
+    pub fn session(&self) -> u32 {
+        self.session
+    }
+
+    /// Accepts the message `payload` with sequence number `seq` at `now`.
+    ///
+    /// Returns the messages which can now be delivered in order, along with the acknowledgement to send.
+    pub fn receive(
+        &mut self,
+        seq: u64,
+        payload: Bytes,
+        config: &ReliableConfig,
+        now: Instant,
+    ) -> (Vec<Bytes>, ReliableAck) {
+        self.last_active = now;
+        let mut deliverable = Vec::new();
+        if seq == self.next {
+            deliverable.push(payload);
+            self.next += 1;
+            while let Some(payload) = self.buffered.remove(&self.next) {
+                deliverable.push(payload);
+                self.next += 1;
+            }
+        } else if seq > self.next && seq < self.next + config.window as u64 {
+            self.buffered.entry(seq).or_insert(payload);
+        }
+        // Anything else is a duplicate or outside of the window, and is sent again if needed
+        (deliverable, self.ack())
+    }
+
+    /// Returns the acknowledgement of all messages received so far
+    fn ack(&self) -> ReliableAck {
+        let received = self
+            .buffered
+            .range(self.next + 1..self.next + 65)
+            .fold(0u64, |received, (seq, _)| {
+                received | (1 << (seq - self.next - 1))
+            });
+        ReliableAck::new(self.session, self.next, received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReliableConfig {
+        ReliableConfig {
+            window: 4,
+            retransmit_timeout: Duration::from_millis(100),
+            max_retransmissions: 2,
+            max_pending: 8,
+            overflow_policy: QueueOverflowPolicy::DropOldest,
+            session_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Extracts the sequence number and payload of the encoded ReliableData `frame`
+    fn decode(mut frame: Bytes) -> (u64, Bytes) {
+        let head = FrameHead::decode_from(&mut frame).expect("frame head");
+        assert_eq!(head.frame_type(), FrameType::ReliableData);
+        assert_eq!(frame.get_u32(), 7);
+        (frame.get_u64(), frame)
+    }
+
+    #[test]
+    fn messages_are_delivered_in_order_despite_loss() {
+        let config = config();
+        let now = Instant::now();
+        let mut sender = ReliableSender::new(7, now);
+        let mut receiver = ReliableReceiver::new(7, now);
+        for i in 0..6u8 {
+            assert!(sender.enqueue(Bytes::from(vec![i]), &config).is_none());
+        }
+        // Only the window is sent at first
+        let frames = sender.send(&config, now);
+        assert_eq!(frames.len(), 4);
+        assert!(sender.send(&config, now).is_empty());
+
+        // The first frame is lost
+        let mut delivered = Vec::new();
+        let mut last_ack = None;
+        for frame in frames.into_iter().skip(1) {
+            let (seq, payload) = decode(frame);
+            let (messages, ack) = receiver.receive(seq, payload, &config, now);
+            delivered.extend(messages);
+            last_ack = Some(ack);
+        }
+        assert!(delivered.is_empty());
+        let ack = last_ack.expect("ack");
+        assert_eq!(ack.next, 0);
+        assert_eq!(ack.received, 0b111);
+        assert!(sender.handle_ack(&ack, now));
+        assert_eq!(sender.unacknowledged(), 3);
+        // The window does not move past the lost frame
+        assert!(sender.send(&config, now).is_empty());
+
+        // Only the lost frame is retransmitted once its timeout expires
+        assert!(sender.retransmit(&config, now).unwrap().is_empty());
+        let later = now + config.retransmit_timeout;
+        let frames = sender.retransmit(&config, later).unwrap();
+        assert_eq!(frames.len(), 1);
+        let (seq, payload) = decode(frames[0].clone());
+        let (messages, ack) = receiver.receive(seq, payload, &config, later);
+        delivered.extend(messages);
+        assert!(!sender.handle_ack(&ack, later));
+
+        for frame in sender.send(&config, later) {
+            let (seq, payload) = decode(frame);
+            let (messages, ack) = receiver.receive(seq, payload, &config, now);
+            delivered.extend(messages);
+            sender.handle_ack(&ack, later);
+        }
+        let delivered: Vec<u8> = delivered.iter().map(|payload| payload[0]).collect();
+        assert_eq!(delivered, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(sender.unacknowledged(), 0);
+        assert_eq!(
+            sender.next_deadline(&config),
+            later + config.session_timeout
+        );
+    }
+
+    #[test]
+    fn sender_gives_up_after_max_retransmissions() {
+        let config = config();
+        let mut now = Instant::now();
+        let config = ReliableConfig {
+            window: 2,
+            ..config
+        };
+        let mut sender = ReliableSender::new(7, now);
+        for payload in [&b"lost"[..], b"unacknowledged", b"pending"].iter() {
+            assert!(sender
+                .enqueue(Bytes::copy_from_slice(payload), &config)
+                .is_none());
+        }
+        assert_eq!(sender.send(&config, now).len(), 2);
+        for _ in 0..config.max_retransmissions {
+            now = sender.next_deadline(&config);
+            assert_eq!(sender.retransmit(&config, now).unwrap().len(), 2);
+        }
+        now = sender.next_deadline(&config);
+        let given_up = sender.retransmit(&config, now).expect_err("give up");
+        assert_eq!(
+            given_up,
+            vec![
+                Bytes::from_static(b"lost"),
+                Bytes::from_static(b"unacknowledged"),
+                Bytes::from_static(b"pending"),
+            ]
+        );
+        assert_eq!(sender.unacknowledged(), 0);
+    }
+
+    #[test]
+    fn duplicates_are_delivered_once() {
+        let config = config();
+        let now = Instant::now();
+        let mut sender = ReliableSender::new(7, now);
+        let mut receiver = ReliableReceiver::new(7, now);
+        for i in 0..3u8 {
+            assert!(sender.enqueue(Bytes::from(vec![i]), &config).is_none());
+        }
+        let frames = sender.send(&config, now);
+        let mut delivered = Vec::new();
+        // The second frame arrives twice before the first one, and the first one twice afterwards
+        for index in [1, 1, 0, 0, 2, 1].iter() {
+            let (seq, payload) = decode(frames[*index].clone());
+            let (messages, ack) = receiver.receive(seq, payload, &config, now);
+            delivered.extend(messages);
+            sender.handle_ack(&ack, now);
+        }
+        let delivered: Vec<u8> = delivered.iter().map(|payload| payload[0]).collect();
+        assert_eq!(delivered, vec![0, 1, 2]);
+        assert_eq!(sender.unacknowledged(), 0);
+    }
+
+    #[test]
+    fn pending_messages_are_bounded() {
+        let config = config();
+        let now = Instant::now();
+        let mut sender = ReliableSender::new(7, now);
+        for i in 0..config.max_pending as u8 {
+            assert!(sender.enqueue(Bytes::from(vec![i]), &config).is_none());
+        }
+        let dropped = sender.enqueue(Bytes::from(vec![100]), &config);
+        assert_eq!(dropped, Some(Bytes::from(vec![0])));
+        let drop_newest = ReliableConfig {
+            overflow_policy: QueueOverflowPolicy::DropNewest,
+            ..config
+        };
+        let dropped = sender.enqueue(Bytes::from(vec![101]), &drop_newest);
+        assert_eq!(dropped, Some(Bytes::from(vec![101])));
+        assert_eq!(sender.unacknowledged(), config.max_pending);
+        // Sending makes room again
+        assert_eq!(sender.send(&config, now).len(), config.window);
+        assert!(sender.enqueue(Bytes::from(vec![102]), &config).is_none());
+    }
+
+    #[test]
+    fn idle_sessions_expire() {
+        let config = config();
+        let now = Instant::now();
+        let mut sender = ReliableSender::new(7, now);
+        let mut receiver = ReliableReceiver::new(7, now);
+        assert!(sender
+            .enqueue(Bytes::from_static(b"data"), &config)
+            .is_none());
+        let frames = sender.send(&config, now);
+        let later = now + config.session_timeout;
+        // Unacknowledged messages keep the session alive
+        assert!(!sender.is_idle(&config, later));
+        let (seq, payload) = decode(frames[0].clone());
+        let (_, ack) = receiver.receive(seq, payload, &config, now);
+        sender.handle_ack(&ack, now);
+        assert_eq!(sender.next_deadline(&config), later);
+        assert!(sender.is_idle(&config, later));
+        assert_eq!(receiver.expiry(&config), now + config.session_timeout * 2);
+    }
+}
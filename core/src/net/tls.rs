@@ -0,0 +1,331 @@
+//! TLS encryption for TCP channels
+
+use super::*;
+use rustls::{
+    server::{AllowAnyAuthenticatedClient, NoClientAuth},
+    Certificate,
+    ClientConfig,
+    ClientConnection,
+    Connection,
+    PrivateKey,
+    RootCertStore,
+    ServerConfig,
+    ServerConnection,
+    ServerName,
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{BufReader, Error, ErrorKind, IoSlice, Read, Write},
+    path::Path,
+};
+
+/// Certificates and keys for encrypting TCP channels with TLS
+///
+/// When set via [NetworkConfig::set_tls_config](NetworkConfig::set_tls_config), every TCP channel
+/// of the [NetworkDispatcher](crate::prelude::NetworkDispatcher) is encrypted.
+/// Remote systems must then use TLS as well, and present a certificate signed by one of the trusted CAs,
+/// which is valid for the IP address of their [SystemPath](crate::prelude::SystemPath).
+///
+/// By default, only the accepting system authenticates itself to the connecting one.
+/// Use [set_mutual_authentication](TlsConfig::set_mutual_authentication) to also require
+/// connecting systems to present a valid certificate for the address they announce.
+///
+/// UDP traffic is not encrypted.
+///
+/// # Example
+///
+/// ```no_run
+/// use kompact::prelude::*;
+///
+/// let mut tls_config = TlsConfig::from_pem_files("node.crt", "node.key", "ca.crt")
+///     .expect("TLS config");
+/// tls_config.set_mutual_authentication(true);
+/// let mut net_config = NetworkConfig::default();
+/// net_config.set_tls_config(tls_config);
+/// let mut conf = KompactConfig::default();
+/// conf.system_components(DeadletterBox::new, net_config.build());
+/// let system = conf.build().expect("system");
+/// # system.shutdown().expect("shutdown");
+/// ```
+#[derive(Clone)]
+pub struct TlsConfig {
+    cert_chain: Vec<Certificate>,
+    private_key: PrivateKey,
+    trusted_cas: Vec<Certificate>,
+    mutual_authentication: bool,
+}
+
+impl TlsConfig {
+    /// Creates a new TLS config from PEM encoded data
+    ///
+    /// - `cert_chain` contains this system's certificate, optionally followed by intermediate certificates.
+    /// - `private_key` contains the private key of this system's certificate, in PKCS#8, PKCS#1 (RSA) or SEC1 (EC) format.
+    /// - `trusted_cas` contains the certificates of the CAs which remote systems' certificates must be signed by.
+    ///
+    /// Returns an error of kind [InvalidInput](std::io::ErrorKind::InvalidInput)
+    /// if any of them can not be parsed, or the key does not match the certificate.
+    pub fn from_pem(cert_chain: &[u8], private_key: &[u8], trusted_cas: &[u8]) -> io::Result<Self> {
+        let cert_chain = read_certs(cert_chain)?;
+        if cert_chain.is_empty() {
+            return Err(invalid_input("no certificate found in cert_chain"));
+        }
+        let trusted_cas = read_certs(trusted_cas)?;
+        if trusted_cas.is_empty() {
+            return Err(invalid_input("no certificate found in trusted_cas"));
+        }
+        let private_key = read_private_key(private_key)?;
+        let config = TlsConfig {
+            cert_chain,
+            private_key,
+            trusted_cas,
+            mutual_authentication: false,
+        };
+        // Make sure the config is usable, so that it can not fail later on the network thread
+        config.build_context()?;
+        Ok(config)
+    }
+
+    /// Creates a new TLS config from PEM encoded files
+    ///
+    /// See [from_pem](TlsConfig::from_pem) for the expected contents of the files.
+    pub fn from_pem_files<P1, P2, P3>(
+        cert_chain: P1,
+        private_key: P2,
+        trusted_cas: P3,
+    ) -> io::Result<Self>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+        P3: AsRef<Path>,
+    {
+        let cert_chain = std::fs::read(cert_chain)?;
+        let private_key = std::fs::read(private_key)?;
+        let trusted_cas = std::fs::read(trusted_cas)?;
+        Self::from_pem(&cert_chain, &private_key, &trusted_cas)
+    }
+
+    /// If set to `true`, connecting systems must authenticate themselves with a certificate
+    /// signed by a trusted CA, which is valid for the IP address of their [SystemPath](crate::prelude::SystemPath).
+    ///
+    /// Connections from systems which fail to do so are closed during the handshake.
+    ///
+    /// Default value is `false`, i.e. only the accepting system is authenticated.
+    pub fn set_mutual_authentication(&mut self, enabled: bool) -> () {
+        self.mutual_authentication = enabled;
+    }
+
+    /// Returns `true` if connecting systems must authenticate themselves.
+    pub fn get_mutual_authentication(&self) -> bool {
+        self.mutual_authentication
+    }
+
+    pub(crate) fn build_context(&self) -> io::Result<TlsContext> {
+        let mut roots = RootCertStore::empty();
+        for ca in self.trusted_cas.iter() {
+            roots
+                .add(ca)
+                .map_err(|e| invalid_input(&format!("invalid CA certificate: {}", e)))?;
+        }
+        let client_verifier = if self.mutual_authentication {
+            AllowAnyAuthenticatedClient::new(roots.clone()).boxed()
+        } else {
+            NoClientAuth::boxed()
+        };
+        let server = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(client_verifier)
+            .with_single_cert(self.cert_chain.clone(), self.private_key.clone())
+            .map_err(|e| invalid_input(&format!("invalid certificate or key: {}", e)))?;
+        let client = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_client_auth_cert(self.cert_chain.clone(), self.private_key.clone())
+            .map_err(|e| invalid_input(&format!("invalid certificate or key: {}", e)))?;
+        Ok(TlsContext {
+            client: Arc::new(client),
+            server: Arc::new(server),
+            mutual_authentication: self.mutual_authentication,
+        })
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the private key
+        f.debug_struct("TlsConfig")
+            .field("cert_chain", &self.cert_chain.len())
+            .field("trusted_cas", &self.trusted_cas.len())
+            .field("mutual_authentication", &self.mutual_authentication)
+            .finish()
+    }
+}
+
+fn invalid_input(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg)
+}
+
+fn read_certs(pem: &[u8]) -> io::Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(pem))
+        .map_err(|e| invalid_input(&format!("invalid PEM certificate: {}", e)))?;
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_private_key(pem: &[u8]) -> io::Result<PrivateKey> {
+    use rustls_pemfile::Item;
+    let mut reader = BufReader::new(pem);
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .map_err(|e| invalid_input(&format!("invalid PEM private key: {}", e)))?
+        {
+            Some(Item::PKCS8Key(key)) | Some(Item::RSAKey(key)) | Some(Item::ECKey(key)) => {
+                return Ok(PrivateKey(key));
+            }
+            Some(_) => (), // skip other items
+            None => return Err(invalid_input("no private key found in private_key")),
+        }
+    }
+}
+
+/// Shared state for creating TLS sessions on the network thread
+pub(crate) struct TlsContext {
+    client: Arc<ClientConfig>,
+    server: Arc<ServerConfig>,
+    mutual_authentication: bool,
+}
+
+impl TlsContext {
+    /// Creates a session for a connection we requested to `remote`,
+    /// which must present a certificate valid for its IP address.
+    pub fn client_session(&self, remote: &SocketAddr) -> io::Result<TlsSession> {
+        let server_name = ServerName::IpAddress(remote.ip());
+        let conn = ClientConnection::new(self.client.clone(), server_name)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        Ok(TlsSession {
+            conn: Connection::from(conn),
+            authenticate_peer: false,
+        })
+    }
+
+    /// Creates a session for a connection we accepted.
+    pub fn server_session(&self) -> io::Result<TlsSession> {
+        let conn = ServerConnection::new(self.server.clone())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        Ok(TlsSession {
+            conn: Connection::from(conn),
+            authenticate_peer: self.mutual_authentication,
+        })
+    }
+}
+
+/// Encrypts and decrypts the traffic of a single non-blocking TCP stream
+pub(crate) struct TlsSession {
+    conn: Connection,
+    /// Whether the address announced by the peer must be covered by its certificate
+    authenticate_peer: bool,
+}
+
+impl TlsSession {
+    /// Reads decrypted data into `buf`, driving the handshake as necessary.
+    ///
+    /// Returns `Ok(0)` if the remote host closed the connection, like a plain TCP stream.
+    /// TLS protocol errors, such as failed certificate verification, are returned
+    /// as errors of kind [ConnectionAborted](std::io::ErrorKind::ConnectionAborted).
+    pub fn read<S: Read + Write>(&mut self, stream: &mut S, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.conn.reader().read(buf) {
+                Ok(n) => return Ok(n),
+                Err(ref err) if would_block(err) => (), // need more data from the stream
+                Err(ref err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(0),
+                Err(err) => return Err(err),
+            }
+            if self.conn.read_tls(stream)? == 0 {
+                return Ok(0);
+            }
+            if let Err(e) = self.conn.process_new_packets() {
+                // Try to let the remote host know why we are closing
+                let _ = self.flush(stream);
+                return Err(Error::new(ErrorKind::ConnectionAborted, e));
+            }
+            // The handshake may require us to respond
+            match self.flush(stream) {
+                Err(ref err) if would_block(err) => (),
+                Err(err) => return Err(err),
+                Ok(_) => (),
+            }
+        }
+    }
+
+    /// Encrypts as much of the slices in `bufs` as possible, in order, and writes it to the `stream`.
+    ///
+    /// Returns the number of bytes of `bufs` which were accepted.
+    /// Encrypted data which could not be written yet is kept and written by later calls
+    /// to this function or [flush](TlsSession::flush).
+    pub fn write_vectored<S: Write>(
+        &mut self,
+        stream: &mut S,
+        bufs: &[IoSlice<'_>],
+    ) -> io::Result<usize> {
+        let mut accepted = 0;
+        for buf in bufs {
+            let n = self.conn.writer().write(buf)?;
+            accepted += n;
+            if n < buf.len() {
+                // Later slices must not overtake the rest of this one
+                break;
+            }
+        }
+        match self.flush(stream) {
+            Err(ref err) if would_block(err) => (),
+            Err(err) => return Err(err),
+            Ok(_) => (),
+        }
+        if accepted == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            // The session's buffer is full until the stream accepts more data
+            Err(ErrorKind::WouldBlock.into())
+        } else {
+            Ok(accepted)
+        }
+    }
+
+    /// Writes any pending encrypted data to the `stream`.
+    pub fn flush<S: Write>(&mut self, stream: &mut S) -> io::Result<usize> {
+        let mut sent_bytes = 0;
+        while self.conn.wants_write() {
+            match self.conn.write_tls(stream)? {
+                0 => return Err(ErrorKind::WriteZero.into()),
+                n => sent_bytes += n,
+            }
+        }
+        Ok(sent_bytes)
+    }
+
+    /// Notifies the remote host that we are closing the session.
+    pub fn close<S: Write>(&mut self, stream: &mut S) -> () {
+        self.conn.send_close_notify();
+        let _ = self.flush(stream);
+    }
+
+    /// Returns `true` if the remote host may use `addr` as its address.
+    ///
+    /// Only checked if mutual authentication is required, in which case the certificate
+    /// presented by the remote host must be valid for the IP address of `addr`.
+    pub fn verify_peer_address(&self, addr: &SocketAddr) -> bool {
+        if !self.authenticate_peer {
+            return true;
+        }
+        let certs = self.conn.peer_certificates();
+        let cert = match certs.and_then(|certs| certs.first()) {
+            Some(cert) => cert,
+            None => return false,
+        };
+        let ip = webpki::IpAddr::from(addr.ip());
+        webpki::EndEntityCert::try_from(cert.0.as_ref())
+            .and_then(|cert| {
+                cert.verify_is_valid_for_subject_name(webpki::SubjectNameRef::IpAddress(
+                    webpki::IpAddrRef::from(&ip),
+                ))
+            })
+            .is_ok()
+    }
+}
@@ -0,0 +1,351 @@
+//! Pluggable transports, which carry the frames of the [NetworkDispatcher](crate::prelude::NetworkDispatcher)
+//!
+//! Every protocol the dispatcher sends frames over is implemented as a [NetworkTransport](NetworkTransport).
+//! The built-in [TCP](TcpTransport) and [UDP](UdpTransport) transports are always available, and custom
+//! transports can be registered for the [Custom](Transport::Custom) transport identifiers with
+//! [register_transport](crate::prelude::NetworkConfig::register_transport).
+//!
+//! Remote systems reached over a custom transport are addressed with paths like
+//! `custom3://127.0.0.1:4000/actor`, where the socket address is only meaningful to the transport.
+//! The dispatcher treats them like TCP hosts: it asks the transport to [connect](NetworkTransport::connect)
+//! before sending the first frame, queues frames until the transport reports the remote host as
+//! [Connected](ConnectionState::Connected), retries failed connections according to its
+//! [RetryStrategy](crate::prelude::RetryStrategy), and sends [rejected](TransportContext::reject)
+//! frames again once reconnected.
+//!
+//! A socket address must not be reached over both TCP and a custom transport by the same system,
+//! as the dispatcher tracks a single connection per remote address.
+//!
+//! # Example
+//!
+//! ```
+//! use kompact::{messaging::SerialisedFrame, net::NetworkBridgeErr, prelude::*};
+//! use std::net::SocketAddr;
+//!
+//! /// A transport which can not reach anybody
+//! struct Void;
+//!
+//! impl NetworkTransport for Void {
+//!     fn start(&mut self, _context: TransportContext) -> std::io::Result<()> {
+//!         Ok(())
+//!     }
+//!
+//!     fn connect(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+//!         Err(NetworkBridgeErr::Other(format!("{} is unreachable", addr)))
+//!     }
+//!
+//!     fn send(&self, addr: SocketAddr, _frame: SerialisedFrame) -> Result<(), NetworkBridgeErr> {
+//!         Err(NetworkBridgeErr::Other(format!("{} is unreachable", addr)))
+//!     }
+//!
+//!     fn stop(&mut self) -> Result<(), NetworkBridgeErr> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut net_config = NetworkConfig::default();
+//! net_config.register_transport(3, || Box::new(Void));
+//! let mut conf = KompactConfig::default();
+//! conf.system_components(DeadletterBox::new, net_config.build());
+//! let system = conf.build().expect("system");
+//! # system.shutdown().expect("shutdown");
+//! ```
+
+use super::*;
+use crate::{
+    messaging::{DispatchEnvelope, EventEnvelope, NetMessage},
+    serialisation::ser_helpers::deserialise_frame,
+};
+use dispatch::lookup::{ActorLookup, LookupResult};
+
+/// A protocol over which the [NetworkDispatcher](crate::prelude::NetworkDispatcher) exchanges frames with remote systems
+///
+/// Transports are created when the dispatcher starts and are driven from its thread,
+/// so they must hand any blocking work off to threads of their own.
+pub trait NetworkTransport: Send {
+    /// Starts the transport, which reports inbound frames and connection events through `context`
+    ///
+    /// Failing to start the transport fails the start of the dispatcher, like failing to bind its socket does.
+    fn start(&mut self, context: TransportContext) -> io::Result<()>;
+
+    /// Starts establishing a connection to the remote system at `addr`
+    ///
+    /// The outcome must be reported with [connection_state](TransportContext::connection_state).
+    /// Connectionless transports can report the remote system as connected right away.
+    fn connect(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr>;
+
+    /// Sends `frame` to the remote system at `addr`
+    ///
+    /// The frame is complete, starting with its frame head,
+    /// and must be handed to [deliver](TransportContext::deliver) unchanged by the remote system.
+    fn send(&self, addr: SocketAddr, frame: SerialisedFrame) -> Result<(), NetworkBridgeErr>;
+
+    /// Gracefully closes the connection to the remote system at `addr`,
+    /// once all frames which were handed to [send](NetworkTransport::send) before have been sent
+    ///
+    /// The closing must be reported with [connection_state](TransportContext::connection_state),
+    /// and the remote system should report it with [peer_left](TransportContext::peer_left),
+    /// such that it does not try to reconnect.
+    ///
+    /// Transports which do not support this fail by default, and their connections are kept.
+    fn leave(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        Err(NetworkBridgeErr::Other(format!(
+            "The transport can not gracefully leave {}",
+            addr
+        )))
+    }
+
+    /// Closes the connection to the remote system at `addr` right away
+    ///
+    /// The closing must be reported with [connection_state](TransportContext::connection_state),
+    /// and frames which were not sent yet should be handed back with [reject](TransportContext::reject).
+    ///
+    /// Transports which do not support this fail by default, and their connections are kept.
+    fn close(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        Err(NetworkBridgeErr::Other(format!(
+            "The transport can not close the connection to {}",
+            addr
+        )))
+    }
+
+    /// Stops the transport, closing all of its connections
+    fn stop(&mut self) -> Result<(), NetworkBridgeErr>;
+}
+
+/// Creates a new instance of a custom transport for each system it is registered with
+pub type TransportFactory = Arc<dyn Fn() -> Box<dyn NetworkTransport> + Send + Sync>;
+
+/// The handle through which a [NetworkTransport](NetworkTransport) reports to its system
+#[derive(Clone)]
+pub struct TransportContext {
+    transport: Transport,
+    local_addr: SocketAddr,
+    lookup: Arc<ArcSwap<ActorStore>>,
+    dispatcher: DispatcherRef,
+    log: KompactLogger,
+}
+
+impl TransportContext {
+    pub(crate) fn new(
+        transport: Transport,
+        local_addr: SocketAddr,
+        lookup: Arc<ArcSwap<ActorStore>>,
+        dispatcher: DispatcherRef,
+        log: KompactLogger,
+    ) -> Self {
+        TransportContext {
+            transport,
+            local_addr,
+            lookup,
+            dispatcher,
+            log,
+        }
+    }
+
+    /// Returns the transport identifier the transport is registered for
+    pub fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// Returns the address the system is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the logger of the system's network bridge
+    pub fn log(&self) -> &KompactLogger {
+        &self.log
+    }
+
+    /// Delivers the `frame` received from a remote system to the actor it is addressed to
+    ///
+    /// # Errors
+    ///
+    /// If the frame can not be deserialised, in which case it is dropped.
+    pub fn deliver(&self, frame: SerialisedFrame) -> Result<(), SerError> {
+        let msg = deserialise_frame(frame)?;
+        deliver_to_lookup(&self.lookup, msg, &self.log);
+        Ok(())
+    }
+
+    /// Reports that the connection to the remote system at `addr` is now in `state`
+    pub fn connection_state(&self, addr: SocketAddr, state: ConnectionState) -> () {
+        self.tell(NetworkEvent::Connection(addr, state));
+    }
+
+    /// Reports that the remote system at `addr` deliberately closed its connection,
+    /// such that the dispatcher does not try to reconnect
+    pub fn peer_left(&self, addr: SocketAddr) -> () {
+        self.tell(NetworkEvent::PeerLeft(addr));
+    }
+
+    /// Hands a `frame` which could not be sent to the remote system at `addr` back to the dispatcher,
+    /// which sends it again once the connection is re-established
+    pub fn reject(&self, addr: SocketAddr, frame: SerialisedFrame) -> () {
+        self.tell(NetworkEvent::RejectedFrame(addr, frame));
+    }
+
+    fn tell(&self, event: NetworkEvent) -> () {
+        self.dispatcher
+            .tell(DispatchEnvelope::Event(EventEnvelope::Network(event)));
+    }
+}
+
+/// Forwards `msg` to the actor it is addressed to in `lookup`, like the network thread does for received frames
+pub(crate) fn deliver_to_lookup(
+    lookup: &ArcSwap<ActorStore>,
+    msg: NetMessage,
+    log: &KompactLogger,
+) -> () {
+    match lookup.load().get_by_actor_path(&msg.receiver) {
+        LookupResult::Ref(actor) => {
+            actor.enqueue(msg);
+        }
+        LookupResult::Group(group) => {
+            group.route(msg, log);
+        }
+        LookupResult::None => {
+            warn!(
+                log,
+                "Could not find actor reference for destination: {:?}, dropping message",
+                msg.receiver
+            );
+        }
+        LookupResult::Err(e) => {
+            error!(
+                log,
+                "An error occurred during local actor lookup for destination: {:?}, dropping message. The error was: {}",
+                msg.receiver,
+                e
+            );
+        }
+    }
+}
+
+/// The built-in TCP transport, which also carries channels over Unix domain sockets
+///
+/// Its channels are driven by the network threads of the bridge.
+pub struct TcpTransport {
+    backend: Arc<BridgeBackend>,
+}
+
+impl TcpTransport {
+    pub(crate) fn new(backend: Arc<BridgeBackend>) -> Self {
+        TcpTransport { backend }
+    }
+}
+
+impl NetworkTransport for TcpTransport {
+    fn start(&mut self, _context: TransportContext) -> io::Result<()> {
+        Ok(()) // The network threads are started by the bridge
+    }
+
+    fn connect(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .thread_for(&addr)?
+                .send_event(events::DispatchEvent::Connect(addr)),
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.connect(addr);
+                Ok(())
+            }
+        }
+    }
+
+    fn send(&self, addr: SocketAddr, frame: SerialisedFrame) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .thread_for(&addr)?
+                .send_event(events::DispatchEvent::SendTCP(addr, frame)),
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.route(addr, frame, Protocol::TCP)
+            }
+        }
+    }
+
+    fn leave(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .thread_for(&addr)?
+                .send_event(events::DispatchEvent::Leave(addr)),
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.leave(addr);
+                Ok(())
+            }
+        }
+    }
+
+    fn close(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => self
+                .backend
+                .thread_for(&addr)?
+                .send_event(events::DispatchEvent::Close(addr)),
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.close(addr);
+                Ok(())
+            }
+        }
+    }
+
+    fn stop(&mut self) -> Result<(), NetworkBridgeErr> {
+        Ok(()) // The network threads are stopped by the bridge
+    }
+}
+
+/// The built-in UDP transport, either best-effort or [reliable](Transport::RUDP)
+///
+/// The UDP socket is driven by the first network thread of the bridge.
+pub struct UdpTransport {
+    backend: Arc<BridgeBackend>,
+    reliable: bool,
+}
+
+impl UdpTransport {
+    pub(crate) fn new(backend: Arc<BridgeBackend>, reliable: bool) -> Self {
+        UdpTransport { backend, reliable }
+    }
+
+    fn protocol(&self) -> Protocol {
+        if self.reliable {
+            Protocol::ReliableUDP
+        } else {
+            Protocol::UDP
+        }
+    }
+}
+
+impl NetworkTransport for UdpTransport {
+    fn start(&mut self, _context: TransportContext) -> io::Result<()> {
+        Ok(()) // The UDP socket is bound by the bridge
+    }
+
+    fn connect(&self, _addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        Err(NetworkBridgeErr::Other(
+            "UDP is connectionless, there is nothing to connect".to_string(),
+        ))
+    }
+
+    fn send(&self, addr: SocketAddr, frame: SerialisedFrame) -> Result<(), NetworkBridgeErr> {
+        match *self.backend {
+            BridgeBackend::Threads(_) => {
+                let event = if self.reliable {
+                    events::DispatchEvent::SendReliableUDP(addr, frame)
+                } else {
+                    events::DispatchEvent::SendUDP(addr, frame)
+                };
+                self.backend.first_thread()?.send_event(event)
+            }
+            BridgeBackend::Memory(ref memory_bridge) => {
+                memory_bridge.route(addr, frame, self.protocol())
+            }
+        }
+    }
+
+    fn stop(&mut self) -> Result<(), NetworkBridgeErr> {
+        Ok(()) // The UDP socket is closed by the bridge
+    }
+}
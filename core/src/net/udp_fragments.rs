@@ -0,0 +1,401 @@
+//! Fragmentation of frames which are too large to be sent in a single UDP datagram
+//!
+//! The sender splits such a frame, including its head, into numbered [Fragment](Fragment) frames,
+//! each of which fits into a single datagram. The receiver collects the fragments per sender
+//! and message id, and restores the original frame once all of them have arrived.
+//! Messages whose fragments do not all arrive within the reassembly timeout are dropped.
+
+use super::*;
+use crate::serialisation::SerError;
+use bytes::Bytes;
+use rustc_hash::FxHashMap;
+use std::time::Instant;
+
+/// Length of the frame head and fields preceding the payload of each fragment
+const FRAGMENT_OVERHEAD: usize = FRAME_HEAD_LEN as usize + Fragment::HEADER_LEN;
+
+/// Splits `frame` into fragments with id `message_id`, which are at most `max_datagram_size` bytes long.
+///
+/// Returns `None` if the frame would need more fragments than can be numbered.
+pub(crate) fn fragment(
+    mut frame: SerialisedFrame,
+    max_datagram_size: usize,
+    message_id: u32,
+) -> Option<Vec<SerialisedFrame>> {
+    assert!(
+        max_datagram_size > FRAGMENT_OVERHEAD,
+        "max_datagram_size must leave room for the fragment payload"
+    );
+    let fragment_len = max_datagram_size - FRAGMENT_OVERHEAD;
+    let count = frame.len().saturating_sub(1) / fragment_len + 1;
+    if count > u16::MAX as usize {
+        return None;
+    }
+    frame.make_contiguous();
+    let fragments = frame
+        .bytes()
+        .chunks(fragment_len)
+        .enumerate()
+        .map(|(index, payload)| {
+            let mut buf = BytesMut::with_capacity(FRAGMENT_OVERHEAD + payload.len());
+            FrameHead::new(FrameType::Fragment, Fragment::HEADER_LEN + payload.len())
+                .encode_into(&mut buf);
+            Fragment::encode_header(message_id, index as u16, count as u16, &mut buf);
+            buf.put_slice(payload);
+            SerialisedFrame::Bytes(buf.freeze())
+        })
+        .collect();
+    Some(fragments)
+}
+
+/// The smallest datagram size accepted by `NetworkConfig::set_max_udp_datagram_size`
+const MIN_DATAGRAM_SIZE: usize = 64;
+
+/// The number of incomplete messages which are buffered per source at most
+const MAX_PARTIALS_PER_SOURCE: usize = 16;
+
+/// The number of maximum sized messages whose fragments are buffered in total at most
+const MAX_BUFFERED_MESSAGES: usize = 4;
+
+/// The fragments of a message which have arrived so far
+struct Partial {
+    fragments: Vec<Option<Bytes>>,
+    missing: usize,
+    len: usize,
+    deadline: Instant,
+}
+
+/// Collects the fragments received over UDP until the messages they belong to are complete
+pub(crate) struct Reassembler {
+    partials: FxHashMap<(SocketAddr, u32), Partial>,
+    partials_per_source: FxHashMap<SocketAddr, usize>,
+    buffered: usize,
+    timeout: Duration,
+    max_message_size: usize,
+    max_buffered: usize,
+}
+
+impl Reassembler {
+    /// Creates a reassembler, which drops messages which are incomplete after `timeout`
+    /// or larger than `max_message_size` bytes.
+    ///
+    /// At most [MAX_PARTIALS_PER_SOURCE](MAX_PARTIALS_PER_SOURCE) incomplete messages are
+    /// buffered per source, and at most [MAX_BUFFERED_MESSAGES](MAX_BUFFERED_MESSAGES) times
+    /// `max_message_size` bytes in total.
+    pub fn new(timeout: Duration, max_message_size: usize) -> Self {
+        Reassembler {
+            partials: FxHashMap::default(),
+            partials_per_source: FxHashMap::default(),
+            buffered: 0,
+            timeout,
+            max_message_size,
+            max_buffered: max_message_size.saturating_mul(MAX_BUFFERED_MESSAGES),
+        }
+    }
+
+    /// Adds the `fragment` received from `source` at `now`.
+    ///
+    /// Returns the original frame, including its head, once all of its fragments have arrived.
+    pub fn insert(
+        &mut self,
+        source: SocketAddr,
+        fragment: Fragment,
+        now: Instant,
+    ) -> Result<Option<Bytes>, SerError> {
+        let key = (source, fragment.message_id);
+        let count = fragment.count as usize;
+        let index = fragment.index as usize;
+        if !self.partials.contains_key(&key) {
+            self.check_new_partial(source, &fragment)?;
+            self.partials.insert(
+                key,
+                Partial {
+                    fragments: vec![None; count],
+                    missing: count,
+                    len: 0,
+                    deadline: now + self.timeout,
+                },
+            );
+            *self.partials_per_source.entry(source).or_insert(0) += 1;
+        }
+        let partial = self.partials.get_mut(&key).expect("partial message");
+        if partial.fragments.len() != count {
+            self.remove(&key);
+            return Err(SerError::InvalidData(format!(
+                "Fragment of message {} claims {} fragments, but earlier ones claimed otherwise",
+                fragment.message_id, count
+            )));
+        }
+        if partial.fragments[index].is_some() {
+            // A duplicated datagram
+            return Ok(None);
+        }
+        let payload_len = fragment.payload.remaining();
+        if partial.len + payload_len > self.max_message_size {
+            self.remove(&key);
+            return Err(SerError::InvalidData(format!(
+                "Fragmented message {} is larger than the maximum of {} bytes",
+                fragment.message_id, self.max_message_size
+            )));
+        }
+        if self.buffered + payload_len > self.max_buffered {
+            self.remove(&key);
+            return Err(SerError::InvalidData(format!(
+                "Dropping fragmented message {}, as {} bytes of incomplete messages are buffered already",
+                fragment.message_id, self.buffered
+            )));
+        }
+        partial.fragments[index] = Some(fragment.payload.create_byte_clone());
+        partial.len += payload_len;
+        partial.missing -= 1;
+        self.buffered += payload_len;
+        if partial.missing > 0 {
+            return Ok(None);
+        }
+        let partial = self.remove(&key).expect("partial message");
+        let mut buf = BytesMut::with_capacity(partial.len);
+        for payload in partial.fragments.into_iter().flatten() {
+            buf.put_slice(&payload);
+        }
+        Ok(Some(buf.freeze()))
+    }
+
+    /// Checks that a message starting with `fragment` may be buffered, before allocating it.
+    fn check_new_partial(&self, source: SocketAddr, fragment: &Fragment) -> Result<(), SerError> {
+        let count = fragment.count as usize;
+        // All fragments but the last one are as large as the sender's fragments get,
+        // and no sender uses fragments smaller than allowed by the minimum datagram size.
+        let fragment_len = if (fragment.index as usize) + 1 < count {
+            fragment.payload.remaining()
+        } else {
+            MIN_DATAGRAM_SIZE - FRAGMENT_OVERHEAD
+        };
+        if (count - 1).saturating_mul(fragment_len) >= self.max_message_size {
+            return Err(SerError::InvalidData(format!(
+                "Fragmented message {} with {} fragments of {} bytes exceeds the maximum of {} bytes",
+                fragment.message_id, count, fragment_len, self.max_message_size
+            )));
+        }
+        let partials = self.partials_per_source.get(&source).copied().unwrap_or(0);
+        if partials >= MAX_PARTIALS_PER_SOURCE {
+            return Err(SerError::InvalidData(format!(
+                "Dropping fragmented message {}, as {} incomplete messages from {} are buffered already",
+                fragment.message_id, partials, source
+            )));
+        }
+        Ok(())
+    }
+
+    /// Removes the message with `key`, releasing its share of the buffer limits.
+    fn remove(&mut self, key: &(SocketAddr, u32)) -> Option<Partial> {
+        let partial = self.partials.remove(key)?;
+        self.buffered -= partial.len;
+        Self::release_source(&mut self.partials_per_source, key.0, 1);
+        Some(partial)
+    }
+
+    fn release_source(
+        partials_per_source: &mut FxHashMap<SocketAddr, usize>,
+        source: SocketAddr,
+        n: usize,
+    ) {
+        if let Some(partials) = partials_per_source.get_mut(&source) {
+            *partials -= n;
+            if *partials == 0 {
+                partials_per_source.remove(&source);
+            }
+        }
+    }
+
+    /// Drops all messages which are still incomplete at `now`,
+    /// returning how many were dropped per source.
+    pub fn expire(&mut self, now: Instant) -> Vec<(SocketAddr, usize)> {
+        let mut expired: FxHashMap<SocketAddr, usize> = FxHashMap::default();
+        let buffered = &mut self.buffered;
+        self.partials.retain(|(source, _), partial| {
+            if partial.deadline <= now {
+                *expired.entry(*source).or_insert(0) += 1;
+                *buffered -= partial.len;
+                false
+            } else {
+                true
+            }
+        });
+        for (source, n) in expired.iter() {
+            Self::release_source(&mut self.partials_per_source, *source, *n);
+        }
+        expired.into_iter().collect()
+    }
+
+    /// Returns when the next incomplete message expires, if there are any.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.partials.values().map(|partial| partial.deadline).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::buffers::{BufferChunk, BufferConfig, DecodeBuffer};
+
+    /// Decodes the fragment `datagram` as the receiving `UdpState` would, and adds it to `reassembler`
+    fn receive(
+        reassembler: &mut Reassembler,
+        source: SocketAddr,
+        datagram: &SerialisedFrame,
+        now: Instant,
+    ) -> Option<Bytes> {
+        try_receive(reassembler, source, datagram, now).expect("valid fragment")
+    }
+
+    fn try_receive(
+        reassembler: &mut Reassembler,
+        source: SocketAddr,
+        datagram: &SerialisedFrame,
+        now: Instant,
+    ) -> Result<Option<Bytes>, SerError> {
+        // The decoded fragment borrows from the buffer, which must outlive its insertion
+        let mut decode_buffer = DecodeBuffer::new(BufferChunk::new(1024), &BufferConfig::default());
+        let writeable = decode_buffer.get_writeable().expect("writeable buffer");
+        writeable[..datagram.len()].copy_from_slice(datagram.bytes());
+        decode_buffer.advance_writeable(datagram.len());
+        match decode_buffer.get_frame() {
+            Ok(Frame::Fragment(fragment)) => reassembler.insert(source, fragment, now),
+            other => panic!("Expected a fragment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fragments_are_reassembled_in_any_order() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let original: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        let fragments = fragment(
+            SerialisedFrame::Bytes(Bytes::from(original.clone())),
+            100,
+            7,
+        )
+        .expect("fragments");
+        assert_eq!(fragments.len(), 13);
+        assert!(fragments.iter().all(|datagram| datagram.len() <= 100));
+
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1 << 20);
+        let mut reassembled = None;
+        for datagram in fragments.iter().rev() {
+            assert!(reassembled.is_none());
+            reassembled = receive(&mut reassembler, addr, datagram, now);
+        }
+        assert_eq!(
+            reassembled.expect("complete message").as_ref(),
+            &original[..]
+        );
+        assert_eq!(reassembler.next_expiry(), None);
+    }
+
+    #[test]
+    fn incomplete_messages_expire() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let timeout = Duration::from_secs(1);
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(timeout, 1 << 20);
+        for message_id in 0..3 {
+            let fragments = fragment(
+                SerialisedFrame::Bytes(Bytes::from(vec![0u8; 500])),
+                100,
+                message_id,
+            )
+            .expect("fragments");
+            assert!(receive(&mut reassembler, addr, &fragments[0], now).is_none());
+        }
+        assert_eq!(reassembler.next_expiry(), Some(now + timeout));
+        assert!(reassembler.expire(now).is_empty());
+        assert_eq!(reassembler.expire(now + timeout), vec![(addr, 3)]);
+        assert_eq!(reassembler.next_expiry(), None);
+    }
+
+    /// Encodes a single fragment with the given header fields
+    fn raw_fragment(message_id: u32, index: u16, count: u16, payload: &[u8]) -> SerialisedFrame {
+        let mut buf = BytesMut::new();
+        FrameHead::new(FrameType::Fragment, Fragment::HEADER_LEN + payload.len())
+            .encode_into(&mut buf);
+        Fragment::encode_header(message_id, index, count, &mut buf);
+        buf.put_slice(payload);
+        SerialisedFrame::Bytes(buf.freeze())
+    }
+
+    #[test]
+    fn implausible_fragment_headers_are_rejected() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1000);
+        // 20 fragments of 100 bytes are more than 1000 bytes, whichever of them arrives first
+        assert!(try_receive(
+            &mut reassembler,
+            addr,
+            &raw_fragment(1, 0, 20, &[0; 100]),
+            now
+        )
+        .is_err());
+        assert!(try_receive(
+            &mut reassembler,
+            addr,
+            &raw_fragment(2, 19, 20, &[0; 10]),
+            now
+        )
+        .is_ok());
+        assert!(try_receive(
+            &mut reassembler,
+            addr,
+            &raw_fragment(3, u16::MAX - 1, u16::MAX, &[0; 10]),
+            now
+        )
+        .is_err());
+        assert_eq!(reassembler.partials.len(), 1);
+    }
+
+    #[test]
+    fn incomplete_messages_per_source_are_limited() {
+        let addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let other: SocketAddr = "127.0.0.1:4001".parse().unwrap();
+        let now = Instant::now();
+        let mut reassembler = Reassembler::new(Duration::from_secs(1), 1 << 20);
+        for message_id in 0..MAX_PARTIALS_PER_SOURCE as u32 {
+            let datagram = raw_fragment(message_id, 0, 2, &[0; 10]);
+            assert!(receive(&mut reassembler, addr, &datagram, now).is_none());
+        }
+        let datagram = raw_fragment(MAX_PARTIALS_PER_SOURCE as u32, 0, 2, &[0; 10]);
+        assert!(try_receive(&mut reassembler, addr, &datagram, now).is_err());
+        assert!(receive(&mut reassembler, other, &datagram, now).is_none());
+        // Completing a message makes room for another one
+        let datagram = raw_fragment(0, 1, 2, &[0; 10]);
+        assert!(receive(&mut reassembler, addr, &datagram, now).is_some());
+        let datagram = raw_fragment(MAX_PARTIALS_PER_SOURCE as u32, 0, 2, &[0; 10]);
+        assert!(receive(&mut reassembler, addr, &datagram, now).is_none());
+    }
+
+    #[test]
+    fn buffered_bytes_are_limited() {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(1);
+        let mut reassembler = Reassembler::new(timeout, 1000);
+        let payload = [0u8; 500];
+        // Spread over sources, so the per source limit is not hit
+        for port in 0..(MAX_BUFFERED_MESSAGES * 2) as u16 {
+            let addr = SocketAddr::new("127.0.0.1".parse().unwrap(), 4000 + port);
+            let datagram = raw_fragment(0, 0, 2, &payload);
+            assert!(receive(&mut reassembler, addr, &datagram, now).is_none());
+        }
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let datagram = raw_fragment(0, 0, 2, &payload);
+        assert!(try_receive(&mut reassembler, addr, &datagram, now).is_err());
+        assert_eq!(reassembler.buffered, MAX_BUFFERED_MESSAGES * 1000);
+        // Expiry releases the buffered bytes
+        assert_eq!(
+            reassembler.expire(now + timeout).len(),
+            MAX_BUFFERED_MESSAGES * 2
+        );
+        assert_eq!(reassembler.buffered, 0);
+        assert!(reassembler.partials_per_source.is_empty());
+        assert!(receive(&mut reassembler, addr, &datagram, now).is_none());
+    }
+}
@@ -1,11 +1,25 @@
 use super::*;
 use crate::{
     messaging::{NetMessage, SerialisedFrame},
-    net::buffers::{BufferChunk, DecodeBuffer},
+    net::{
+        buffers::{BufferChunk, DecodeBuffer},
+        reliable_udp::{ReliableConfig, ReliableReceiver, ReliableSender},
+        udp_fragments::{fragment, Reassembler},
+    },
+    serialisation::ser_helpers::{deserialise_bytes, deserialise_frame},
 };
-use mio::net::UdpSocket;
+use bytes::Bytes;
+use mio::{net::UdpSocket, Token};
 use network_thread::*;
-use std::{cmp::min, collections::VecDeque, io, net::SocketAddr};
+use rustc_hash::FxHashMap;
+use socket2::{Domain, SockAddr, Socket, Type};
+use std::{
+    cmp::min,
+    collections::VecDeque,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Instant,
+};
 
 // Note that this is a theoretical IPv4 limit.
 // This may be violated with IPv6 jumbograms.
@@ -19,6 +33,23 @@ pub(super) struct UdpState {
     input_buffer: DecodeBuffer,
     pub(super) incoming_messages: VecDeque<NetMessage>,
     max_packet_size: usize,
+    max_datagram_size: usize,
+    next_message_id: u32,
+    reassembler: Reassembler,
+    reliable_config: ReliableConfig,
+    reliable_senders: FxHashMap<SocketAddr, ReliableSender>,
+    reliable_receivers: FxHashMap<SocketAddr, ReliableReceiver>,
+    /// Changes in the state of reliable UDP sessions and dropped reliable UDP messages,
+    /// to be reported to the dispatcher
+    pub(super) reliable_events: VecDeque<NetworkEvent>,
+    /// The sockets receiving the datagrams sent to the joined multicast groups, by their poll token
+    multicast_sockets: FxHashMap<Token, MulticastSocket>,
+}
+
+/// A socket which is a member of a multicast group
+struct MulticastSocket {
+    group: SocketAddr,
+    socket: UdpSocket,
 }
 
 impl UdpState {
@@ -31,6 +62,9 @@ impl UdpState {
         // If chunk_size is smaller than MAX_PACKET_SIZE we will use that size as the limit instead.
         let chunk_size = network_config.get_buffer_config().chunk_size;
         let max_packet_size = min(chunk_size, MAX_PACKET_SIZE);
+        let max_datagram_size = min(network_config.get_max_udp_datagram_size(), max_packet_size);
+        // Encoded messages can not be larger than the buffers they are serialised into
+        let max_message_size = chunk_size * network_config.get_buffer_config().max_chunk_count;
         UdpState {
             logger,
             socket,
@@ -38,6 +72,17 @@ impl UdpState {
             input_buffer: DecodeBuffer::new(buffer_chunk, network_config.get_buffer_config()),
             incoming_messages: VecDeque::new(),
             max_packet_size,
+            max_datagram_size,
+            next_message_id: 0,
+            reassembler: Reassembler::new(
+                network_config.get_udp_reassembly_timeout(),
+                max_message_size,
+            ),
+            reliable_config: ReliableConfig::from_network_config(network_config),
+            reliable_senders: FxHashMap::default(),
+            reliable_receivers: FxHashMap::default(),
+            reliable_events: VecDeque::new(),
+            multicast_sockets: FxHashMap::default(),
         }
     }
 
@@ -79,7 +124,8 @@ impl UdpState {
         Ok(sent_bytes)
     }
 
-    pub(super) fn try_read(&mut self) -> io::Result<(usize, IOReturn)> {
+    /// Reads the datagrams received on the main socket, or on the multicast socket with `token`
+    pub(super) fn try_read(&mut self, token: Option<Token>) -> io::Result<(usize, IOReturn)> {
         let mut received_bytes: usize = 0;
         let mut interrupts = 0;
         loop {
@@ -92,7 +138,14 @@ impl UdpState {
                     );
                     return Ok((received_bytes, IOReturn::SwapBuffer));
                 }
-                match self.socket.recv_from(&mut buf) {
+                let socket = match token {
+                    None => &self.socket,
+                    Some(token) => match self.multicast_sockets.get(&token) {
+                        Some(multicast) => &multicast.socket,
+                        None => return Ok((received_bytes, IOReturn::None)),
+                    },
+                };
+                match socket.recv_from(&mut buf) {
                     Ok((0, addr)) => {
                         debug!(self.logger, "Got empty UDP datagram from {}", addr);
                         return Ok((received_bytes, IOReturn::None));
@@ -137,6 +190,25 @@ impl UdpState {
                     }
                 }
             }
+            Ok(Frame::Fragment(fragment)) => {
+                match self.reassembler.insert(source, fragment, Instant::now()) {
+                    Ok(Some(frame)) => self.decode_reassembled(source, frame),
+                    Ok(None) => (),
+                    Err(e) => {
+                        warn!(
+                            self.logger,
+                            "Could not reassemble UDP message from {}: {}", source, e
+                        );
+                    }
+                }
+            }
+            Ok(Frame::ReliableData(data)) => {
+                let payload = data.payload.create_byte_clone();
+                self.receive_reliable(source, data.session, data.seq, payload);
+            }
+            Ok(Frame::ReliableAck(ack)) => {
+                self.handle_reliable_ack(source, ack);
+            }
             Ok(frame) => {
                 warn!(
                     self.logger,
@@ -152,11 +224,308 @@ impl UdpState {
         }
     }
 
+    /// Extracts the message from a data `frame` reassembled from fragments
+    fn decode_reassembled(&mut self, source: SocketAddr, frame: Bytes) {
+        let mut body = frame.clone();
+        match FrameHead::decode_from(&mut body) {
+            Ok(head)
+                if head.frame_type() == FrameType::ReliableData
+                    && body.remaining() >= ReliableData::HEADER_LEN =>
+            {
+                let session = body.get_u32();
+                let seq = body.get_u64();
+                self.receive_reliable(source, session, seq, body);
+            }
+            Ok(head) if head.frame_type() == FrameType::Data => {
+                match deserialise_frame(SerialisedFrame::Bytes(frame)) {
+                    Ok(envelope) => self.incoming_messages.push_back(envelope),
+                    Err(e) => {
+                        warn!(
+                            self.logger,
+                            "Could not deserialise reassembled UDP frame from {}: {}", source, e
+                        );
+                    }
+                }
+            }
+            Ok(head) => {
+                warn!(
+                    self.logger,
+                    "Reassembled unexpected frame from UDP datagrams from {}: {:?}", source, head
+                );
+            }
+            Err(e) => {
+                warn!(
+                    self.logger,
+                    "Could not decode reassembled UDP frame from {}: {:?}", source, e
+                );
+            }
+        }
+    }
+
+    /// Queues `frame` to be sent to `addr`, split into fragments if it does not fit into a single datagram
     pub(super) fn enqueue_serialised(&mut self, addr: SocketAddr, frame: SerialisedFrame) -> () {
-        self.outbound_queue.push_back((addr, frame));
+        if frame.len() <= self.max_datagram_size {
+            self.outbound_queue.push_back((addr, frame));
+            return;
+        }
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+        match fragment(frame, self.max_datagram_size, message_id) {
+            Some(fragments) => self
+                .outbound_queue
+                .extend(fragments.into_iter().map(|fragment| (addr, fragment))),
+            None => {
+                warn!(
+                    self.logger,
+                    "Dropping UDP message to {}, as it is too large to be fragmented.", addr
+                );
+            }
+        }
+    }
+
+    /// Queues the data `frame` to be sent to `addr` over reliable UDP
+    pub(super) fn enqueue_reliable(&mut self, addr: SocketAddr, mut frame: SerialisedFrame) -> () {
+        let head_len = FRAME_HEAD_LEN as usize;
+        if frame.len() < head_len {
+            warn!(
+                self.logger,
+                "Dropping truncated reliable UDP frame to {}", addr
+            );
+            return;
+        }
+        // Unacknowledged messages are kept around for retransmission, so don't hold on to the buffer
+        frame.make_contiguous();
+        let payload = Bytes::copy_from_slice(&frame.bytes()[head_len..]);
+        let now = Instant::now();
+        let dropped = self
+            .reliable_senders
+            .entry(addr)
+            .or_insert_with(|| ReliableSender::new(rand::random(), now))
+            .enqueue(payload, &self.reliable_config);
+        if let Some(dropped) = dropped {
+            self.reliable_events
+                .push_back(NetworkEvent::ReliableUdpOverflow(
+                    addr,
+                    vec![data_frame(dropped)],
+                ));
+        }
+        self.send_reliable(addr);
+    }
+
+    /// Sends the messages to `addr` which fit into the reliable UDP send window
+    fn send_reliable(&mut self, addr: SocketAddr) -> () {
+        let frames = match self.reliable_senders.get_mut(&addr) {
+            Some(sender) => sender.send(&self.reliable_config, Instant::now()),
+            None => return,
+        };
+        for frame in frames {
+            self.enqueue_serialised(addr, SerialisedFrame::Bytes(frame));
+        }
+    }
+
+    fn receive_reliable(&mut self, source: SocketAddr, session: u32, seq: u64, payload: Bytes) {
+        let now = Instant::now();
+        let receiver = self
+            .reliable_receivers
+            .entry(source)
+            .or_insert_with(|| ReliableReceiver::new(session, now));
+        if receiver.session() != session {
+            // The remote host started over, e.g. after giving up on its previous session
+            debug!(
+                self.logger,
+                "Starting reliable UDP session {} from {}", session, source
+            );
+            *receiver = ReliableReceiver::new(session, now);
+        }
+        let (messages, ack) = receiver.receive(seq, payload, &self.reliable_config, now);
+        for payload in messages {
+            match deserialise_bytes(payload) {
+                Ok(envelope) => self.incoming_messages.push_back(envelope),
+                Err(e) => {
+                    warn!(
+                        self.logger,
+                        "Could not deserialise reliable UDP frame from {}: {}", source, e
+                    );
+                }
+            }
+        }
+        let mut ack = Frame::ReliableAck(ack);
+        let mut buf = BytesMut::with_capacity(FRAME_HEAD_LEN as usize + ack.encoded_len());
+        ack.encode_into(&mut buf)
+            .expect("ReliableAck should fit into its buffer");
+        self.outbound_queue
+            .push_back((source, SerialisedFrame::Bytes(buf.freeze())));
+    }
+
+    fn handle_reliable_ack(&mut self, source: SocketAddr, ack: ReliableAck) {
+        let first = match self.reliable_senders.get_mut(&source) {
+            Some(sender) => sender.handle_ack(&ack, Instant::now()),
+            None => return,
+        };
+        if first {
+            self.reliable_events
+                .push_back(NetworkEvent::ReliableUdpConnection(
+                    source,
+                    ConnectionState::Connected(source),
+                ));
+        }
+        // Acknowledged messages make room in the send window
+        self.send_reliable(source);
+    }
+
+    /// Drops the messages which could not be reassembled in time,
+    /// returning how many were dropped per source.
+    pub(super) fn expire_fragments(&mut self, now: Instant) -> Vec<(SocketAddr, usize)> {
+        self.reassembler.expire(now)
+    }
+
+    /// Retransmits the reliable UDP frames whose acknowledgement is overdue at `now`.
+    ///
+    /// Gives up on remote hosts which did not acknowledge a frame after the maximum number of
+    /// retransmissions, handing all unacknowledged messages to them back to the dispatcher.
+    /// Drops the sessions which have been idle for too long.
+    pub(super) fn retransmit_reliable(&mut self, now: Instant) -> () {
+        let mut retransmissions = Vec::new();
+        let mut lost = Vec::new();
+        for (addr, sender) in self.reliable_senders.iter_mut() {
+            match sender.retransmit(&self.reliable_config, now) {
+                Ok(frames) => retransmissions.push((*addr, frames)),
+                Err(dropped) => lost.push((*addr, dropped)),
+            }
+        }
+        for (addr, frames) in retransmissions {
+            for frame in frames {
+                self.enqueue_serialised(addr, SerialisedFrame::Bytes(frame));
+            }
+        }
+        for (addr, dropped) in lost {
+            warn!(
+                self.logger,
+                "Giving up on reliable UDP to {}, dropping {} unacknowledged messages",
+                addr,
+                dropped.len()
+            );
+            self.reliable_senders.remove(&addr);
+            let frames = dropped.into_iter().map(data_frame).collect();
+            self.reliable_events
+                .push_back(NetworkEvent::ReliableUdpDropped(addr, frames));
+            let error = io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Reliable UDP frames were not acknowledged",
+            );
+            self.reliable_events
+                .push_back(NetworkEvent::ReliableUdpConnection(
+                    addr,
+                    ConnectionState::Error(error),
+                ));
+        }
+        let config = &self.reliable_config;
+        self.reliable_senders
+            .retain(|_, sender| !sender.is_idle(config, now));
+        self.reliable_receivers
+            .retain(|_, receiver| receiver.expiry(config) > now);
+    }
+
+    /// Returns when the next incomplete message expires or unacknowledged frame must be retransmitted,
+    /// if there are any.
+    pub(super) fn next_deadline(&self) -> Option<Instant> {
+        let config = &self.reliable_config;
+        self.reliable_senders
+            .values()
+            .map(|sender| sender.next_deadline(config))
+            .chain(
+                self.reliable_receivers
+                    .values()
+                    .map(|receiver| receiver.expiry(config)),
+            )
+            .chain(self.reassembler.next_expiry())
+            .min()
+    }
+
+    /// Joins the multicast `group` on a new socket, which receives the datagrams sent to the group.
+    ///
+    /// Returns the new socket, which must be registered for reading with `token`.
+    pub(super) fn join_multicast(
+        &mut self,
+        group: SocketAddr,
+        token: Token,
+    ) -> io::Result<&mut UdpSocket> {
+        if self.multicast_token(group).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Already a member of multicast group {}", group),
+            ));
+        }
+        let domain = match group {
+            SocketAddr::V4(_) => Domain::IPV4,
+            SocketAddr::V6(_) => Domain::IPV6,
+        };
+        let socket = Socket::new(domain, Type::DGRAM, Some(socket2::Protocol::UDP))?;
+        // Other systems on the same host may be members of the group as well
+        socket.set_reuse_address(true)?;
+        // The BSDs only share multicast addresses between sockets which set both options
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        ))]
+        socket.set_reuse_port(true)?;
+        match group.ip() {
+            IpAddr::V4(ip) => {
+                // Join on the interface the system is bound to
+                let interface = match self.socket.local_addr()?.ip() {
+                    IpAddr::V4(local) => local,
+                    IpAddr::V6(_) => Ipv4Addr::UNSPECIFIED,
+                };
+                socket.join_multicast_v4(&ip, &interface)?;
+            }
+            IpAddr::V6(ip) => socket.join_multicast_v6(&ip, 0)?,
+        }
+        // Bound to the group address, the socket does not receive datagrams sent to other groups
+        socket.bind(&SockAddr::from(group))?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from_std(socket.into());
+        let multicast = self
+            .multicast_sockets
+            .entry(token)
+            .or_insert(MulticastSocket { group, socket });
+        Ok(&mut multicast.socket)
+    }
+
+    /// Leaves the multicast `group`, returning the socket which was a member of it, if any
+    pub(super) fn leave_multicast(&mut self, group: SocketAddr) -> Option<UdpSocket> {
+        let token = self.multicast_token(group)?;
+        self.multicast_sockets
+            .remove(&token)
+            .map(|multicast| multicast.socket)
+    }
+
+    /// Returns the token of the socket which is a member of the multicast `group`, if any
+    fn multicast_token(&self, group: SocketAddr) -> Option<Token> {
+        self.multicast_sockets
+            .iter()
+            .find(|(_, multicast)| multicast.group == group)
+            .map(|(token, _)| *token)
+    }
+
+    /// Returns `true` if `token` belongs to the socket of a joined multicast group
+    pub(super) fn is_multicast(&self, token: Token) -> bool {
+        self.multicast_sockets.contains_key(&token)
     }
 
     pub(super) fn swap_buffer(&mut self, new_buffer: &mut BufferChunk) -> () {
         self.input_buffer.swap_buffer(new_buffer);
     }
 }
+
+/// Wraps the serialised message `payload` of a reliable UDP frame into a data frame,
+/// such that the dispatcher can handle it like any other undelivered frame
+fn data_frame(payload: Bytes) -> SerialisedFrame {
+    let mut buf = BytesMut::with_capacity(FRAME_HEAD_LEN as usize + payload.len());
+    FrameHead::new(FrameType::Data, payload.len()).encode_into(&mut buf);
+    buf.put_slice(&payload);
+    SerialisedFrame::Bytes(buf.freeze())
+}
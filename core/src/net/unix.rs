@@ -0,0 +1,186 @@
+//! Channels over Unix domain sockets, to systems on the same host
+//!
+//! The network threads identify the channels to remote hosts by socket address. A channel requested
+//! over the Unix domain socket of a remote system is therefore keyed by a [synthetic address](unix_socket_key),
+//! derived from the path of the socket, which never belongs to a real host. Remote systems which listen on
+//! a Unix domain socket themselves announce it in the Start of the channels they request, and channels
+//! accepted by the [UnixServer](UnixServer) are keyed by its synthetic address as well. Two systems
+//! requesting channels to each other at the same time thus end up with the same keys on both sides,
+//! and keep only one of the channels, just like for TCP. Channels from systems without a socket
+//! are keyed by the canonical address announced in the Start, just like accepted TCP channels.
+//!
+//! Unix domain sockets are only supported on Unix platforms.
+
+use super::*;
+use crate::net::network_channel::ChannelStream;
+#[cfg(unix)]
+use mio::net::{UnixListener, UnixStream};
+use mio::{Registry, Token};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{
+    net::{IpAddr, Ipv6Addr},
+    path::Path,
+};
+
+/// The first segment of all synthetic addresses, from the unique local IPv6 range
+const UNIX_KEY_PREFIX: u16 = 0xfd75;
+/// The second segment of the synthetic addresses of Unix domain sockets
+const SOCKET_KEY: u16 = 0;
+/// The second segment of the keys of accepted channels, until their Start is received
+const ACCEPTED_KEY: u16 = 1;
+
+/// Returns the synthetic address standing in for the Unix domain socket at `path`
+pub(crate) fn unix_socket_key(path: &Path) -> SocketAddr {
+    let mut hasher = FxHasher::default();
+    path.hash(&mut hasher);
+    synthetic_addr(SOCKET_KEY, hasher.finish())
+}
+
+/// Returns the key of the channel accepted by the [UnixServer](UnixServer) with `token`,
+/// which is used until the remote system announces its canonical address
+pub(crate) fn accepted_unix_key(token: Token) -> SocketAddr {
+    synthetic_addr(ACCEPTED_KEY, token.0 as u64)
+}
+
+fn synthetic_addr(kind: u16, id: u64) -> SocketAddr {
+    let ip = Ipv6Addr::new(
+        UNIX_KEY_PREFIX,
+        kind,
+        0,
+        0,
+        (id >> 48) as u16,
+        (id >> 32) as u16,
+        (id >> 16) as u16,
+        id as u16,
+    );
+    SocketAddr::new(IpAddr::V6(ip), 0)
+}
+
+/// Connects to the Unix domain socket at `path`
+#[cfg(unix)]
+pub(crate) fn connect(path: &Path) -> io::Result<ChannelStream> {
+    UnixStream::connect(path).map(ChannelStream::Unix)
+}
+
+/// Connects to the Unix domain socket at `path`
+#[cfg(not(unix))]
+pub(crate) fn connect(path: &Path) -> io::Result<ChannelStream> {
+    Err(unsupported(path))
+}
+
+#[cfg(not(unix))]
+fn unsupported(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!(
+            "Can not use Unix domain socket {}, as it is not supported on this platform",
+            path.display()
+        ),
+    )
+}
+
+/// A Unix domain socket, on which channels from systems on the same host are accepted
+#[cfg(unix)]
+pub(crate) struct UnixServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+/// A Unix domain socket, on which channels from systems on the same host are accepted
+#[cfg(not(unix))]
+pub(crate) enum UnixServer {}
+
+#[cfg(unix)]
+impl UnixServer {
+    /// Binds to the Unix domain socket at `path` and registers it with `token` for polling.
+    ///
+    /// If a socket file already exists at `path`, which nobody is listening on anymore,
+    /// it is left behind by a system which did not stop cleanly, and is replaced.
+    pub fn bind(
+        path: &Path,
+        registry: &Registry,
+        token: Token,
+        log: &KompactLogger,
+    ) -> io::Result<UnixServer> {
+        let mut listener = match UnixListener::bind(path) {
+            Err(ref e) if e.kind() == io::ErrorKind::AddrInUse => {
+                if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("Unix domain socket {} is in use", path.display()),
+                    ));
+                }
+                debug!(log, "Replacing stale Unix domain socket {}", path.display());
+                std::fs::remove_file(path)?;
+                UnixListener::bind(path)?
+            }
+            result => result?,
+        };
+        registry.register(&mut listener, token, Interest::READABLE)?;
+        Ok(UnixServer {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Accepts the next pending channel, if any
+    pub fn accept(&self) -> io::Result<Option<ChannelStream>> {
+        match self.listener.accept() {
+            Ok((stream, _)) => Ok(Some(ChannelStream::Unix(stream))),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the path of the socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stops listening and removes the socket file
+    pub fn close(mut self, registry: &Registry) -> () {
+        registry.deregister(&mut self.listener).ok();
+        drop(self.listener);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(not(unix))]
+impl UnixServer {
+    pub fn bind(
+        path: &Path,
+        _registry: &Registry,
+        _token: Token,
+        _log: &KompactLogger,
+    ) -> io::Result<UnixServer> {
+        Err(unsupported(path))
+    }
+
+    pub fn accept(&self) -> io::Result<Option<ChannelStream>> {
+        match *self {}
+    }
+
+    pub fn path(&self) -> &Path {
+        match *self {}
+    }
+
+    pub fn close(self, _registry: &Registry) -> () {
+        match self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthetic_addresses_are_distinct() {
+        let a = unix_socket_key(Path::new("/tmp/a.sock"));
+        assert_eq!(a, unix_socket_key(Path::new("/tmp/./a.sock")));
+        assert_ne!(a, unix_socket_key(Path::new("/tmp/b.sock")));
+        assert_ne!(accepted_unix_key(Token(4)), accepted_unix_key(Token(5)));
+        assert_eq!(a.port(), 0);
+        assert!(!a.ip().is_loopback());
+    }
+}
@@ -3,9 +3,14 @@ use super::*;
 #[cfg(all(nightly, feature = "type_erasure"))]
 use crate::utils::erased::CreateErased;
 use crate::{
+    dispatch::network_status_port::NetworkStatusPort,
     messaging::{
+        ConnectionInfo,
+        ConnectionRequest,
+        Departure,
         DispatchEnvelope,
         MsgEnvelope,
+        MulticastMembership,
         PathResolvable,
         RegistrationEnvelope,
         RegistrationError,
@@ -17,7 +22,8 @@ use crate::{
 };
 use hocon::{Hocon, HoconLoader};
 use oncemutex::{OnceMutex, OnceMutexGuard};
-use std::{any::TypeId, fmt, sync::Mutex};
+use std::{any::TypeId, fmt, net::SocketAddr, sync::Mutex};
+use uuid::Uuid;
 
 /// A Kompact system is a collection of components and services
 ///
@@ -950,6 +956,155 @@ impl KompactSystem {
         self.inner.system_path()
     }
 
+    /// Return the incarnation id of this Kompact system
+    ///
+    /// The id is randomly generated for every system that is built,
+    /// so it differs between a system and one that replaces it on the same [SystemPath](SystemPath),
+    /// e.g. after a process restart.
+    /// It is exchanged when connecting to remote systems, to detect that they were restarted.
+    pub fn incarnation(&self) -> Uuid {
+        self.inner.incarnation
+    }
+
+    /// Connect the `required` port to the dispatcher's [NetworkStatusPort](NetworkStatusPort)
+    ///
+    /// Once connected, the owner of `required` receives a [NetworkStatus](crate::prelude::NetworkStatus)
+    /// indication whenever a connection to a remote system is established, lost, or given up on.
+    ///
+    /// See [NetworkStatusPort](NetworkStatusPort) for an example.
+    pub fn connect_network_status_port(
+        &self,
+        required: &mut RequiredPort<NetworkStatusPort>,
+    ) -> () {
+        self.inner.assert_active();
+        self.get_system_components()
+            .connect_network_status_port(required);
+    }
+
+    /// Join the UDP multicast `group`
+    ///
+    /// Once joined, messages sent to a [multicast path](NamedPath::multicast) of the group
+    /// are delivered to the actor registered at that path in this system.
+    /// Groups which should be joined on start-up can be configured via
+    /// [join_multicast_group](crate::prelude::NetworkConfig::join_multicast_group) instead.
+    ///
+    /// Joining happens asynchronously and failures are only logged.
+    ///
+    /// # Panics
+    ///
+    /// If `group` is not a multicast address.
+    pub fn join_multicast_group(&self, group: SocketAddr) -> () {
+        assert!(
+            group.ip().is_multicast(),
+            "{} is not a multicast address",
+            group
+        );
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Multicast(MulticastMembership::Join(
+                group,
+            )));
+    }
+
+    /// Leave the UDP multicast `group`
+    ///
+    /// Messages sent to the group are no longer received by this system afterwards.
+    pub fn leave_multicast_group(&self, group: SocketAddr) -> () {
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Multicast(MulticastMembership::Leave(
+                group,
+            )));
+    }
+
+    /// Gracefully leave the remote system at `remote`
+    ///
+    /// Messages which were sent to the remote system before are still delivered,
+    /// and then the connection is closed. The remote system indicates that this system
+    /// [left](crate::prelude::NetworkStatus::PeerLeft) instead of trying to reconnect,
+    /// and this system indicates that it [left](crate::prelude::NetworkStatus::Left) the remote system.
+    /// Sending another message to the remote system connects to it again.
+    ///
+    /// Leaving happens asynchronously and failures are only logged.
+    pub fn leave_remote(&self, remote: SystemPath) -> () {
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Leave(Departure::Remote(remote)));
+    }
+
+    /// Gracefully leave all remote systems, announcing the departure of this system
+    ///
+    /// See [leave_remote](KompactSystem::leave_remote).
+    pub fn leave_all_remotes(&self) -> () {
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Leave(Departure::All));
+    }
+
+    /// Establish a connection to the remote system at `remote` right away,
+    /// instead of when the first message is sent to it
+    ///
+    /// This can be used to warm up connections, e.g. when the system starts.
+    /// Like for lazily established connections, the outcome is indicated on the
+    /// [NetworkStatusPort](NetworkStatusPort), and failed attempts are retried according to the
+    /// [RetryStrategy](crate::prelude::RetryStrategy).
+    /// Connectionless transports like UDP have nothing to connect.
+    ///
+    /// Connecting happens asynchronously and failures are only logged.
+    pub fn connect_remote(&self, remote: SystemPath) -> () {
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Connection(ConnectionRequest::Connect(
+                remote,
+            )));
+    }
+
+    /// Returns a future with the dispatcher's current connections to remote systems,
+    /// along with the number of messages queued for each of them
+    ///
+    /// Dispatchers without networking have no connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kompact::prelude::*;
+    /// use std::time::Duration;
+    /// let mut cfg = KompactConfig::new();
+    /// cfg.system_components(DeadletterBox::new, NetworkConfig::default().build());
+    /// let system = cfg.build().expect("KompactSystem");
+    /// let connections = system
+    ///     .connections()
+    ///     .wait_timeout(Duration::from_millis(1000))
+    ///     .expect("connections");
+    /// assert!(connections.is_empty());
+    /// # system.shutdown().expect("shutdown");
+    /// ```
+    pub fn connections(&self) -> KFuture<Vec<ConnectionInfo>> {
+        self.inner.assert_active();
+        let (promise, future) = utils::promise();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Connection(ConnectionRequest::List(
+                promise,
+            )));
+        future
+    }
+
+    /// Close the connection to the remote system at `remote` right away
+    ///
+    /// Unlike when [leaving](KompactSystem::leave_remote) the remote system, messages queued for it
+    /// are dropped and no Bye is sent, so this can be used to shed misbehaving peers.
+    /// This system indicates the [closed connection](crate::prelude::NetworkStatus::ConnectionClosed)
+    /// and does not try to reconnect, until another message is sent to the remote system.
+    ///
+    /// Closing happens asynchronously and failures are only logged.
+    pub fn close_connection(&self, remote: SystemPath) -> () {
+        self.inner.assert_active();
+        self.dispatcher_ref()
+            .tell(DispatchEnvelope::Connection(ConnectionRequest::Close(
+                remote,
+            )));
+    }
+
     /// Generate an unique path for the given component
     ///
     /// Produces a unique id [ActorPath](prelude::ActorPath) for `component`
@@ -1494,9 +1649,73 @@ pub trait SystemHandle: Dispatching + CanCancelTimers {
     /// The system path forms a prefix for every [ActorPath](prelude::ActorPath).
     fn system_path(&self) -> SystemPath;
 
+    /// Return the incarnation id of this Kompact system
+    ///
+    /// See [KompactSystem::incarnation](KompactSystem::incarnation).
+    ///
+    /// The default implementation returns `None`, as the handle does not know the incarnation of its system.
+    fn incarnation(&self) -> Option<Uuid> {
+        None
+    }
+
     /// Returns a reference to the system's deadletter box
     fn deadletter_ref(&self) -> ActorRef<Never>;
 
+    /// Join the UDP multicast `group`
+    ///
+    /// See [KompactSystem::join_multicast_group](KompactSystem::join_multicast_group).
+    ///
+    /// The default implementation does nothing, so no multicast messages are received.
+    fn join_multicast_group(&self, _group: SocketAddr) -> () {}
+
+    /// Leave the UDP multicast `group`
+    ///
+    /// See [KompactSystem::leave_multicast_group](KompactSystem::leave_multicast_group).
+    ///
+    /// The default implementation does nothing.
+    fn leave_multicast_group(&self, _group: SocketAddr) -> () {}
+
+    /// Gracefully leave the remote system at `remote`
+    ///
+    /// See [KompactSystem::leave_remote](KompactSystem::leave_remote).
+    ///
+    /// The default implementation does nothing, so no remote system is told that this one left.
+    fn leave_remote(&self, _remote: SystemPath) -> () {}
+
+    /// Gracefully leave all remote systems
+    ///
+    /// See [KompactSystem::leave_all_remotes](KompactSystem::leave_all_remotes).
+    ///
+    /// The default implementation does nothing.
+    fn leave_all_remotes(&self) -> () {}
+
+    /// Establish a connection to the remote system at `remote` right away
+    ///
+    /// See [KompactSystem::connect_remote](KompactSystem::connect_remote).
+    ///
+    /// The default implementation does nothing, so connections are only established on the first message.
+    fn connect_remote(&self, _remote: SystemPath) -> () {}
+
+    /// Returns a future with the dispatcher's current connections to remote systems
+    ///
+    /// See [KompactSystem::connections](KompactSystem::connections).
+    ///
+    /// The default implementation returns a future which is already fulfilled with no connections.
+    fn connections(&self) -> KFuture<Vec<ConnectionInfo>> {
+        let (promise, future) = utils::promise();
+        promise
+            .fulfil(Vec::new())
+            .expect("The future is held right here");
+        future
+    }
+
+    /// Close the connection to the remote system at `remote` right away
+    ///
+    /// See [KompactSystem::close_connection](KompactSystem::close_connection).
+    ///
+    /// The default implementation does nothing.
+    fn close_connection(&self, _remote: SystemPath) -> () {}
+
     /// Run a Future on this system's executor pool and return a handle to the result
     ///
     /// Handles can be awaited like any other future.
@@ -1514,6 +1733,10 @@ pub trait SystemComponents: Send + Sync + 'static {
     fn dispatcher_ref(&self) -> DispatcherRef;
     /// Return a system path for this dispatcher
     fn system_path(&self) -> SystemPath;
+    /// Connect `required` to the dispatcher's [NetworkStatusPort](NetworkStatusPort)
+    ///
+    /// The default implementation does nothing, so `required` never receives any indications.
+    fn connect_network_status_port(&self, _required: &mut RequiredPort<NetworkStatusPort>) -> () {}
     /// Start all the system components
     fn start(&self, _system: &KompactSystem) -> ();
     /// Stop all the system components
@@ -1613,6 +1836,7 @@ struct KompactRuntime {
     internal_components: OnceMutex<Option<InternalComponents>>,
     logger: KompactLogger,
     state: AtomicUsize,
+    incarnation: Uuid,
 }
 
 impl KompactRuntime {
@@ -1630,6 +1854,7 @@ impl KompactRuntime {
             internal_components: OnceMutex::new(None),
             logger,
             state: lifecycle::initial_state(),
+            incarnation: Uuid::new_v4(),
         }
     }
 
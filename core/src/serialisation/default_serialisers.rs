@@ -36,6 +36,9 @@ pub mod serialisation_ids {
     /// Id for a `()` (unit type) serialiser.
     pub const UNIT: SerId = 8;
 
+    /// Id for a [DeliveryFailure](crate::prelude::DeliveryFailure).
+    pub const DELIVERY_FAILURE: SerId = 9;
+
     /// Id for the Serde serialiser
     pub const SERDE: SerId = 19;
 
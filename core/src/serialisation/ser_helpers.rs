@@ -4,14 +4,18 @@
 //! and can be used for custom network implementations.
 use crate::{
     actors::ActorPath,
-    messaging::{HeapOrSer, NetData, NetMessage, Serialised},
+    messaging::{HeapOrSer, NetData, NetMessage, Serialised, SerialisedFrame},
     net::{
         buffers::{BufferEncoder, ChunkLease, ChunkRef},
         frames::{FrameHead, FrameType, FRAME_HEAD_LEN},
     },
     serialisation::*,
 };
-use bytes::{buf::BufMut, BytesMut};
+use bytes::{
+    buf::{Buf, BufMut},
+    Bytes,
+    BytesMut,
+};
 
 /// Creates a new [NetMessage](NetMessage) from the provided fields
 ///
@@ -254,3 +258,37 @@ pub fn deserialise_chunk_ref(mut buffer: ChunkRef) -> Result<NetMessage, SerErro
 
     Ok(envelope)
 }
+
+/// Extracts a [NetMessage](NetMessage) from a complete outgoing frame
+///
+/// This expects the format from [serialise_msg](serialise_msg) or [embed_msg](embed_msg),
+/// including the frame head.
+pub fn deserialise_frame(frame: SerialisedFrame) -> Result<NetMessage, SerError> {
+    if frame.len() < FRAME_HEAD_LEN as usize {
+        return Err(SerError::InvalidData("Frame is too short".into()));
+    }
+    match frame {
+        SerialisedFrame::ChunkLease(mut chunk) => {
+            chunk.advance(FRAME_HEAD_LEN as usize);
+            deserialise_chunk_lease(chunk)
+        }
+        SerialisedFrame::ChunkRef(mut chunk) => {
+            chunk.advance(FRAME_HEAD_LEN as usize);
+            deserialise_chunk_ref(chunk)
+        }
+        SerialisedFrame::Bytes(mut bytes) => {
+            bytes.advance(FRAME_HEAD_LEN as usize);
+            deserialise_bytes(bytes)
+        }
+    }
+}
+
+/// Extracts a [NetMessage](NetMessage) from the provided bytes
+///
+/// This expects the format from [serialise_msg](serialise_msg), without the frame head.
+pub fn deserialise_bytes(mut bytes: Bytes) -> Result<NetMessage, SerError> {
+    let src = ActorPath::deserialise(&mut bytes)?;
+    let dst = ActorPath::deserialise(&mut bytes)?;
+    let ser_id = bytes.get_ser_id();
+    Ok(NetMessage::with_bytes(ser_id, src, dst, bytes))
+}
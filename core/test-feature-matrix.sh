@@ -32,3 +32,8 @@ echo "%%%%%% Testing type_erasure %%%%%%"
 cargo clippy --features type_erasure -- -D warnings
 cargo test --features type_erasure,"$LOG_LEVEL" -- "$@"
 echo "%%%%%% Finished testing type_erasure %%%%%%"
+
+echo "%%%%%% Testing tls %%%%%%"
+cargo clippy --features tls -- -D warnings
+cargo test --features tls,"$LOG_LEVEL" -- "$@"
+echo "%%%%%% Finished testing tls %%%%%%"
@@ -1,5 +1,21 @@
-use kompact::{prelude::*, prelude_test::net_test_helpers::*};
-use std::{net::SocketAddr, thread, time::Duration};
+use kompact::{
+    messaging::SerialisedFrame,
+    net::{ConnectionState, NetworkBridgeErr},
+    prelude::*,
+    prelude_test::net_test_helpers::*,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 fn system_from_network_config(network_config: NetworkConfig) -> KompactSystem {
     let mut cfg = KompactConfig::new();
@@ -526,6 +542,57 @@ fn remote_delivery_to_registered_actors_lazy() {
         .expect("Kompact didn't shut down properly");
 }
 
+#[test]
+// Sets up two KompactSystems and a Pinger which addresses the remote Ponger by
+// the domain name `localhost`, instead of an IP address. They are expected to exchange
+// PING_COUNT ping-pong messages.
+fn remote_delivery_to_domain_path() {
+    let system = system_from_network_config(NetworkConfig::default());
+    let remote = system_from_network_config(NetworkConfig::default());
+
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    let poaf = remote.register_by_alias(&ponger, "custom_name");
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    poaf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+
+    let domain_path: ActorPath = format!(
+        "tcp://localhost:{}/custom_name",
+        remote.system_path().port()
+    )
+    .parse()
+    .expect("Domain path should parse");
+    assert_eq!(domain_path.host().domain(), Some("localhost"));
+
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(domain_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    // TODO maybe we could do this a bit more reliable?
+    thread::sleep(Duration::from_millis(3000));
+
+    let pingf = system.kill_notify(pinger.clone());
+    let pongf = remote.kill_notify(ponger);
+    pingf
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never died!");
+    pongf
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Ponger never died!");
+
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, PING_COUNT);
+    });
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
 #[test]
 // Sets up two KompactSystems with 2x Pingers and Pongers. One Ponger is registered by UUID,
 // the other by a custom name. One Pinger communicates with the UUID-registered Ponger,
@@ -677,18 +744,90 @@ fn remote_lost_and_continued_connection() {
         .expect("Kompact didn't shut down properly");
 }
 
+/// Forwards the indications of a system's [NetworkStatusPort] to a test, which can wait on them
+#[derive(ComponentDefinition)]
+struct StatusRecorder {
+    ctx: ComponentContext<Self>,
+    network_status_port: RequiredPort<NetworkStatusPort>,
+    statuses: Sender<NetworkStatus>,
+}
+
+ignore_lifecycle!(StatusRecorder);
+
+impl Require<NetworkStatusPort> for StatusRecorder {
+    fn handle(&mut self, status: NetworkStatus) -> Handled {
+        let _ = self.statuses.send(status);
+        Handled::Ok
+    }
+}
+
+impl Actor for StatusRecorder {
+    type Message = Never;
+
+    fn receive_local(&mut self, _msg: Self::Message) -> Handled {
+        unimplemented!();
+    }
+
+    fn receive_network(&mut self, _msg: NetMessage) -> Handled {
+        unimplemented!();
+    }
+}
+
+/// Starts recording the network status indications of `system`
+fn record_statuses(system: &KompactSystem) -> Receiver<NetworkStatus> {
+    let (sender, receiver) = mpsc::channel();
+    let recorder = system.create(move || StatusRecorder {
+        ctx: ComponentContext::uninitialised(),
+        network_status_port: RequiredPort::uninitialised(),
+        statuses: sender,
+    });
+    recorder.on_definition(|c| system.connect_network_status_port(&mut c.network_status_port));
+    system
+        .start_notify(&recorder)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("StatusRecorder never started!");
+    receiver
+}
+
+/// Waits until a status matching `expected` is recorded, skipping any others
+fn expect_status<F>(statuses: &Receiver<NetworkStatus>, description: &str, mut expected: F)
+where
+    F: FnMut(&NetworkStatus) -> bool,
+{
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match statuses.recv_timeout(timeout) {
+            Ok(status) if expected(&status) => return,
+            Ok(_) => (),
+            Err(_) => panic!("Timed out waiting for {}", description),
+        }
+    }
+}
+
+/// Waits until `pinger` received all its pongs
+fn expect_pongs(pinger: &Arc<Component<PingerAct>>) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pinger.on_definition(|c| c.count) < PING_COUNT {
+        assert!(Instant::now() < deadline, "Timed out waiting for pongs");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
 #[test]
-// Identical with `remote_lost_and_continued_connection` up to the final sleep time and assertion
-// system1 times out in its reconnection attempts and drops the enqueued buffers.
-// After indirectly asserting that the queue was dropped we start up a new pinger, and assert that it succeeds.
-#[ignore]
+// system1 times out in its reconnection attempts to a remote that left, and drops the enqueued frames.
+// After asserting that the queue was dropped we start up a new pinger, and assert that it succeeds.
 fn remote_lost_and_dropped_connection() {
     let mut net_cfg = NetworkConfig::default();
-    net_cfg.set_max_connection_retry_attempts(2);
-    net_cfg.set_connection_retry_interval(500);
+    net_cfg.set_retry_strategy(RetryStrategy::FixedInterval {
+        interval_ms: 100,
+        num_tries: 2,
+    });
     let system = system_from_network_config(net_cfg);
+    let statuses = record_statuses(&system);
     let remote_a = system_from_network_config(NetworkConfig::default());
     let remote_port = remote_a.system_path().port();
+    let remote = remote_a.system_path();
 
     let (ponger_named, ponf) = remote_a.create_and_register(PongerAct::new_lazy);
     let poaf = remote_a.register_by_alias(&ponger_named, "custom_name");
@@ -707,35 +846,30 @@ fn remote_lost_and_dropped_connection() {
 
     remote_a.start(&ponger_named);
     system.start(&pinger_named);
+    expect_pongs(&pinger_named);
 
-    // Wait for the pingpong
-    thread::sleep(Duration::from_millis(2000));
-
-    // Assert that things are going as we expect
-    let pongfn = remote_a.kill_notify(ponger_named);
-    pongfn
-        .wait_timeout(Duration::from_millis(1000))
-        .expect("Ponger never died!");
-    pinger_named.on_definition(|c| {
-        assert_eq!(c.count, PING_COUNT);
-    });
-    // We now kill system2
+    // The remote says Bye while shutting down
     remote_a.shutdown().ok();
-    // Start a new pinger on system
+    expect_status(&statuses, "the remote to leave", |status| {
+        status == &NetworkStatus::PeerLeft(remote.clone())
+    });
+    // Start a new pinger on system, whose pings are queued up while the remote can not be reached
     let (pinger_named2, pinf2) =
         system.create_and_register(move || PingerAct::new_lazy(named_path));
     pinf2.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
     system.start(&pinger_named2);
-    // Wait for it to send its pings, system should recognize the remote address
-    thread::sleep(Duration::from_millis(1000));
-    // Assert that things are going as they should be, ping count has not increased
-    pinger_named2.on_definition(|c| {
-        assert_eq!(c.count, 0);
+    expect_status(&statuses, "the retries to be exhausted", |status| {
+        status == &NetworkStatus::RetriesExhausted(remote.clone())
     });
-    // Sleep long-enough that the remote connection will be dropped with its queue
-    thread::sleep(Duration::from_millis(
-        3 * 500, // retry config from above
-    ));
+    expect_status(
+        &statuses,
+        "the queue to be dropped",
+        |status| match status {
+            NetworkStatus::QueueDropped(path, dropped) => path == &remote && *dropped > 0,
+            _ => false,
+        },
+    );
+
     // Start up remote_b
     let mut addr: SocketAddr = "127.0.0.1:0".parse().expect("Address should work");
     addr.set_port(remote_port);
@@ -747,23 +881,15 @@ fn remote_lost_and_dropped_connection() {
     poaf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
     remote_b.start(&ponger_named);
 
-    // We give the connection plenty of time to re-establish and transfer it's old queue
-    thread::sleep(Duration::from_millis(5000));
-    // Final assertion, did our systems re-connect without lost messages?
-    pinger_named2.on_definition(|c| {
-        assert_eq!(c.count, 0);
-    });
-
     // This one should now succeed
-    let (pinger_named2, pinf2) =
+    let (pinger_named3, pinf3) =
         system.create_and_register(move || PingerAct::new_lazy(named_path_clone2));
-    pinf2.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
-    system.start(&pinger_named2);
-    // Wait for it to send its pings, system should recognize the remote address
-    thread::sleep(Duration::from_millis(1000));
-    // Assert that things are going as they should be
+    pinf3.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+    system.start(&pinger_named3);
+    expect_pongs(&pinger_named3);
+    // The dropped pings were never delivered
     pinger_named2.on_definition(|c| {
-        assert_eq!(c.count, PING_COUNT);
+        assert_eq!(c.count, 0);
     });
 
     system
@@ -1052,3 +1178,778 @@ fn remote_forwarding_named() {
         .shutdown()
         .expect("Kompact didn't shut down properly");
 }
+
+#[cfg(feature = "tls")]
+fn tls_ca() -> rcgen::Certificate {
+    let mut params = rcgen::CertificateParams::new(Vec::new());
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    rcgen::Certificate::from_params(params).expect("CA certificate")
+}
+
+/// Creates a TLS config with a certificate for 127.0.0.1 signed by `signer`, trusting `trusted_ca`
+#[cfg(feature = "tls")]
+fn loopback_tls_config(signer: &rcgen::Certificate, trusted_ca: &rcgen::Certificate) -> TlsConfig {
+    let mut params = rcgen::CertificateParams::new(Vec::new());
+    params.subject_alt_names = vec![rcgen::SanType::IpAddress("127.0.0.1".parse().unwrap())];
+    let cert = rcgen::Certificate::from_params(params).expect("certificate");
+    TlsConfig::from_pem(
+        cert.serialize_pem_with_signer(signer)
+            .expect("certificate PEM")
+            .as_bytes(),
+        cert.serialize_private_key_pem().as_bytes(),
+        trusted_ca.serialize_pem().expect("CA PEM").as_bytes(),
+    )
+    .expect("TLS config")
+}
+
+#[cfg(feature = "tls")]
+fn tls_network_config(mut tls_config: TlsConfig, mutual_authentication: bool) -> NetworkConfig {
+    tls_config.set_mutual_authentication(mutual_authentication);
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().expect("Address should work"));
+    net_config.set_tls_config(tls_config);
+    net_config
+}
+
+#[cfg(feature = "tls")]
+fn system_from_tls_config(tls_config: TlsConfig, mutual_authentication: bool) -> KompactSystem {
+    system_from_network_config(tls_network_config(tls_config, mutual_authentication))
+}
+
+/// Runs a Pinger on `system` against a Ponger on `remote` and returns the number of pongs received,
+/// once all of them arrived or the pinger gave up waiting for them
+fn ping_pong_count(system: &KompactSystem, remote: &KompactSystem) -> u64 {
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let ponger_path = ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pinger.on_definition(|c| c.count) < PING_COUNT && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    system
+        .kill_notify(pinger.clone())
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never died!");
+    remote
+        .kill_notify(ponger)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Ponger never died!");
+    pinger.on_definition(|c| c.count)
+}
+
+#[test]
+#[cfg(feature = "tls")]
+fn tls_config_rejects_invalid_pem() {
+    let ca = tls_ca();
+    let ca_pem = ca.serialize_pem().expect("CA PEM");
+    let res = TlsConfig::from_pem(b"not a certificate", b"not a key", ca_pem.as_bytes());
+    assert_eq!(
+        res.expect_err("Invalid PEM should be rejected").kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+#[cfg(feature = "tls")]
+// Sets up two KompactSystems communicating over TLS, with certificates signed by the same CA.
+// They are expected to exchange PING_COUNT ping-pong messages,
+// both with and without mutual authentication.
+fn tls_remote_delivery() {
+    let ca = tls_ca();
+    for &mutual_authentication in &[false, true] {
+        let system = system_from_tls_config(loopback_tls_config(&ca, &ca), mutual_authentication);
+        let remote = system_from_tls_config(loopback_tls_config(&ca, &ca), mutual_authentication);
+
+        assert_eq!(ping_pong_count(&system, &remote), PING_COUNT);
+
+        system
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+        remote
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+    }
+}
+
+#[test]
+#[cfg(feature = "tls")]
+// Sets up two KompactSystems requiring mutual TLS authentication, where the connecting
+// system's certificate is signed by a CA which the remote system does not trust.
+// The connection must be rejected until the connecting system gives up, so no pings are delivered.
+fn tls_mutual_authentication_rejects_untrusted_certificate() {
+    let ca = tls_ca();
+    let untrusted_ca = tls_ca();
+    let mut net_config = tls_network_config(loopback_tls_config(&untrusted_ca, &ca), true);
+    net_config.set_retry_strategy(RetryStrategy::FixedInterval {
+        interval_ms: 100,
+        num_tries: 2,
+    });
+    let system = system_from_network_config(net_config);
+    let statuses = record_statuses(&system);
+    let remote = system_from_tls_config(loopback_tls_config(&ca, &ca), true);
+
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let ponger_path = ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    expect_status(&statuses, "the retries to be exhausted", |status| {
+        status == &NetworkStatus::RetriesExhausted(remote.system_path())
+    });
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, 0);
+    });
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+fn system_from_memory_network(network: &MemoryNetwork, addr: SocketAddr) -> KompactSystem {
+    let mut net_config = NetworkConfig::new(addr);
+    net_config.set_memory_network(network.clone());
+    system_from_network_config(net_config)
+}
+
+#[test]
+// Sets up two KompactSystems connected through a MemoryNetwork instead of sockets.
+// They are expected to exchange PING_COUNT ping-pong messages.
+fn memory_remote_delivery() {
+    let network = MemoryNetwork::new();
+    let system = system_from_memory_network(&network, "127.0.0.1:0".parse().unwrap());
+    let remote = system_from_memory_network(&network, "127.0.0.1:0".parse().unwrap());
+    assert_ne!(system.system_path(), remote.system_path());
+
+    assert_eq!(ping_pong_count(&system, &remote), PING_COUNT);
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+#[test]
+// Like `remote_lost_and_continued_connection`, but over a MemoryNetwork.
+// Pings sent while remote_a is down are queued and delivered to remote_b,
+// which is bound to the same virtual address.
+fn memory_lost_and_continued_connection() {
+    let network = MemoryNetwork::new();
+    let mut net_cfg = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_cfg.set_memory_network(network.clone());
+    net_cfg.set_max_connection_retry_attempts(10);
+    net_cfg.set_connection_retry_interval(100);
+    let system = system_from_network_config(net_cfg);
+    let remote_addr: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+    let remote_a = system_from_memory_network(&network, remote_addr);
+
+    assert_eq!(ping_pong_count(&system, &remote_a), PING_COUNT);
+    remote_a.shutdown().ok();
+
+    let named_path = ActorPath::Named(NamedPath::with_system(
+        SystemPath::new(Transport::TCP, remote_addr.ip(), remote_addr.port()),
+        vec!["custom_name".into()],
+    ));
+    let (pinger, pinf) = system.create_and_register(move || PingerAct::new_lazy(named_path));
+    pinf.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+    system.start(&pinger);
+    thread::sleep(Duration::from_millis(200));
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, 0);
+    });
+
+    let remote_b = system_from_memory_network(&network, remote_addr);
+    let (ponger, ponf) = remote_b.create_and_register(PongerAct::new_lazy);
+    let poaf = remote_b.register_by_alias(&ponger, "custom_name");
+    ponf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    poaf.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    remote_b.start(&ponger);
+
+    thread::sleep(Duration::from_millis(2000));
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, PING_COUNT);
+    });
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote_b
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+fn system_with_faults(network: &MemoryNetwork, injector: &FaultInjector) -> KompactSystem {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_config.set_memory_network(network.clone());
+    net_config.set_fault_injector(injector.clone());
+    net_config.set_max_connection_retry_attempts(30);
+    net_config.set_connection_retry_interval(100);
+    system_from_network_config(net_config)
+}
+
+#[test]
+// Sets up two KompactSystems sharing a FaultInjector.
+// Pings are delayed but still complete, and no pings arrive while all frames are dropped.
+fn fault_injection_drops_and_delays() {
+    let network = MemoryNetwork::new();
+    let injector = FaultInjector::with_seed(1);
+    let system = system_with_faults(&network, &injector);
+    let remote = system_with_faults(&network, &injector);
+
+    injector.set_delay(Some(FaultDelay::Uniform {
+        min: Duration::from_millis(1),
+        max: Duration::from_millis(20),
+    }));
+    assert_eq!(ping_pong_count(&system, &remote), PING_COUNT);
+
+    injector.set_delay(None);
+    injector.set_drop_probability(1.0);
+    assert_eq!(ping_pong_count(&system, &remote), 0);
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+#[test]
+// Sets up two KompactSystems sharing a FaultInjector and partitions them.
+// Pings sent during the partition are queued and delivered once it is healed.
+fn fault_injection_partition_and_heal() {
+    let network = MemoryNetwork::new();
+    let injector = FaultInjector::new();
+    let system = system_with_faults(&network, &injector);
+    let remote = system_with_faults(&network, &injector);
+    assert_eq!(ping_pong_count(&system, &remote), PING_COUNT);
+
+    injector.partition("split", &[system.system_path()], &[remote.system_path()]);
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let ponger_path = ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    thread::sleep(Duration::from_millis(500));
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, 0);
+    });
+
+    injector.heal("split");
+    thread::sleep(Duration::from_millis(1000));
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, PING_COUNT);
+    });
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+fn system_with_compression_threshold(threshold: Option<usize>) -> KompactSystem {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_config.set_compression_threshold(threshold);
+    system_from_network_config(net_config)
+}
+
+#[test]
+// Sets up pairs of KompactSystems exchanging large BigPing messages, where either both
+// or only one of the systems compresses messages. The BigPonger validates the messages on reception,
+// so compressed and uncompressed messages must be delivered intact.
+fn compressed_remote_delivery() {
+    for &(threshold, remote_threshold) in
+        &[(Some(64), Some(64)), (Some(64), None), (None, Some(64))]
+    {
+        let system = system_with_compression_threshold(threshold);
+        let remote = system_with_compression_threshold(remote_threshold);
+
+        let (ponger, pof) = remote.create_and_register(BigPongerAct::new_lazy);
+        pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+        let ponger_path =
+            ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+        let (pinger, pif) =
+            system.create_and_register(move || BigPingerAct::new_lazy(ponger_path, 10000));
+        pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+        remote.start(&ponger);
+        system.start(&pinger);
+
+        thread::sleep(Duration::from_millis(3000));
+
+        system
+            .stop_notify(&pinger)
+            .wait_timeout(Duration::from_millis(1000))
+            .expect("Pinger never stopped!");
+        remote
+            .kill_notify(ponger)
+            .wait_timeout(Duration::from_millis(1000))
+            .expect("Ponger never died!");
+        pinger.on_definition(|c| {
+            assert_eq!(c.count, PING_COUNT);
+        });
+
+        system
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+        remote
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+    }
+}
+
+#[test]
+// Sets up two KompactSystems exchanging BigPing messages over UDP, which are many times
+// larger than a single datagram. The BigPonger validates the messages on reception,
+// so the fragments must be reassembled into the original messages.
+fn fragmented_udp_remote_delivery() {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_config.set_max_udp_datagram_size(1024);
+    let system = system_from_network_config(net_config.clone());
+    let remote = system_from_network_config(net_config);
+
+    let (ponger, pof) = remote.create_and_register(BigPongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let mut ponger_path =
+        ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+    ponger_path.via_udp();
+    let (pinger, pif) =
+        system.create_and_register(move || BigPingerAct::new_lazy(ponger_path, 20000));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    thread::sleep(Duration::from_millis(3000));
+
+    system
+        .stop_notify(&pinger)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never stopped!");
+    remote
+        .kill_notify(ponger)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Ponger never died!");
+    pinger.on_definition(|c| {
+        assert_eq!(c.count, PING_COUNT);
+    });
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+fn system_with_cluster_secret(secret: Option<&str>) -> KompactSystem {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    if let Some(secret) = secret {
+        net_config.set_cluster_secret(ClusterSecret::new(secret));
+    }
+    system_from_network_config(net_config)
+}
+
+#[test]
+// Sets up two KompactSystems sharing the same cluster secret.
+// They are expected to exchange PING_COUNT ping-pong messages.
+fn cluster_secret_remote_delivery() {
+    let system = system_with_cluster_secret(Some("secret"));
+    let remote = system_with_cluster_secret(Some("secret"));
+
+    assert_eq!(ping_pong_count(&system, &remote), PING_COUNT);
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+#[test]
+// Sets up pairs of KompactSystems where either the secrets differ or only one system has a secret.
+// The handshake must fail in either direction, so no pings are delivered.
+fn cluster_secret_rejects_unauthenticated() {
+    for &(secret, remote_secret) in &[
+        (Some("secret"), Some("other secret")),
+        (None, Some("secret")),
+        (Some("secret"), None),
+    ] {
+        let system = system_with_cluster_secret(secret);
+        let remote = system_with_cluster_secret(remote_secret);
+
+        assert_eq!(ping_pong_count(&system, &remote), 0);
+
+        system
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+        remote
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+    }
+}
+
+fn system_with_network_threads(threads: usize) -> KompactSystem {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_config.set_network_threads(threads);
+    system_from_network_config(net_config)
+}
+
+fn system_with_tcp_lanes(lanes: u8) -> KompactSystem {
+    let mut net_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    net_config.set_tcp_lanes(lanes);
+    system_from_network_config(net_config)
+}
+
+/// Like ping_pong_count, but with `pairs` pingers and pongers exchanging pings concurrently
+fn concurrent_ping_pong_counts(
+    system: &KompactSystem,
+    remote: &KompactSystem,
+    pairs: usize,
+) -> Vec<u64> {
+    let pairs: Vec<_> = (0..pairs)
+        .map(|_| {
+            let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+            pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+            let ponger_path =
+                ActorPath::Unique(UniquePath::with_system(remote.system_path(), ponger.id()));
+            let (pinger, pif) =
+                system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+            pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+            (pinger, ponger)
+        })
+        .collect();
+    for (pinger, ponger) in pairs.iter() {
+        remote.start(ponger);
+        system.start(pinger);
+    }
+
+    thread::sleep(Duration::from_millis(3000));
+
+    pairs
+        .into_iter()
+        .map(|(pinger, ponger)| {
+            system
+                .kill_notify(pinger.clone())
+                .wait_timeout(Duration::from_millis(1000))
+                .expect("Pinger never died!");
+            remote
+                .kill_notify(ponger)
+                .wait_timeout(Duration::from_millis(1000))
+                .expect("Ponger never died!");
+            pinger.on_definition(|c| c.count)
+        })
+        .collect()
+}
+
+#[test]
+// Sets up two KompactSystems with several TCP lanes between them, and a third one with a single lane.
+// Several pairs of actors exchange PING_COUNT ping-pong messages concurrently over the lanes in both directions,
+// while the third system falls back to a single connection.
+fn tcp_lanes_remote_delivery() {
+    let system = system_with_tcp_lanes(4);
+    let remote = system_with_tcp_lanes(4);
+    let single = system_with_tcp_lanes(1);
+
+    assert_eq!(
+        concurrent_ping_pong_counts(&system, &remote, 4),
+        vec![PING_COUNT; 4]
+    );
+    assert_eq!(
+        concurrent_ping_pong_counts(&remote, &system, 4),
+        vec![PING_COUNT; 4]
+    );
+    assert_eq!(ping_pong_count(&system, &single), PING_COUNT);
+    assert_eq!(ping_pong_count(&single, &remote), PING_COUNT);
+
+    for system in [system, remote, single] {
+        system
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+    }
+}
+
+#[test]
+// Sets up a KompactSystem with several network threads, and several remote systems connecting to it.
+// Channels accepted by its first thread are handed over to other threads,
+// while each pair of systems still exchanges PING_COUNT ping-pong messages in both directions.
+fn multiple_network_threads_remote_delivery() {
+    let system = system_with_network_threads(4);
+    let remotes: Vec<KompactSystem> = (0..3).map(|_| system_with_network_threads(2)).collect();
+
+    for remote in remotes.iter() {
+        assert_eq!(ping_pong_count(remote, &system), PING_COUNT);
+        assert_eq!(ping_pong_count(&system, remote), PING_COUNT);
+    }
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    for remote in remotes {
+        remote
+            .shutdown()
+            .expect("Kompact didn't shut down properly");
+    }
+}
+
+/// Runs a PingerAct on `system` against the PongerActs registered as "ponger" in the multicast `group`,
+/// returning the number of pongs it received.
+fn multicast_ping_pong_count(system: &KompactSystem, group: SocketAddr) -> u64 {
+    let ponger_path = ActorPath::Named(NamedPath::multicast(group, vec!["ponger".into()]));
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    system.start(&pinger);
+    thread::sleep(Duration::from_millis(1000));
+
+    system
+        .kill_notify(pinger.clone())
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never died!");
+    pinger.on_definition(|c| c.count)
+}
+
+#[test]
+// Requires multicast routing on the loopback interface, which many CI hosts and containers lack.
+// Run with `cargo test -- --ignored` where it is available.
+#[ignore]
+// Sets up two KompactSystems in a multicast group, one joining on start-up and one while running,
+// each with a PongerAct registered as "ponger". Every ping sent to the group is answered by both,
+// until one of them leaves the group again.
+fn multicast_remote_delivery() {
+    // Pick a port which is not in use
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")
+        .and_then(|socket| socket.local_addr())
+        .expect("free port")
+        .port();
+    let group = SocketAddr::new("239.255.77.77".parse().unwrap(), port);
+
+    let system = system_from_network_config(NetworkConfig::new("127.0.0.1:0".parse().unwrap()));
+    let mut member_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    member_config.join_multicast_group(group);
+    let member = system_from_network_config(member_config);
+    let late_member =
+        system_from_network_config(NetworkConfig::new("127.0.0.1:0".parse().unwrap()));
+    late_member.join_multicast_group(group);
+
+    let pongers: Vec<_> = [&member, &late_member]
+        .iter()
+        .map(|remote| {
+            let (ponger, _pof) = remote.create_and_register(PongerAct::new_lazy);
+            remote
+                .register_by_alias(&ponger, "ponger")
+                .wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+            remote.start(&ponger);
+            ponger
+        })
+        .collect();
+    // Give the network threads time to join
+    thread::sleep(Duration::from_millis(500));
+
+    // Each of the PING_COUNT pings is answered by both members
+    assert_eq!(multicast_ping_pong_count(&system, group), 2 * PING_COUNT);
+
+    late_member.leave_multicast_group(group);
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(multicast_ping_pong_count(&system, group), PING_COUNT);
+
+    for (remote, ponger) in [&member, &late_member].iter().zip(pongers) {
+        remote
+            .kill_notify(ponger)
+            .wait_timeout(Duration::from_millis(1000))
+            .expect("Ponger never died!");
+    }
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    member
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    late_member
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+}
+
+/// Returns a path for a Unix domain socket in the temporary directory, unique to this test run
+#[cfg(unix)]
+fn unix_socket_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("kompact-{}-{}.sock", std::process::id(), name))
+}
+
+#[test]
+#[cfg(unix)]
+// Sets up two KompactSystems which also listen on Unix domain sockets, one of which replaces
+// a stale socket file. A PingerAct reaching its PongerAct through the socket is expected to
+// receive PING_COUNT pongs, and the socket files are removed once the systems shut down.
+fn unix_socket_remote_delivery() {
+    let system_socket = unix_socket_path("system");
+    let remote_socket = unix_socket_path("remote");
+    // Left behind like by a system which did not shut down cleanly
+    drop(std::os::unix::net::UnixListener::bind(&remote_socket).expect("stale socket"));
+    assert!(remote_socket.exists());
+
+    let mut system_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    system_config.set_unix_socket_path(system_socket.clone());
+    let system = system_from_network_config(system_config);
+    let mut remote_config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+    remote_config.set_unix_socket_path(remote_socket.clone());
+    let remote = system_from_network_config(remote_config);
+
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let ponger_path = ActorPath::Unique(UniquePath::with_system(
+        SystemPath::with_unix_socket(remote_socket.clone()),
+        ponger.id(),
+    ));
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    thread::sleep(Duration::from_millis(3000));
+
+    system
+        .kill_notify(pinger.clone())
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never died!");
+    remote
+        .kill_notify(ponger)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Ponger never died!");
+    assert_eq!(pinger.on_definition(|c| c.count), PING_COUNT);
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    assert!(!system_socket.exists());
+    assert!(!remote_socket.exists());
+}
+
+/// A custom transport between the systems in the same process, which delivers frames directly
+struct InProcessTransport {
+    systems: Arc<Mutex<HashMap<SocketAddr, TransportContext>>>,
+    sent: Arc<AtomicUsize>,
+    context: Option<TransportContext>,
+}
+
+impl NetworkTransport for InProcessTransport {
+    fn start(&mut self, context: TransportContext) -> std::io::Result<()> {
+        self.systems
+            .lock()
+            .unwrap()
+            .insert(context.local_addr(), context.clone());
+        self.context = Some(context);
+        Ok(())
+    }
+
+    fn connect(&self, addr: SocketAddr) -> Result<(), NetworkBridgeErr> {
+        let context = self.context.as_ref().expect("started");
+        if self.systems.lock().unwrap().contains_key(&addr) {
+            context.connection_state(addr, ConnectionState::Connected(addr));
+        } else {
+            context.connection_state(addr, ConnectionState::Closed);
+        }
+        Ok(())
+    }
+
+    fn send(&self, addr: SocketAddr, frame: SerialisedFrame) -> Result<(), NetworkBridgeErr> {
+        let remote = self.systems.lock().unwrap().get(&addr).cloned();
+        match remote {
+            Some(remote) => {
+                self.sent.fetch_add(1, Ordering::SeqCst);
+                remote.deliver(frame)?;
+            }
+            None => self.context.as_ref().expect("started").reject(addr, frame),
+        }
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), NetworkBridgeErr> {
+        if let Some(context) = self.context.take() {
+            self.systems.lock().unwrap().remove(&context.local_addr());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+// Sets up two KompactSystems, which register an in-process custom transport. A PingerAct
+// reaching its PongerAct over the custom transport is expected to receive PING_COUNT pongs,
+// and both the pings and the pongs are carried by the custom transport.
+fn custom_transport_remote_delivery() {
+    let systems = Arc::new(Mutex::new(HashMap::new()));
+    let sent = Arc::new(AtomicUsize::new(0));
+    let network_config = || {
+        let mut config = NetworkConfig::new("127.0.0.1:0".parse().unwrap());
+        let systems = systems.clone();
+        let sent = sent.clone();
+        config.register_transport(1, move || {
+            Box::new(InProcessTransport {
+                systems: systems.clone(),
+                sent: sent.clone(),
+                context: None,
+            })
+        });
+        config
+    };
+    let system = system_from_network_config(network_config());
+    let remote = system_from_network_config(network_config());
+    assert_eq!(systems.lock().unwrap().len(), 2);
+
+    let (ponger, pof) = remote.create_and_register(PongerAct::new_lazy);
+    pof.wait_expect(Duration::from_millis(1000), "Ponger failed to register!");
+    let remote_addr = remote.system_path().socket_addr().expect("socket address");
+    let ponger_path = ActorPath::Unique(UniquePath::with_system(
+        SystemPath::with_socket(Transport::Custom(1), remote_addr),
+        ponger.id(),
+    ));
+    let (pinger, pif) = system.create_and_register(move || PingerAct::new_lazy(ponger_path));
+    pif.wait_expect(Duration::from_millis(1000), "Pinger failed to register!");
+
+    remote.start(&ponger);
+    system.start(&pinger);
+
+    thread::sleep(Duration::from_millis(3000));
+
+    system
+        .kill_notify(pinger.clone())
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Pinger never died!");
+    remote
+        .kill_notify(ponger)
+        .wait_timeout(Duration::from_millis(1000))
+        .expect("Ponger never died!");
+    assert_eq!(pinger.on_definition(|c| c.count), PING_COUNT);
+    assert_eq!(sent.load(Ordering::SeqCst), 2 * PING_COUNT as usize);
+
+    system
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    remote
+        .shutdown()
+        .expect("Kompact didn't shut down properly");
+    assert!(systems.lock().unwrap().is_empty());
+}